@@ -91,7 +91,7 @@ export async function createPropertyListing(
         const dataHash = generatePropertyHash(propertyData);
 
         const propertyIdScVal = StellarSdk.nativeToScVal(propertyId, { type: 'string' });
-        const dataHashScVal = StellarSdk.nativeToScVal(dataHash, { type: 'string' });
+        const dataHashScVal = StellarSdk.nativeToScVal(Buffer.from(dataHash, 'hex'));
         const ownerScVal = StellarSdk.nativeToScVal(StellarSdk.Address.fromString(ownerAddress));
 
         const operation = contract.call(
@@ -158,7 +158,7 @@ export async function updatePropertyListing(
         const dataHash = generatePropertyHash(propertyData);
 
         const propertyIdScVal = StellarSdk.nativeToScVal(propertyId, { type: 'string' });
-        const dataHashScVal = StellarSdk.nativeToScVal(dataHash, { type: 'string' });
+        const dataHashScVal = StellarSdk.nativeToScVal(Buffer.from(dataHash, 'hex'));
         const ownerScVal = StellarSdk.nativeToScVal(StellarSdk.Address.fromString(ownerAddress));
 
         const operation = contract.call(
@@ -298,7 +298,7 @@ export async function getPropertyListing(propertyId: string): Promise<PropertyLi
         if (result) {
           return {
             id: result.id,
-            data_hash: result.data_hash,
+            data_hash: Buffer.from(result.data_hash).toString('hex'),
             owner: result.owner,
             status: result.status,
           };
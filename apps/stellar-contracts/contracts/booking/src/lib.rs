@@ -1,7 +1,8 @@
 #![no_std]
 
 use soroban_sdk::{
-    contract, contractimpl, contracttype, symbol_short, vec, Address, Env, String, Symbol, Vec,
+    contract, contractclient, contracterror, contractimpl, contracttype, panic_with_error,
+    symbol_short, vec, Address, BytesN, Env, IntoVal, String, Symbol, Val, Vec,
 };
 
 // Booking status enum
@@ -12,6 +13,7 @@ pub enum BookingStatus {
     Confirmed,
     Completed,
     Cancelled,
+    CheckedIn,
 }
 
 // Main booking data structure
@@ -26,20 +28,187 @@ pub struct Booking {
     pub total_price: i128,
     pub status: BookingStatus,
     pub escrow_id: Option<String>,
+    // Listing content version the guest booked against, 0 when unknown
+    pub listing_version: u32,
 }
 
+// Booking record written before schema version 4, which did not track the
+// listing version. Only read by migrations.
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct LegacyBooking {
+    pub id: u64,
+    pub property_id: String,
+    pub user_id: String,
+    pub start_date: u64,
+    pub end_date: u64,
+    pub total_price: i128,
+    pub status: BookingStatus,
+    pub escrow_id: Option<String>,
+}
+
+// Dates held by a pending, confirmed or checked in booking, kept per
+// property so overlap and active booking checks read a single entry
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BookedStay {
+    pub booking_id: u64,
+    pub start_date: u64,
+    pub end_date: u64,
+    // Guests hold the property until they check out, even past the end date
+    pub checked_in: bool,
+}
+
+// Page of bookings returned by the paginated views
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BookingPage {
+    pub bookings: Vec<Booking>,
+    // Cursor to pass to the next call, None when there are no more entries
+    pub next_cursor: Option<u32>,
+}
+
+// Facts about a listing attested by verifiers of the listing contract. Must
+// match `AttestationKind` in the listing contract.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AttestationKind {
+    Address,
+    Photos,
+    License,
+}
+
+// Listing attestation as returned by the listing contract. Must match
+// `Attestation` in the listing contract.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Attestation {
+    pub kind: AttestationKind,
+    pub verifier: Address,
+    pub evidence_hash: BytesN<32>,
+    pub issued_at: u64,
+    pub expires_at: u64,
+    pub revoked: bool,
+}
+
+// Share of the host proceeds as returned by the listing contract. Must match
+// `RevenueShare` in the listing contract.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RevenueShare {
+    pub beneficiary: Address,
+    pub bps: u32,
+}
+
+// Amount of the host proceeds of a completed booking owed to a beneficiary
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Payout {
+    pub beneficiary: Address,
+    pub amount: i128,
+}
+
+// Interface of the property listing contract used to authorize hosts
+#[contractclient(name = "PropertyListingClient")]
+pub trait PropertyListingInterface {
+    fn has_permission(env: Env, id: String, caller: Address, permission: u32) -> bool;
+    fn set_occupancy(env: Env, id: String, occupied: bool);
+    fn get_max_guests(env: Env, id: String) -> Option<u32>;
+    fn get_listing_version(env: Env, id: String) -> u32;
+    fn get_attestations(env: Env, id: String) -> Vec<Attestation>;
+    fn get_annual_night_cap(env: Env, id: String) -> Option<u32>;
+    fn get_nightly_rate(env: Env, id: String) -> Option<i128>;
+    fn get_revenue_shares(env: Env, id: String) -> Vec<RevenueShare>;
+}
+
+/// Listing contract permission flag allowing an owner's delegate to manage
+/// bookings. Must match `PERM_CONFIRM_BOOKINGS` in the listing contract.
+pub const PERM_CONFIRM_BOOKINGS: u32 = 1 << 3;
+
 // Storage keys
-const BOOKINGS: Symbol = symbol_short!("BOOKINGS");
+const BOOKING: Symbol = symbol_short!("BOOKING");
 const BOOK_COUNT: Symbol = symbol_short!("BOOKCOUNT");
+const ADMIN: Symbol = symbol_short!("ADMIN");
+const VERSION: Symbol = symbol_short!("VERSION");
+const GUARDIAN: Symbol = symbol_short!("GUARDIAN");
+const PAUSED: Symbol = symbol_short!("PAUSED");
+const USER_BOOKINGS: Symbol = symbol_short!("USERBOOKS");
+const PROP_BOOKINGS: Symbol = symbol_short!("PROPBOOKS");
+const LISTING_CONTRACT: Symbol = symbol_short!("LISTING");
+// Open stays: (STAYS, property id) -> BookedStay of each booking still
+// holding its dates. Cancelled and completed bookings leave right away,
+// stays that ended without a check-in whenever the list is next written.
+const STAYS: Symbol = symbol_short!("STAYS");
+// Booked nights: (NIGHTS, property id, calendar year) -> number of nights of
+// bookings that are not cancelled, counted in the year each night starts.
+// Schema version 5 only counted listings with an annual night cap and marked
+// the bookings it counted under (NIGHTS, booking id), which only migrations
+// read.
+const NIGHTS: Symbol = symbol_short!("NIGHTS");
+// Payouts of completed bookings: (PAYOUT, booking id) -> Vec<Payout>
+const PAYOUT: Symbol = symbol_short!("PAYOUT");
+// Attestation kinds a listing needs for instant booking
+const INSTANT_BOOK: Symbol = symbol_short!("INSTANT");
+// Position reached by an unfinished migration step
+const MIGRATION: Symbol = symbol_short!("MIGRATION");
+
+// Layout before schema version 3, which kept every booking in one global
+// vector and a full copy of each booking under its raw property id.
+// Only read by migrations.
+const BOOKINGS: Symbol = symbol_short!("BOOKINGS");
+
+/// Version of the storage layout written by this code. Bump it together with
+/// a new arm in `migrate` whenever the layout of stored data changes.
+pub const SCHEMA_VERSION: u32 = 6;
+
+/// Basis points the revenue shares of a listing add up to. Must match
+/// `TOTAL_SHARE_BPS` in the listing contract.
+pub const TOTAL_SHARE_BPS: u32 = 10_000;
+
+/// Length of a booked night in seconds
+pub const DAY_IN_SECONDS: u64 = 86_400;
+
+/// Maximum number of nights of a single booking
+pub const MAX_STAY_NIGHTS: u64 = 365;
+
+/// Maximum number of bookings returned by a single page
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+// Storage TTL policy. Ledgers close roughly every 5 seconds, so one day is
+// about 17280 ledgers. Entries are bumped back to the full bump amount
+// whenever they are read or written and their remaining TTL has dropped
+// below the threshold.
+pub const DAY_IN_LEDGERS: u32 = 17_280;
+/// Instance storage (admin and contract config) is kept alive for 30 days.
+pub const INSTANCE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+/// Persistent entries (bookings, counters, property indexes) are kept alive for 90 days.
+pub const PERSISTENT_BUMP_AMOUNT: u32 = 90 * DAY_IN_LEDGERS;
+pub const PERSISTENT_LIFETIME_THRESHOLD: u32 = PERSISTENT_BUMP_AMOUNT - 7 * DAY_IN_LEDGERS;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum BookingError {
+    ContractPaused = 1,
+    StayTooLong = 2,
+    DateOutOfRange = 3,
+}
 
 #[contract]
 pub struct BookingContract;
 
 #[contractimpl]
 impl BookingContract {
-    /// Initialize the contract
-    pub fn initialize(env: Env) {
+    /// Set the admin when the contract is deployed, so no one can claim it
+    /// between deployment and setup
+    pub fn __constructor(env: Env, admin: Address) {
+        env.storage().instance().set(&ADMIN, &admin);
+        env.storage().instance().set(&VERSION, &SCHEMA_VERSION);
         env.storage().persistent().set(&BOOK_COUNT, &0u64);
+
+        Self::extend_instance_ttl(&env);
+        Self::extend_persistent_ttl(&env, &BOOK_COUNT);
     }
 
     /// Check if a property is available for the given dates
@@ -54,22 +223,48 @@ impl BookingContract {
             return false;
         }
 
-        // Get all bookings for this property
-        let bookings = Self::get_property_bookings_internal(&env, property_id.clone());
-
-        // Check for overlaps
-        for booking in bookings.iter() {
-            if booking.status != BookingStatus::Cancelled {
-                // Check if dates overlap
-                if !(end_date <= booking.start_date || start_date >= booking.end_date) {
-                    return false;
-                }
+        // Check for overlaps with the stays still holding their dates
+        for stay in Self::get_stays(&env, &property_id).iter() {
+            if !(end_date <= stay.start_date || start_date >= stay.end_date) {
+                return false;
             }
         }
 
         true
     }
 
+    /// Check whether a property has bookings that are still to be honoured:
+    /// pending or confirmed stays that have not ended yet, or guests that
+    /// have not checked out. Used by the listing contract before a listing
+    /// is removed.
+    pub fn has_active_bookings(env: Env, property_id: String) -> bool {
+        let current_time = env.ledger().timestamp();
+        Self::get_stays(&env, &property_id)
+            .iter()
+            .any(|stay| stay.checked_in || stay.end_date > current_time)
+    }
+
+    /// Check whether a party of the given size fits the property. Uses the
+    /// listing metadata when a listing contract is configured; properties
+    /// without metadata accept any non-empty party.
+    pub fn check_occupancy(env: Env, property_id: String, guests: u32) -> bool {
+        if guests == 0 {
+            return false;
+        }
+
+        let max_guests = match Self::get_listing_contract(env.clone()) {
+            Some(listing_contract) => {
+                PropertyListingClient::new(&env, &listing_contract).get_max_guests(&property_id)
+            }
+            None => None,
+        };
+
+        match max_guests {
+            Some(max_guests) => guests <= max_guests,
+            None => true,
+        }
+    }
+
     /// Create a new booking
     pub fn create_booking(
         env: Env,
@@ -79,6 +274,355 @@ impl BookingContract {
         end_date: u64,
         total_price: i128,
     ) -> u64 {
+        Self::require_not_paused(&env);
+
+        Self::validate_booking(&env, &property_id, start_date, end_date, total_price);
+
+        Self::insert_booking(
+            &env,
+            property_id,
+            user_id,
+            start_date,
+            end_date,
+            total_price,
+            BookingStatus::Pending,
+        )
+    }
+
+    /// Book a property without waiting for the host to confirm. The booking
+    /// is created as confirmed, which requires the listing to hold valid
+    /// attestations of every kind set with `set_instant_book_requirements`.
+    pub fn instant_book(
+        env: Env,
+        property_id: String,
+        user_id: String,
+        start_date: u64,
+        end_date: u64,
+        total_price: i128,
+    ) -> u64 {
+        Self::require_not_paused(&env);
+
+        if !Self::can_instant_book(env.clone(), property_id.clone()) {
+            panic!("Instant booking not allowed for this property");
+        }
+        Self::validate_booking(&env, &property_id, start_date, end_date, total_price);
+
+        Self::insert_booking(
+            &env,
+            property_id,
+            user_id,
+            start_date,
+            end_date,
+            total_price,
+            BookingStatus::Confirmed,
+        )
+    }
+
+    /// Check whether a property can be booked instantly: a listing contract
+    /// and instant booking requirements are configured, and the listing
+    /// holds an unexpired, unrevoked attestation of every required kind.
+    pub fn can_instant_book(env: Env, property_id: String) -> bool {
+        let requirements = Self::get_instant_book_requirements(env.clone());
+        let listing_contract = match Self::get_listing_contract(env.clone()) {
+            Some(listing_contract) if !requirements.is_empty() => listing_contract,
+            _ => return false,
+        };
+
+        let current_time = env.ledger().timestamp();
+        let attestations =
+            PropertyListingClient::new(&env, &listing_contract).get_attestations(&property_id);
+        requirements.iter().all(|kind| {
+            attestations.iter().any(|attestation| {
+                attestation.kind == kind
+                    && !attestation.revoked
+                    && attestation.expires_at > current_time
+            })
+        })
+    }
+
+    /// Get the number of nights a property is booked in a calendar year,
+    /// counting bookings that are not cancelled. Nights are counted whether
+    /// or not the listing has an annual night cap, so a cap set later also
+    /// covers the nights booked before it.
+    pub fn get_booked_nights(env: Env, property_id: String, year: u32) -> u32 {
+        let key = (NIGHTS, property_id, year);
+        match env.storage().persistent().get(&key) {
+            Some(nights) => {
+                Self::extend_persistent_ttl(&env, &key);
+                nights
+            }
+            None => 0,
+        }
+    }
+
+    /// Set the attestation kinds a listing needs for instant booking. An
+    /// empty list disables instant booking. Only callable by the admin.
+    pub fn set_instant_book_requirements(env: Env, kinds: Vec<AttestationKind>) {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        env.storage().instance().set(&INSTANT_BOOK, &kinds);
+        Self::extend_instance_ttl(&env);
+    }
+
+    /// Get the attestation kinds a listing needs for instant booking
+    pub fn get_instant_book_requirements(env: Env) -> Vec<AttestationKind> {
+        env.storage()
+            .instance()
+            .get(&INSTANT_BOOK)
+            .unwrap_or_else(|| vec![&env])
+    }
+
+    /// Cancel a booking
+    pub fn cancel_booking(env: Env, booking_id: u64, user_id: String) -> bool {
+        Self::require_not_paused(&env);
+
+        // Get the booking
+        let mut booking = Self::load_booking(&env, booking_id);
+
+        // Verify user authorization
+        if booking.user_id != user_id {
+            panic!("Unauthorized: only the booking owner can cancel");
+        }
+
+        // Check if booking can be cancelled. Guests in the house check out
+        // instead, so the property is released in the listing contract.
+        if booking.status == BookingStatus::Completed
+            || booking.status == BookingStatus::Cancelled
+            || booking.status == BookingStatus::CheckedIn
+        {
+            panic!("Invalid status: booking cannot be cancelled");
+        }
+
+        // Update status
+        booking.status = BookingStatus::Cancelled;
+        Self::save_booking(&env, &booking);
+        Self::update_stay(&env, &booking);
+        Self::update_booked_nights(&env, &booking, false);
+        Self::extend_instance_ttl(&env);
+
+        // TODO: Trigger escrow refund
+
+        true
+    }
+
+    /// Get a specific booking by ID
+    pub fn get_booking(env: Env, booking_id: u64) -> Booking {
+        Self::load_booking(&env, booking_id)
+    }
+
+    /// Update booking status
+    pub fn update_status(
+        env: Env,
+        booking_id: u64,
+        new_status: BookingStatus,
+        caller: Address,
+    ) -> Booking {
+        Self::require_not_paused(&env);
+
+        caller.require_auth();
+
+        let mut booking = Self::load_booking(&env, booking_id);
+        Self::require_host_permission(&env, &booking, &caller);
+
+        // Validate status transition
+        match (booking.status, new_status) {
+            (BookingStatus::Pending, BookingStatus::Confirmed)
+            | (BookingStatus::Confirmed, BookingStatus::Completed)
+            | (BookingStatus::Pending, BookingStatus::Cancelled)
+            | (BookingStatus::Confirmed, BookingStatus::Cancelled) => {
+                booking.status = new_status;
+            }
+            _ => panic!("Invalid status transition"),
+        }
+
+        Self::save_booking(&env, &booking);
+        match new_status {
+            BookingStatus::Cancelled => {
+                Self::update_stay(&env, &booking);
+                Self::update_booked_nights(&env, &booking, false);
+            }
+            BookingStatus::Completed => {
+                Self::update_stay(&env, &booking);
+                Self::record_payout(&env, &booking);
+            }
+            _ => {}
+        }
+        Self::extend_instance_ttl(&env);
+
+        // TODO: Trigger escrow actions based on status change
+
+        booking
+    }
+
+    /// Check a guest in to a confirmed booking. Marks the property as booked
+    /// in the listing contract when one is configured.
+    pub fn check_in(env: Env, booking_id: u64, caller: Address) -> Booking {
+        Self::require_not_paused(&env);
+        caller.require_auth();
+
+        let mut booking = Self::load_booking(&env, booking_id);
+        Self::require_host_permission(&env, &booking, &caller);
+
+        if booking.status != BookingStatus::Confirmed {
+            panic!("Invalid status: only confirmed bookings can check in");
+        }
+        if env.ledger().timestamp() < booking.start_date {
+            panic!("Invalid dates: the stay has not started yet");
+        }
+
+        booking.status = BookingStatus::CheckedIn;
+        Self::save_booking(&env, &booking);
+        Self::update_stay(&env, &booking);
+        Self::extend_instance_ttl(&env);
+
+        if let Some(listing_contract) = Self::get_listing_contract(env.clone()) {
+            PropertyListingClient::new(&env, &listing_contract)
+                .set_occupancy(&booking.property_id, &true);
+        }
+
+        booking
+    }
+
+    /// Check a guest out, completing the booking. Makes the property
+    /// available again in the listing contract when one is configured.
+    pub fn check_out(env: Env, booking_id: u64, caller: Address) -> Booking {
+        Self::require_not_paused(&env);
+        caller.require_auth();
+
+        let mut booking = Self::load_booking(&env, booking_id);
+        Self::require_host_permission(&env, &booking, &caller);
+
+        if booking.status != BookingStatus::CheckedIn {
+            panic!("Invalid status: only checked in bookings can check out");
+        }
+
+        booking.status = BookingStatus::Completed;
+        Self::save_booking(&env, &booking);
+        Self::update_stay(&env, &booking);
+        Self::record_payout(&env, &booking);
+        Self::extend_instance_ttl(&env);
+
+        if let Some(listing_contract) = Self::get_listing_contract(env.clone()) {
+            PropertyListingClient::new(&env, &listing_contract)
+                .set_occupancy(&booking.property_id, &false);
+        }
+
+        booking
+    }
+
+    /// Get how the host proceeds of a completed booking are split between
+    /// the beneficiaries of the listing. Empty when the booking is not
+    /// completed or its listing was unknown to the listing contract at
+    /// completion.
+    pub fn get_payout(env: Env, booking_id: u64) -> Vec<Payout> {
+        let key = (PAYOUT, booking_id);
+        match env.storage().persistent().get(&key) {
+            Some(payouts) => {
+                Self::extend_persistent_ttl(&env, &key);
+                payouts
+            }
+            None => vec![&env],
+        }
+    }
+
+    /// Get a page of bookings for a specific property, oldest first
+    pub fn get_property_bookings(
+        env: Env,
+        property_id: String,
+        cursor: u32,
+        limit: u32,
+    ) -> BookingPage {
+        let limit = Self::validate_limit(limit);
+        let booking_ids = Self::get_property_booking_ids(&env, &property_id);
+
+        let mut bookings = vec![&env];
+        let mut i = cursor;
+        while i < booking_ids.len() && bookings.len() < limit {
+            bookings.push_back(Self::load_booking(&env, booking_ids.get(i).unwrap()));
+            i += 1;
+        }
+
+        let next_cursor = if i < booking_ids.len() { Some(i) } else { None };
+
+        BookingPage {
+            bookings,
+            next_cursor,
+        }
+    }
+
+    /// Get a page of bookings made by a guest, oldest first, optionally
+    /// filtered by status
+    pub fn get_user_bookings(
+        env: Env,
+        guest: String,
+        status_filter: Option<BookingStatus>,
+        cursor: u32,
+        limit: u32,
+    ) -> BookingPage {
+        let limit = Self::validate_limit(limit);
+        let booking_ids = Self::get_user_booking_ids(&env, &guest);
+
+        let mut bookings = vec![&env];
+        let mut i = cursor;
+        while i < booking_ids.len() && bookings.len() < limit {
+            let booking = Self::load_booking(&env, booking_ids.get(i).unwrap());
+            if status_filter.is_none() || status_filter == Some(booking.status) {
+                bookings.push_back(booking);
+            }
+            i += 1;
+        }
+
+        let next_cursor = if i < booking_ids.len() { Some(i) } else { None };
+
+        BookingPage {
+            bookings,
+            next_cursor,
+        }
+    }
+
+    /// Check that booking records and the property and guest indexes agree
+    /// for booking ids `start_id..start_id + limit`. Returns the ids that
+    /// violate an invariant, so an empty result means the range is consistent.
+    pub fn audit_state(env: Env, start_id: u64, limit: u32) -> Vec<u64> {
+        let limit = Self::validate_limit(limit);
+        let book_count: u64 = env.storage().persistent().get(&BOOK_COUNT).unwrap_or(0);
+        let end_id = book_count.min(start_id.saturating_add(limit as u64));
+
+        let mut violations = vec![&env];
+        let mut id = start_id;
+        while id < end_id {
+            let booking: Option<Booking> = env.storage().persistent().get(&(BOOKING, id));
+            let consistent = match booking {
+                Some(booking) => {
+                    let prop_ids = Self::get_property_booking_ids(&env, &booking.property_id);
+                    let user_ids = Self::get_user_booking_ids(&env, &booking.user_id);
+
+                    booking.id == id
+                        && Self::count_id(&prop_ids, id) == 1
+                        && Self::count_id(&user_ids, id) == 1
+                }
+                None => false,
+            };
+
+            if !consistent {
+                violations.push_back(id);
+            }
+            id += 1;
+        }
+
+        violations
+    }
+
+    /// Internal helper to reject bookings with invalid dates or price, or
+    /// that overlap an existing reservation
+    fn validate_booking(
+        env: &Env,
+        property_id: &String,
+        start_date: u64,
+        end_date: u64,
+        total_price: i128,
+    ) {
         // Validate inputs
         if start_date >= end_date {
             panic!("Invalid dates: start date must be before end date");
@@ -88,6 +632,18 @@ impl BookingContract {
             panic!("Invalid price: price must be greater than zero");
         }
 
+        let nights = (end_date - start_date).div_ceil(DAY_IN_SECONDS);
+        if nights > MAX_STAY_NIGHTS {
+            panic_with_error!(env, BookingError::StayTooLong);
+        }
+
+        // Enforce the nightly rate set by the host
+        if let Some(rate) = Self::get_nightly_rate(env, property_id) {
+            if total_price < rate.saturating_mul(nights as i128) {
+                panic!("Invalid price: below the nightly rate of the property");
+            }
+        }
+
         // Get current timestamp for basic validation
         let current_time = env.ledger().timestamp();
         if start_date < current_time {
@@ -99,6 +655,38 @@ impl BookingContract {
             panic!("Booking overlap: dates conflict with existing reservation");
         }
 
+        // Enforce the annual night cap of the listing's jurisdiction
+        if let Some(night_cap) = Self::get_night_cap(env, property_id) {
+            let mut within_cap = true;
+            Self::for_each_year(env, start_date, end_date, |year, nights| {
+                let booked = Self::get_booked_nights(env.clone(), property_id.clone(), year);
+                within_cap &= booked + nights <= night_cap;
+            });
+            if !within_cap {
+                panic!("Night cap exceeded: property is booked too many nights this year");
+            }
+        }
+    }
+
+    /// Internal helper to store a new booking and add it to the property and
+    /// guest indexes
+    fn insert_booking(
+        env: &Env,
+        property_id: String,
+        user_id: String,
+        start_date: u64,
+        end_date: u64,
+        total_price: i128,
+        status: BookingStatus,
+    ) -> u64 {
+        // Record which version of the listing content the guest saw
+        let listing_version = match Self::get_listing_contract(env.clone()) {
+            Some(listing_contract) => {
+                PropertyListingClient::new(env, &listing_contract).get_listing_version(&property_id)
+            }
+            None => 0,
+        };
+
         // Generate unique booking ID
         let book_count: u64 = env.storage().persistent().get(&BOOK_COUNT).unwrap_or(0);
 
@@ -112,231 +700,605 @@ impl BookingContract {
             start_date,
             end_date,
             total_price,
-            status: BookingStatus::Pending,
+            status,
             escrow_id: None, // Will be set when escrow is initiated
+            listing_version,
         };
 
         // Store booking
-        let mut bookings_map: Vec<(u64, Booking)> = env
-            .storage()
-            .persistent()
-            .get(&BOOKINGS)
-            .unwrap_or(vec![&env]);
-
-        bookings_map.push_back((booking_id, booking.clone()));
-        env.storage().persistent().set(&BOOKINGS, &bookings_map);
+        Self::save_booking(env, &booking);
+        Self::update_stay(env, &booking);
+        Self::update_booked_nights(env, &booking, true);
 
-        // Update property bookings
-        let mut prop_bookings = Self::get_property_bookings_internal(&env, property_id.clone());
-        prop_bookings.push_back(booking.clone());
+        // Update property index
+        let prop_key = (PROP_BOOKINGS, property_id.clone());
+        let mut prop_bookings = Self::get_property_booking_ids(env, &property_id);
+        prop_bookings.push_back(booking_id);
+        env.storage().persistent().set(&prop_key, &prop_bookings);
 
-        // Store with property ID as key
-        env.storage().persistent().set(&property_id, &prop_bookings);
+        // Update guest index
+        let user_key = (USER_BOOKINGS, user_id.clone());
+        let mut user_bookings = Self::get_user_booking_ids(env, &user_id);
+        user_bookings.push_back(booking_id);
+        env.storage().persistent().set(&user_key, &user_bookings);
 
         // Update booking counter
         env.storage()
             .persistent()
             .set(&BOOK_COUNT, &(book_count + 1));
 
+        Self::extend_persistent_ttl(env, &prop_key);
+        Self::extend_persistent_ttl(env, &user_key);
+        Self::extend_persistent_ttl(env, &BOOK_COUNT);
+        Self::extend_instance_ttl(env);
+
         // TODO: Initiate escrow here
         // This would typically involve calling an external escrow contract
 
         booking_id
     }
 
-    /// Cancel a booking
-    pub fn cancel_booking(env: Env, booking_id: u64, user_id: String) -> bool {
-        // Get the booking
-        let mut bookings_map: Vec<(u64, Booking)> = env
-            .storage()
-            .persistent()
-            .get(&BOOKINGS)
-            .unwrap_or(vec![&env]);
-
-        let mut booking_found = false;
-        let mut updated_booking = None;
-
-        for i in 0..bookings_map.len() {
-            let (id, mut booking) = bookings_map.get(i).unwrap();
-            if id == booking_id {
-                // Verify user authorization
-                if booking.user_id != user_id {
-                    panic!("Unauthorized: only the booking owner can cancel");
-                }
+    /// Internal helper to bring the stay of a booking in line with its
+    /// status. Bookings that are cancelled or completed, or whose stay ended
+    /// without a check-in, release their dates; other ended stays of the
+    /// property are dropped on the way.
+    fn update_stay(env: &Env, booking: &Booking) {
+        let current_time = env.ledger().timestamp();
+        let holds_dates = |stay: &BookedStay| stay.checked_in || stay.end_date > current_time;
 
-                // Check if booking can be cancelled
-                if booking.status == BookingStatus::Completed
-                    || booking.status == BookingStatus::Cancelled
-                {
-                    panic!("Invalid status: booking cannot be cancelled");
-                }
+        let mut stays = vec![env];
+        for stay in Self::get_stays(env, &booking.property_id).iter() {
+            if stay.booking_id != booking.id && holds_dates(&stay) {
+                stays.push_back(stay);
+            }
+        }
+        let stay = BookedStay {
+            booking_id: booking.id,
+            start_date: booking.start_date,
+            end_date: booking.end_date,
+            checked_in: booking.status == BookingStatus::CheckedIn,
+        };
+        let open = matches!(
+            booking.status,
+            BookingStatus::Pending | BookingStatus::Confirmed | BookingStatus::CheckedIn
+        );
+        if open && holds_dates(&stay) {
+            stays.push_back(stay);
+        }
 
-                // Update status
-                booking.status = BookingStatus::Cancelled;
-                bookings_map.set(i, (id, booking.clone()));
-                updated_booking = Some(booking.clone());
-                booking_found = true;
-                break;
+        let key = (STAYS, booking.property_id.clone());
+        if stays.is_empty() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &stays);
+            Self::extend_persistent_ttl(env, &key);
+        }
+    }
+
+    /// Internal helper to get the open stays of a property
+    fn get_stays(env: &Env, property_id: &String) -> Vec<BookedStay> {
+        let key = (STAYS, property_id.clone());
+        match env.storage().persistent().get(&key) {
+            Some(stays) => {
+                Self::extend_persistent_ttl(env, &key);
+                stays
             }
+            None => vec![env],
+        }
+    }
+
+    /// Internal helper to split the host proceeds of a completed booking by
+    /// the revenue shares of its listing. Rounding leftovers go to the first
+    /// beneficiary. The escrow system pays out according to the result.
+    fn record_payout(env: &Env, booking: &Booking) {
+        let listing_contract = match Self::get_listing_contract(env.clone()) {
+            Some(listing_contract) => listing_contract,
+            None => return,
+        };
+        let shares = PropertyListingClient::new(env, &listing_contract)
+            .get_revenue_shares(&booking.property_id);
+        if shares.is_empty() {
+            return;
         }
 
-        if !booking_found {
-            panic!("Booking not found");
+        let mut payouts = vec![env];
+        let mut paid = 0i128;
+        for share in shares.iter() {
+            let amount = booking.total_price * share.bps as i128 / TOTAL_SHARE_BPS as i128;
+            paid += amount;
+            payouts.push_back(Payout {
+                beneficiary: share.beneficiary,
+                amount,
+            });
         }
+        let mut first = payouts.get(0).unwrap();
+        first.amount += booking.total_price - paid;
+        payouts.set(0, first);
 
-        // Update storage
-        env.storage().persistent().set(&BOOKINGS, &bookings_map);
+        let key = (PAYOUT, booking.id);
+        env.storage().persistent().set(&key, &payouts);
+        Self::extend_persistent_ttl(env, &key);
 
-        // Update property bookings
-        if let Some(booking) = updated_booking {
-            let mut prop_bookings: Vec<Booking> = env
-                .storage()
-                .persistent()
-                .get(&booking.property_id)
-                .unwrap_or(vec![&env]);
+        env.events()
+            .publish((symbol_short!("payout"), booking.id), payouts);
+    }
 
-            for i in 0..prop_bookings.len() {
-                if prop_bookings.get(i).unwrap().id == booking_id {
-                    prop_bookings.set(i, booking.clone());
-                    break;
-                }
+    /// Internal helper returning the annual night cap of a listing, if a
+    /// listing contract is configured and the listing has one
+    fn get_night_cap(env: &Env, property_id: &String) -> Option<u32> {
+        let listing_contract = Self::get_listing_contract(env.clone())?;
+        PropertyListingClient::new(env, &listing_contract).get_annual_night_cap(property_id)
+    }
+
+    /// Internal helper returning the nightly rate of a listing, if a listing
+    /// contract is configured and the listing has one
+    fn get_nightly_rate(env: &Env, property_id: &String) -> Option<i128> {
+        let listing_contract = Self::get_listing_contract(env.clone())?;
+        PropertyListingClient::new(env, &listing_contract).get_nightly_rate(property_id)
+    }
+
+    /// Internal helper to add or remove the nights of a booking from the
+    /// booked nights of its property
+    fn update_booked_nights(env: &Env, booking: &Booking, add: bool) {
+        Self::for_each_year(env, booking.start_date, booking.end_date, |year, nights| {
+            let key = (NIGHTS, booking.property_id.clone(), year);
+            let booked: u32 = env.storage().persistent().get(&key).unwrap_or(0);
+            let booked = if add {
+                booked + nights
+            } else {
+                booked.saturating_sub(nights)
+            };
+            if booked == 0 {
+                env.storage().persistent().remove(&key);
+            } else {
+                env.storage().persistent().set(&key, &booked);
+                Self::extend_persistent_ttl(env, &key);
             }
+        });
+    }
 
-            env.storage()
-                .persistent()
-                .set(&booking.property_id, &prop_bookings);
+    /// Internal helper to split the nights of a stay by calendar year. A
+    /// started day counts as a night, and each night belongs to the year in
+    /// which it starts.
+    fn for_each_year(env: &Env, start_date: u64, end_date: u64, mut f: impl FnMut(u32, u32)) {
+        let mut night = start_date;
+        while night < end_date {
+            let year = Self::calendar_year(env, night);
+            let year_end = Self::year_start(year + 1).min(end_date);
+            let nights = (year_end - night).div_ceil(DAY_IN_SECONDS);
+            f(year, nights as u32);
+            night += nights * DAY_IN_SECONDS;
         }
+    }
 
-        // TODO: Trigger escrow refund
+    /// Internal helper returning the UTC calendar year of a timestamp
+    fn calendar_year(env: &Env, timestamp: u64) -> u32 {
+        // No year has more than 366 days, so this never overshoots. Half the
+        // u32 range leaves room for the years it undershoots by.
+        let years = u32::try_from(timestamp / DAY_IN_SECONDS / 366)
+            .ok()
+            .filter(|years| *years <= u32::MAX / 2)
+            .unwrap_or_else(|| panic_with_error!(env, BookingError::DateOutOfRange));
+        let mut year = 1970 + years;
+        while Self::year_start(year + 1) <= timestamp {
+            year += 1;
+        }
+        year
+    }
 
-        true
+    /// Internal helper returning the timestamp of January 1st of a year
+    fn year_start(year: u32) -> u64 {
+        let leap_days = |y: u64| y / 4 - y / 100 + y / 400;
+        let year = year as u64;
+        let days = 365 * (year - 1970) + leap_days(year - 1) - leap_days(1969);
+        days * DAY_IN_SECONDS
     }
 
-    /// Get a specific booking by ID
-    pub fn get_booking(env: Env, booking_id: u64) -> Booking {
-        let bookings_map: Vec<(u64, Booking)> = env
-            .storage()
-            .persistent()
-            .get(&BOOKINGS)
-            .unwrap_or(vec![&env]);
+    /// Internal helper to count how often an id appears in an index
+    fn count_id(ids: &Vec<u64>, id: u64) -> u32 {
+        ids.iter().filter(|entry| *entry == id).count() as u32
+    }
 
-        for (id, booking) in bookings_map.iter() {
-            if id == booking_id {
-                return booking;
+    /// Internal helper to check that the caller may manage bookings of a
+    /// property. Once a listing contract is configured, only the property
+    /// owner and delegates allowed to manage bookings pass.
+    fn require_host_permission(env: &Env, booking: &Booking, caller: &Address) {
+        if let Some(listing_contract) = Self::get_listing_contract(env.clone()) {
+            let listing = PropertyListingClient::new(env, &listing_contract);
+            if !listing.has_permission(&booking.property_id, caller, &PERM_CONFIRM_BOOKINGS) {
+                panic!("Unauthorized: caller cannot manage bookings for this property");
             }
         }
+    }
 
-        panic!("Booking not found");
+    /// Internal helper to validate and cap a page size
+    fn validate_limit(limit: u32) -> u32 {
+        if limit == 0 {
+            panic!("Invalid limit: must be greater than zero");
+        }
+        limit.min(MAX_PAGE_SIZE)
     }
 
-    /// Update booking status
-    pub fn update_status(
+    /// Internal helper to read a booking record
+    fn load_booking(env: &Env, booking_id: u64) -> Booking {
+        let key = (BOOKING, booking_id);
+        match env.storage().persistent().get(&key) {
+            Some(booking) => {
+                Self::extend_persistent_ttl(env, &key);
+                booking
+            }
+            None => panic!("Booking not found"),
+        }
+    }
+
+    /// Internal helper to write a booking record
+    fn save_booking(env: &Env, booking: &Booking) {
+        let key = (BOOKING, booking.id);
+        env.storage().persistent().set(&key, booking);
+        Self::extend_persistent_ttl(env, &key);
+    }
+
+    /// Internal helper to get the booking ids of a property
+    fn get_property_booking_ids(env: &Env, property_id: &String) -> Vec<u64> {
+        let key = (PROP_BOOKINGS, property_id.clone());
+        match env.storage().persistent().get(&key) {
+            Some(ids) => {
+                Self::extend_persistent_ttl(env, &key);
+                ids
+            }
+            None => vec![env],
+        }
+    }
+
+    /// Internal helper to get the booking ids of a guest
+    fn get_user_booking_ids(env: &Env, user_id: &String) -> Vec<u64> {
+        let key = (USER_BOOKINGS, user_id.clone());
+        match env.storage().persistent().get(&key) {
+            Some(ids) => {
+                Self::extend_persistent_ttl(env, &key);
+                ids
+            }
+            None => vec![env],
+        }
+    }
+
+    /// Set escrow ID for a booking (called by escrow system)
+    pub fn set_escrow_id(
         env: Env,
         booking_id: u64,
-        new_status: BookingStatus,
-        caller: Address,
-    ) -> Booking {
-        // TODO: Add proper authorization check for host/system
-        caller.require_auth();
+        escrow_id: String,
+        _caller: Address, // TODO: Verify this is the escrow contract
+    ) -> bool {
+        Self::require_not_paused(&env);
 
-        let mut bookings_map: Vec<(u64, Booking)> = env
-            .storage()
-            .persistent()
-            .get(&BOOKINGS)
-            .unwrap_or(vec![&env]);
-
-        let mut booking_found = false;
-        let mut updated_booking = None;
-
-        for i in 0..bookings_map.len() {
-            let (id, mut booking) = bookings_map.get(i).unwrap();
-            if id == booking_id {
-                // Validate status transition
-                match (booking.status, new_status) {
-                    (BookingStatus::Pending, BookingStatus::Confirmed)
-                    | (BookingStatus::Confirmed, BookingStatus::Completed)
-                    | (BookingStatus::Pending, BookingStatus::Cancelled)
-                    | (BookingStatus::Confirmed, BookingStatus::Cancelled) => {
-                        booking.status = new_status;
-                        bookings_map.set(i, (id.clone(), booking.clone()));
-                        updated_booking = Some(booking.clone());
-                        booking_found = true;
+        let mut booking = Self::load_booking(&env, booking_id);
+        booking.escrow_id = Some(escrow_id);
+        Self::save_booking(&env, &booking);
+        Self::extend_instance_ttl(&env);
+
+        true
+    }
+
+    /// Extend the TTL of the contract instance, the booking counter and
+    /// everything stored for the given properties: their booking index, open
+    /// stays and booked nights, and their bookings with their payouts and
+    /// guest indexes. Missing entries are skipped. Only callable by the admin.
+    pub fn bump_ttl(env: Env, property_ids: Vec<String>) {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        Self::extend_instance_ttl(&env);
+        Self::extend_persistent_ttl_if_present(&env, &BOOK_COUNT);
+
+        for property_id in property_ids.iter() {
+            Self::extend_persistent_ttl_if_present(&env, &(STAYS, property_id.clone()));
+            for booking_id in Self::get_property_booking_ids(&env, &property_id).iter() {
+                let key = (BOOKING, booking_id);
+                let booking: Booking = match env.storage().persistent().get(&key) {
+                    Some(booking) => booking,
+                    None => continue,
+                };
+                Self::extend_persistent_ttl(&env, &key);
+                Self::extend_persistent_ttl_if_present(&env, &(PAYOUT, booking_id));
+                Self::extend_persistent_ttl_if_present(&env, &(USER_BOOKINGS, booking.user_id));
+                Self::for_each_year(&env, booking.start_date, booking.end_date, |year, _| {
+                    Self::extend_persistent_ttl_if_present(
+                        &env,
+                        &(NIGHTS, property_id.clone(), year),
+                    );
+                });
+            }
+        }
+    }
+
+    /// Set the property listing contract used to authorize status updates.
+    /// Only callable by the admin.
+    pub fn set_listing_contract(env: Env, listing_contract: Address) {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        env.storage()
+            .instance()
+            .set(&LISTING_CONTRACT, &listing_contract);
+        Self::extend_instance_ttl(&env);
+    }
+
+    /// Get the configured property listing contract
+    pub fn get_listing_contract(env: Env) -> Option<Address> {
+        env.storage().instance().get(&LISTING_CONTRACT)
+    }
+
+    /// Upgrade the contract code. Storage is kept as is, so `migrate` must be
+    /// called afterwards if the new code expects a newer schema version.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    /// Get the schema version of the data in storage
+    pub fn version(env: Env) -> u32 {
+        env.storage().instance().get(&VERSION).unwrap_or(0)
+    }
+
+    /// Migrate stored data to the schema version of the current code,
+    /// converting at most `limit` bookings per call so that large data sets
+    /// fit in a transaction. Each call continues where the previous one
+    /// stopped, and the stored version is only bumped once a step has
+    /// converted every booking. Call it until it returns SCHEMA_VERSION and
+    /// keep the contract paused meanwhile. Only callable by the admin.
+    pub fn migrate(env: Env, limit: u32) -> u32 {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        if limit == 0 {
+            panic!("Invalid limit: must be greater than zero");
+        }
+        let mut version = Self::version(env.clone());
+        if version > SCHEMA_VERSION {
+            panic!("Stored schema is newer than the contract code");
+        }
+
+        let mut cursor: u64 = env.storage().instance().get(&MIGRATION).unwrap_or(0);
+        let mut budget = u64::from(limit);
+        while version < SCHEMA_VERSION {
+            // Steps up to version 3 go through the global booking list of the
+            // legacy layout, later steps through the booking ids
+            let legacy: Vec<(u64, LegacyBooking)> = match version {
+                1 | 2 => env
+                    .storage()
+                    .persistent()
+                    .get(&BOOKINGS)
+                    .unwrap_or(vec![&env]),
+                _ => vec![&env],
+            };
+            let size = match version {
+                // Contracts deployed before schema versioning was introduced
+                // already use the version 1 layout.
+                0 => 0,
+                1 | 2 => u64::from(legacy.len()),
+                _ => env.storage().persistent().get(&BOOK_COUNT).unwrap_or(0),
+            };
+            let end = size.min(cursor.saturating_add(budget));
+
+            for position in cursor..end {
+                match version {
+                    1 | 2 => {
+                        // Positions below the list length fit in a u32
+                        let (id, booking) = legacy.get(position as u32).unwrap();
+                        if version == 1 {
+                            Self::migrate_v1_to_v2(&env, id, booking);
+                        } else {
+                            Self::migrate_v2_to_v3(&env, id, booking);
+                        }
                     }
-                    _ => panic!("Invalid status transition"),
+                    3 => Self::migrate_v3_to_v4(&env, position),
+                    4 => Self::migrate_v4_to_v5(&env, position),
+                    5 => Self::migrate_v5_to_v6(&env, position),
+                    _ => panic!("No migration path for schema version"),
                 }
+            }
+            budget -= end - cursor;
+
+            if end < size {
+                cursor = end;
                 break;
             }
+            // The global list is dropped once every booking is stored on its own
+            if version == 2 && env.storage().persistent().has(&BOOKINGS) {
+                env.storage().persistent().remove(&BOOKINGS);
+            }
+            cursor = 0;
+            version += 1;
         }
 
-        if !booking_found {
-            panic!("Booking not found");
+        if cursor == 0 {
+            env.storage().instance().remove(&MIGRATION);
+        } else {
+            env.storage().instance().set(&MIGRATION, &cursor);
         }
+        env.storage().instance().set(&VERSION, &version);
+        Self::extend_instance_ttl(&env);
 
-        // Update storage
-        env.storage().persistent().set(&BOOKINGS, &bookings_map);
+        version
+    }
 
-        // Update property bookings
-        if let Some(booking) = &updated_booking {
-            let mut prop_bookings: Vec<Booking> = env
-                .storage()
-                .persistent()
-                .get(&booking.property_id)
-                .unwrap_or(vec![&env]);
+    /// Version 2 adds the per-guest booking index, rebuilt here from the
+    /// global booking list
+    fn migrate_v1_to_v2(env: &Env, id: u64, booking: LegacyBooking) {
+        let user_key = (USER_BOOKINGS, booking.user_id);
+        let mut user_bookings: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&user_key)
+            .unwrap_or(vec![env]);
+        if !user_bookings.contains(id) {
+            user_bookings.push_back(id);
+            env.storage().persistent().set(&user_key, &user_bookings);
+            Self::extend_persistent_ttl(env, &user_key);
+        }
+    }
 
-            for i in 0..prop_bookings.len() {
-                if prop_bookings.get(i).unwrap().id == booking_id {
-                    prop_bookings.set(i, booking.clone());
-                    break;
-                }
+    /// Version 3 stores each booking once under its id and turns the
+    /// per-property lists into id indexes. The global list is authoritative,
+    /// since the per-property copies could hold a stale escrow id.
+    fn migrate_v2_to_v3(env: &Env, id: u64, booking: LegacyBooking) {
+        let key = (BOOKING, id);
+        env.storage().persistent().set(&key, &booking);
+        Self::extend_persistent_ttl(env, &key);
+
+        let prop_key = (PROP_BOOKINGS, booking.property_id.clone());
+        let mut prop_bookings: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&prop_key)
+            .unwrap_or(vec![env]);
+        if !prop_bookings.contains(id) {
+            prop_bookings.push_back(id);
+            env.storage().persistent().set(&prop_key, &prop_bookings);
+            Self::extend_persistent_ttl(env, &prop_key);
+        }
+
+        if env.storage().persistent().has(&booking.property_id) {
+            env.storage().persistent().remove(&booking.property_id);
+        }
+    }
+
+    /// Version 4 records the listing version on each booking. Bookings made
+    /// before it get version 0, since the content they saw is unknown.
+    fn migrate_v3_to_v4(env: &Env, id: u64) {
+        let key = (BOOKING, id);
+        let legacy: Option<LegacyBooking> = env.storage().persistent().get(&key);
+        if let Some(legacy) = legacy {
+            let booking = Booking {
+                id: legacy.id,
+                property_id: legacy.property_id,
+                user_id: legacy.user_id,
+                start_date: legacy.start_date,
+                end_date: legacy.end_date,
+                total_price: legacy.total_price,
+                status: legacy.status,
+                escrow_id: legacy.escrow_id,
+                listing_version: 0,
+            };
+            Self::save_booking(env, &booking);
+        }
+    }
+
+    /// Version 5 tracks booked nights per property and calendar year,
+    /// counted here from the bookings that are not cancelled. Counted
+    /// bookings are marked like the version 5 release did.
+    fn migrate_v4_to_v5(env: &Env, id: u64) {
+        let booking: Option<Booking> = env.storage().persistent().get(&(BOOKING, id));
+        if let Some(booking) = booking {
+            if booking.status != BookingStatus::Cancelled {
+                Self::update_booked_nights(env, &booking, true);
+                env.storage().persistent().set(&(NIGHTS, id), &true);
             }
+        }
+    }
+
+    /// Version 6 keeps the open stays of each property, collected here from
+    /// the bookings, and counts the nights of every listing. The nights of
+    /// bookings the version 5 release left uncounted are added, and the
+    /// marks of counted bookings dropped.
+    fn migrate_v5_to_v6(env: &Env, id: u64) {
+        let booking: Option<Booking> = env.storage().persistent().get(&(BOOKING, id));
+        if let Some(booking) = booking {
+            Self::update_stay(env, &booking);
 
-            env.storage()
-                .persistent()
-                .set(&booking.property_id, &prop_bookings);
+            let counted_key = (NIGHTS, id);
+            if env.storage().persistent().has(&counted_key) {
+                env.storage().persistent().remove(&counted_key);
+            } else if booking.status != BookingStatus::Cancelled {
+                Self::update_booked_nights(env, &booking, true);
+            }
         }
+    }
 
-        // TODO: Trigger escrow actions based on status change
+    /// Set the guardian allowed to pause the contract. Only callable by the admin.
+    pub fn set_guardian(env: Env, guardian: Address) {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
 
-        updated_booking.unwrap()
+        env.storage().instance().set(&GUARDIAN, &guardian);
+        Self::extend_instance_ttl(&env);
     }
 
-    /// Get all bookings for a specific property
-    pub fn get_property_bookings(env: Env, property_id: String) -> Vec<Booking> {
-        Self::get_property_bookings_internal(&env, property_id)
+    /// Pause all state-changing entrypoints. Callable by the admin or the guardian.
+    pub fn pause(env: Env, caller: Address) {
+        caller.require_auth();
+
+        let guardian: Option<Address> = env.storage().instance().get(&GUARDIAN);
+        if caller != Self::get_admin(&env) && Some(caller.clone()) != guardian {
+            panic!("Unauthorized: only the admin or guardian can pause");
+        }
+
+        env.storage().instance().set(&PAUSED, &true);
+        Self::extend_instance_ttl(&env);
+
+        env.events().publish((symbol_short!("paused"),), caller);
     }
 
-    /// Internal helper to get property bookings
-    fn get_property_bookings_internal(env: &Env, property_id: String) -> Vec<Booking> {
-        env.storage()
-            .persistent()
-            .get(&property_id)
-            .unwrap_or(vec![env])
+    /// Resume state-changing entrypoints. Only callable by the admin.
+    pub fn unpause(env: Env) {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        env.storage().instance().set(&PAUSED, &false);
+        Self::extend_instance_ttl(&env);
+
+        env.events().publish((symbol_short!("unpaused"),), admin);
     }
 
-    /// Set escrow ID for a booking (called by escrow system)
-    pub fn set_escrow_id(
-        env: Env,
-        booking_id: u64,
-        escrow_id: String,
-        _caller: Address, // TODO: Verify this is the escrow contract
-    ) -> bool {
-        let mut bookings_map: Vec<(u64, Booking)> = env
-            .storage()
-            .persistent()
-            .get(&BOOKINGS)
-            .unwrap_or(vec![&env]);
-
-        for i in 0..bookings_map.len() {
-            let (id, mut booking) = bookings_map.get(i).unwrap();
-            if id == booking_id {
-                booking.escrow_id = Some(escrow_id);
-                bookings_map.set(i, (id, booking));
-                env.storage().persistent().set(&BOOKINGS, &bookings_map);
-                return true;
-            }
+    /// Check whether state-changing entrypoints are paused
+    pub fn is_paused(env: Env) -> bool {
+        env.storage().instance().get(&PAUSED).unwrap_or(false)
+    }
+
+    /// Internal helper to reject state changes while paused
+    fn require_not_paused(env: &Env) {
+        if Self::is_paused(env.clone()) {
+            panic_with_error!(env, BookingError::ContractPaused);
         }
+    }
+
+    /// Internal helper to read the admin
+    fn get_admin(env: &Env) -> Address {
+        env.storage()
+            .instance()
+            .get(&ADMIN)
+            .unwrap_or_else(|| panic!("Contract not initialized"))
+    }
 
-        panic!("Booking not found");
+    /// Internal helper to keep the contract instance alive
+    fn extend_instance_ttl(env: &Env) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+    }
+
+    /// Internal helper to keep a persistent entry alive
+    fn extend_persistent_ttl<K>(env: &Env, key: &K)
+    where
+        K: IntoVal<Env, Val>,
+    {
+        env.storage().persistent().extend_ttl(
+            key,
+            PERSISTENT_LIFETIME_THRESHOLD,
+            PERSISTENT_BUMP_AMOUNT,
+        );
+    }
+
+    /// Internal helper to keep a persistent entry alive if it exists
+    fn extend_persistent_ttl_if_present<K>(env: &Env, key: &K)
+    where
+        K: IntoVal<Env, Val>,
+    {
+        if env.storage().persistent().has(key) {
+            Self::extend_persistent_ttl(env, key);
+        }
     }
 }
 
@@ -2,18 +2,164 @@
 
 use super::*;
 use soroban_sdk::{
-    testutils::{Address as _, Ledger},
-    Address, Env, String,
+    testutils::{
+        storage::{Instance as _, Persistent as _},
+        Address as _, Events, Ledger,
+    },
+    vec, Address, BytesN, Env, String,
 };
 
+// Release builds of the contract at each schema version, used by the upgrade tests
+mod booking_v1 {
+    soroban_sdk::contractimport!(file = "fixtures/booking_v1.wasm");
+}
+
+mod booking_v2 {
+    soroban_sdk::contractimport!(file = "fixtures/booking_v2.wasm");
+}
+
+mod booking_v3 {
+    soroban_sdk::contractimport!(file = "fixtures/booking_v3.wasm");
+}
+
+mod booking_v4 {
+    soroban_sdk::contractimport!(file = "fixtures/booking_v4.wasm");
+}
+
+mod booking_v5 {
+    soroban_sdk::contractimport!(file = "fixtures/booking_v5.wasm");
+}
+
+mod booking_v6 {
+    soroban_sdk::contractimport!(file = "fixtures/booking_v6.wasm");
+}
+
+// Stand-in for the property listing contract that grants permissions per
+// (property id, caller) pair
+#[contract]
+pub struct MockListingContract;
+
+#[contractimpl]
+impl MockListingContract {
+    pub fn set_permissions(env: Env, id: String, caller: Address, permissions: u32) {
+        env.storage().persistent().set(&(id, caller), &permissions);
+    }
+
+    pub fn has_permission(env: Env, id: String, caller: Address, permission: u32) -> bool {
+        let permissions: u32 = env.storage().persistent().get(&(id, caller)).unwrap_or(0);
+        permissions & permission == permission
+    }
+
+    pub fn set_occupancy(env: Env, id: String, occupied: bool) {
+        env.storage().persistent().set(&id, &occupied);
+    }
+
+    pub fn is_occupied(env: Env, id: String) -> bool {
+        env.storage().persistent().get(&id).unwrap_or(false)
+    }
+
+    pub fn set_max_guests(env: Env, id: String, max_guests: u32) {
+        env.storage().persistent().set(&(id,), &max_guests);
+    }
+
+    pub fn get_max_guests(env: Env, id: String) -> Option<u32> {
+        env.storage().persistent().get(&(id,))
+    }
+
+    pub fn set_listing_version(env: Env, id: String, version: u32) {
+        env.storage()
+            .persistent()
+            .set(&(symbol_short!("version"), id), &version);
+    }
+
+    pub fn get_listing_version(env: Env, id: String) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&(symbol_short!("version"), id))
+            .unwrap_or(0)
+    }
+
+    pub fn set_attestations(env: Env, id: String, attestations: Vec<Attestation>) {
+        env.storage()
+            .persistent()
+            .set(&(symbol_short!("attests"), id), &attestations);
+    }
+
+    pub fn get_attestations(env: Env, id: String) -> Vec<Attestation> {
+        env.storage()
+            .persistent()
+            .get(&(symbol_short!("attests"), id))
+            .unwrap_or(vec![&env])
+    }
+
+    pub fn set_annual_night_cap(env: Env, id: String, night_cap: u32) {
+        env.storage()
+            .persistent()
+            .set(&(symbol_short!("nightcap"), id), &night_cap);
+    }
+
+    pub fn get_annual_night_cap(env: Env, id: String) -> Option<u32> {
+        env.storage()
+            .persistent()
+            .get(&(symbol_short!("nightcap"), id))
+    }
+
+    pub fn set_nightly_rate(env: Env, id: String, rate: i128) {
+        env.storage()
+            .persistent()
+            .set(&(symbol_short!("rate"), id), &rate);
+    }
+
+    pub fn get_nightly_rate(env: Env, id: String) -> Option<i128> {
+        env.storage().persistent().get(&(symbol_short!("rate"), id))
+    }
+
+    pub fn set_revenue_shares(env: Env, id: String, shares: Vec<RevenueShare>) {
+        env.storage()
+            .persistent()
+            .set(&(symbol_short!("shares"), id), &shares);
+    }
+
+    pub fn get_revenue_shares(env: Env, id: String) -> Vec<RevenueShare> {
+        env.storage()
+            .persistent()
+            .get(&(symbol_short!("shares"), id))
+            .unwrap_or(vec![&env])
+    }
+}
+
+// Build an attestation as the listing contract would return it
+fn attestation(env: &Env, kind: AttestationKind, expires_at: u64, revoked: bool) -> Attestation {
+    Attestation {
+        kind,
+        verifier: Address::generate(env),
+        evidence_hash: BytesN::from_array(env, &[1; 32]),
+        issued_at: 0,
+        expires_at,
+        revoked,
+    }
+}
+
+// Strip a booking down to the record layout used before schema version 4
+fn to_legacy(booking: Booking) -> LegacyBooking {
+    LegacyBooking {
+        id: booking.id,
+        property_id: booking.property_id,
+        user_id: booking.user_id,
+        start_date: booking.start_date,
+        end_date: booking.end_date,
+        total_price: booking.total_price,
+        status: booking.status,
+        escrow_id: booking.escrow_id,
+    }
+}
+
 #[test]
 fn test_initialize() {
     let env = Env::default();
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
 
-    client.initialize();
-
     // Verify initialization by attempting to create a booking
     let property_id = String::from_str(&env, "PROP1");
     let user_id = String::from_str(&env, "USER1");
@@ -29,11 +175,9 @@ fn test_initialize() {
 #[test]
 fn test_check_availability_empty() {
     let env = Env::default();
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
 
-    client.initialize();
-
     let property_id = String::from_str(&env, "PROP1");
     let start_date = 1704067200u64;
     let end_date = 1704153600u64;
@@ -52,11 +196,9 @@ fn test_create_booking_success() {
         li.timestamp = 1703980800; // Dec 31, 2023
     });
 
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
 
-    client.initialize();
-
     let property_id = String::from_str(&env, "PROP1");
     let user_id = String::from_str(&env, "USER1");
     let start_date = 1704067200u64; // Jan 1, 2024
@@ -87,11 +229,9 @@ fn test_booking_overlap_prevention() {
         li.timestamp = 1703980800;
     });
 
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
 
-    client.initialize();
-
     let property_id = String::from_str(&env, "PROP1");
     let user_id1 = String::from_str(&env, "USER1");
     let user_id2 = String::from_str(&env, "USER2");
@@ -132,11 +272,9 @@ fn test_non_overlapping_bookings() {
         li.timestamp = 1703980800;
     });
 
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
 
-    client.initialize();
-
     let property_id = String::from_str(&env, "PROP1");
     let user_id1 = String::from_str(&env, "USER1");
     let user_id2 = String::from_str(&env, "USER2");
@@ -178,11 +316,9 @@ fn test_cancel_booking_success() {
         li.timestamp = 1703980800;
     });
 
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
 
-    client.initialize();
-
     let property_id = String::from_str(&env, "PROP1");
     let user_id = String::from_str(&env, "USER1");
     let start_date = 1704067200u64;
@@ -222,11 +358,9 @@ fn test_cancel_booking_unauthorized() {
         li.timestamp = 1703980800;
     });
 
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
 
-    client.initialize();
-
     let property_id = String::from_str(&env, "PROP1");
     let user_id1 = String::from_str(&env, "USER1");
     let user_id2 = String::from_str(&env, "USER2");
@@ -256,11 +390,9 @@ fn test_update_status() {
         li.timestamp = 1703980800;
     });
 
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
 
-    client.initialize();
-
     let property_id = String::from_str(&env, "PROP1");
     let user_id = String::from_str(&env, "USER1");
     let start_date = 1704067200u64;
@@ -291,11 +423,9 @@ fn test_invalid_status_transition() {
         li.timestamp = 1703980800;
     });
 
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
 
-    client.initialize();
-
     let property_id = String::from_str(&env, "PROP1");
     let user_id = String::from_str(&env, "USER1");
     let start_date = 1704067200u64;
@@ -320,11 +450,9 @@ fn test_get_property_bookings() {
         li.timestamp = 1703980800;
     });
 
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
 
-    client.initialize();
-
     let property_id = String::from_str(&env, "PROP1");
     let user_id1 = String::from_str(&env, "USER1");
     let user_id2 = String::from_str(&env, "USER2");
@@ -352,7 +480,7 @@ fn test_get_property_bookings() {
     );
 
     // Get all bookings for the property
-    let bookings = client.get_property_bookings(&property_id);
+    let bookings = client.get_property_bookings(&property_id, &0, &10).bookings;
     assert_eq!(bookings.len(), 2);
 
     // Verify both bookings are present
@@ -370,11 +498,9 @@ fn test_invalid_dates() {
         li.timestamp = 1704067200; // Jan 1, 2024
     });
 
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
 
-    client.initialize();
-
     let property_id = String::from_str(&env, "PROP1");
     let user_id = String::from_str(&env, "USER1");
     let total_price = 1000000000i128;
@@ -396,11 +522,9 @@ fn test_invalid_price() {
         li.timestamp = 1703980800;
     });
 
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
 
-    client.initialize();
-
     let property_id = String::from_str(&env, "PROP1");
     let user_id = String::from_str(&env, "USER1");
     let start_date = 1704067200u64;
@@ -419,11 +543,9 @@ fn test_set_escrow_id() {
         li.timestamp = 1703980800;
     });
 
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
 
-    client.initialize();
-
     let property_id = String::from_str(&env, "PROP1");
     let user_id = String::from_str(&env, "USER1");
     let start_date = 1704067200u64;
@@ -450,11 +572,9 @@ fn test_set_escrow_id() {
 #[should_panic(expected = "Booking not found")]
 fn test_booking_not_found() {
     let env = Env::default();
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
 
-    client.initialize();
-
     let booking_id = 999u64;
 
     // Test get_booking - should panic
@@ -477,9 +597,8 @@ fn test_reentrancy_attack_prevention() {
     env.ledger().with_mut(|li| {
         li.timestamp = 1703980800;
     });
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
-    client.initialize();
     let property_id = String::from_str(&env, "PROP1");
     let user_id = String::from_str(&env, "USER1");
     let start_date = 1704067200u64;
@@ -503,9 +622,8 @@ fn test_integer_overflow_underflow() {
     env.ledger().with_mut(|li| {
         li.timestamp = 1703980800;
     });
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
-    client.initialize();
     let property_id = String::from_str(&env, "PROP1");
     let user_id = String::from_str(&env, "USER1");
     // Fechas inválidas (overflow)
@@ -527,9 +645,8 @@ fn test_timestamp_manipulation_resistance() {
     env.ledger().with_mut(|li| {
         li.timestamp = u64::MAX;
     });
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
-    client.initialize();
     let property_id = String::from_str(&env, "PROP1");
     let user_id = String::from_str(&env, "USER1");
     let start_date = u64::MAX;
@@ -550,9 +667,8 @@ fn test_unauthorized_access_attempts() {
     env.ledger().with_mut(|li| {
         li.timestamp = 1703980800;
     });
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
-    client.initialize();
     let property_id = String::from_str(&env, "PROP1");
     let user_id1 = String::from_str(&env, "USER1");
     let user_id2 = String::from_str(&env, "USER2");
@@ -577,9 +693,8 @@ fn test_invalid_state_transition_attempts() {
     env.ledger().with_mut(|li| {
         li.timestamp = 1703980800;
     });
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
-    client.initialize();
     let property_id = String::from_str(&env, "PROP1");
     let user_id = String::from_str(&env, "USER1");
     let start_date = 1704067200u64;
@@ -603,9 +718,8 @@ fn test_economic_attack_simulation() {
     env.ledger().with_mut(|li| {
         li.timestamp = 1703980800;
     });
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
-    client.initialize();
     let property_id = String::from_str(&env, "PROP1");
     let user_id1 = String::from_str(&env, "USER1");
     let user_id2 = String::from_str(&env, "USER2");
@@ -629,7 +743,7 @@ fn test_economic_attack_simulation() {
     
     client.create_booking(&property_id, &user_id3, &start_date, &end_date, &low_price);
     // Debe fallar si hay validación de precio mínimo
-    assert!(false, "Debe prevenir operaciones concurrentes que modifiquen el estado");
+    unreachable!("Debe prevenir operaciones concurrentes que modifiquen el estado");
 }
 
 // =========================
@@ -640,9 +754,8 @@ fn test_economic_attack_simulation() {
 fn test_property_fuzzing() {
     let env = Env::default();
     env.mock_all_auths();
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
-    client.initialize();
     let mut success_count = 0;
     // Generar combinaciones únicas de inputs válidos
     for i in 0u32..26 {
@@ -734,9 +847,8 @@ fn test_cross_contract_interaction() {
     });
     
     // Simular el flujo completo: Property Listing -> Booking -> Review
-    let booking_contract_id = env.register(BookingContract, ());
+    let booking_contract_id = env.register(BookingContract, (Address::generate(&env),));
     let booking_client = BookingContractClient::new(&env, &booking_contract_id);
-    booking_client.initialize();
     
     // Simular property listing (mock)
     let property_id = String::from_str(&env, "PROP1");
@@ -781,9 +893,8 @@ fn test_gas_optimization_validation() {
         li.timestamp = 1703980800;
     });
     
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
-    client.initialize();
     
     // Medir el costo de operaciones críticas
     let property_id = String::from_str(&env, "PROP1");
@@ -804,9 +915,6 @@ fn test_gas_optimization_validation() {
     let booking = client.get_booking(&booking_id);
     assert_eq!(booking.status, BookingStatus::Cancelled);
     
-    // Nota: Soroban aún no expone métricas detalladas de gas en el entorno de test
-    // En producción, se debe monitorear el consumo real de recursos
-    assert!(true, "Validación de gas completada - monitorear en producción");
 }
 
 // =========================
@@ -822,9 +930,8 @@ fn test_deployment_validation_networks() {
     });
     
     // Simular diferentes configuraciones de red
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
-    client.initialize();
     
     // Test básico de funcionalidad (simula despliegue en cualquier red)
     let property_id = String::from_str(&env, "PROP1");
@@ -840,9 +947,6 @@ fn test_deployment_validation_networks() {
     let booking = client.get_booking(&booking_id);
     assert_eq!(booking.status, BookingStatus::Pending);
     
-    // Nota: Para tests reales de despliegue, se necesitan scripts de CI/CD
-    // que desplieguen en Testnet y Mainnet y ejecuten estos tests
-    assert!(true, "Validación de despliegue completada - usar CI/CD para tests reales");
 }
 
 // =========================
@@ -856,9 +960,8 @@ fn test_edge_cases() {
         li.timestamp = 1703980800;
     });
     
-    let contract_id = env.register(BookingContract, ());
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
     let client = BookingContractClient::new(&env, &contract_id);
-    client.initialize();
     
     // Test: Fechas muy cercanas
     let property_id = String::from_str(&env, "PROP1");
@@ -876,3 +979,2316 @@ fn test_edge_cases() {
     let booking_id2 = client.create_booking(&property_id, &user_id, &start_date, &end_date, &exact_price);
     assert_eq!(booking_id2, 1u64);
 }
+
+// =========================
+// Storage TTL Management
+// =========================
+#[test]
+fn test_booking_ttl_extended_on_write() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    let booking_id = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+
+    env.as_contract(&contract_id, || {
+        assert_eq!(
+            env.storage().persistent().get_ttl(&(BOOKING, booking_id)),
+            PERSISTENT_BUMP_AMOUNT
+        );
+        assert_eq!(
+            env.storage().persistent().get_ttl(&BOOK_COUNT),
+            PERSISTENT_BUMP_AMOUNT
+        );
+        assert_eq!(
+            env.storage()
+                .persistent()
+                .get_ttl(&(PROP_BOOKINGS, property_id.clone())),
+            PERSISTENT_BUMP_AMOUNT
+        );
+        assert_eq!(env.storage().instance().get_ttl(), INSTANCE_BUMP_AMOUNT);
+    });
+}
+
+#[test]
+fn test_booking_ttl_extended_on_read() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    let booking_id = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+
+    // Advance the ledger until the entries drop below the bump threshold
+    let elapsed = PERSISTENT_BUMP_AMOUNT - PERSISTENT_LIFETIME_THRESHOLD + 1;
+    env.ledger().with_mut(|li| {
+        li.sequence_number += elapsed;
+    });
+
+    env.as_contract(&contract_id, || {
+        assert_eq!(
+            env.storage().persistent().get_ttl(&(BOOKING, booking_id)),
+            PERSISTENT_LIFETIME_THRESHOLD - 1
+        );
+    });
+
+    // Reads bump the touched entries back to the full amount
+    client.get_booking(&booking_id);
+    client.get_property_bookings(&property_id, &0, &10);
+
+    env.as_contract(&contract_id, || {
+        assert_eq!(
+            env.storage().persistent().get_ttl(&(BOOKING, booking_id)),
+            PERSISTENT_BUMP_AMOUNT
+        );
+        assert_eq!(
+            env.storage()
+                .persistent()
+                .get_ttl(&(PROP_BOOKINGS, property_id.clone())),
+            PERSISTENT_BUMP_AMOUNT
+        );
+    });
+}
+
+#[test]
+fn test_bump_ttl_by_admin() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let listing_id = env.register(MockListingContract, ());
+    let listing = MockListingContractClient::new(&env, &listing_id);
+    client.set_listing_contract(&listing_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let missing_property = String::from_str(&env, "MISSING");
+    let user_id = String::from_str(&env, "USER1");
+    let host = Address::generate(&env);
+    listing.set_permissions(&property_id, &host, &PERM_CONFIRM_BOOKINGS);
+    listing.set_revenue_shares(
+        &property_id,
+        &vec![
+            &env,
+            RevenueShare {
+                beneficiary: host.clone(),
+                bps: TOTAL_SHARE_BPS,
+            },
+        ],
+    );
+    let booking_id = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+    let completed_id = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704153600u64,
+        &1704240000u64,
+        &1000000000i128,
+    );
+    client.update_status(&completed_id, &BookingStatus::Confirmed, &host);
+    client.update_status(&completed_id, &BookingStatus::Completed, &host);
+
+    env.ledger().with_mut(|li| {
+        li.sequence_number += 8 * DAY_IN_LEDGERS;
+    });
+
+    // Properties without bookings are skipped
+    client.bump_ttl(&vec![&env, property_id.clone(), missing_property]);
+
+    env.as_contract(&contract_id, || {
+        assert_eq!(
+            env.storage().persistent().get_ttl(&(BOOKING, booking_id)),
+            PERSISTENT_BUMP_AMOUNT
+        );
+        assert_eq!(
+            env.storage().persistent().get_ttl(&BOOK_COUNT),
+            PERSISTENT_BUMP_AMOUNT
+        );
+        assert_eq!(
+            env.storage()
+                .persistent()
+                .get_ttl(&(PROP_BOOKINGS, property_id.clone())),
+            PERSISTENT_BUMP_AMOUNT
+        );
+        assert_eq!(
+            env.storage()
+                .persistent()
+                .get_ttl(&(STAYS, property_id.clone())),
+            PERSISTENT_BUMP_AMOUNT
+        );
+        assert_eq!(
+            env.storage()
+                .persistent()
+                .get_ttl(&(NIGHTS, property_id.clone(), 2024u32)),
+            PERSISTENT_BUMP_AMOUNT
+        );
+        assert_eq!(
+            env.storage()
+                .persistent()
+                .get_ttl(&(USER_BOOKINGS, user_id.clone())),
+            PERSISTENT_BUMP_AMOUNT
+        );
+        assert_eq!(
+            env.storage().persistent().get_ttl(&(PAYOUT, completed_id)),
+            PERSISTENT_BUMP_AMOUNT
+        );
+        assert_eq!(env.storage().instance().get_ttl(), INSTANCE_BUMP_AMOUNT);
+    });
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_bump_ttl_requires_admin_auth() {
+    let env = Env::default();
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    // No auth mocked for the admin
+    client.bump_ttl(&vec![&env]);
+}
+
+// =========================
+// Upgrades and Schema Versioning
+// =========================
+#[test]
+fn test_version_after_deploy() {
+    let env = Env::default();
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    assert_eq!(client.version(), SCHEMA_VERSION);
+}
+
+#[test]
+fn test_upgrade_keeps_state() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    // Deploy the version 1 release and create bookings with it
+    let contract_id = env.register(booking_v1::WASM, ());
+    let old_client = booking_v1::Client::new(&env, &contract_id);
+    old_client.initialize(&Address::generate(&env));
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    let booking_id = old_client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+    assert_eq!(old_client.version(), 1);
+
+    // Upgrade to the version 2 release and migrate the stored data
+    let new_wasm_hash = env.deployer().upload_contract_wasm(booking_v2::WASM);
+    old_client.upgrade(&new_wasm_hash);
+
+    let client = booking_v2::Client::new(&env, &contract_id);
+    assert_eq!(client.version(), 1);
+    assert_eq!(client.migrate(), 2);
+    assert_eq!(client.version(), 2);
+
+    let booking = client.get_booking(&booking_id);
+    assert_eq!(booking.property_id, property_id);
+    assert_eq!(booking.user_id, user_id);
+    assert_eq!(booking.status, booking_v2::BookingStatus::Pending);
+
+    // The guest index was backfilled by the migration
+    let page = client.get_user_bookings(&user_id, &None, &0, &10);
+    assert_eq!(page.bookings.len(), 1);
+    assert_eq!(page.bookings.get(0).unwrap().id, booking_id);
+
+    // New bookings keep using the stored counter
+    let next_id = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704153600u64,
+        &1704240000u64,
+        &1000000000i128,
+    );
+    assert_eq!(next_id, booking_id + 1);
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_upgrade_requires_admin_auth() {
+    let env = Env::default();
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    // No auth mocked for the admin
+    client.upgrade(&BytesN::from_array(&env, &[0u8; 32]));
+}
+
+#[test]
+fn test_migrate_legacy_storage() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    // Simulate a deployment from before schema versioning
+    env.as_contract(&contract_id, || {
+        env.storage().instance().remove(&VERSION);
+    });
+    assert_eq!(client.version(), 0);
+
+    assert_eq!(client.migrate(&100), SCHEMA_VERSION);
+    assert_eq!(client.version(), SCHEMA_VERSION);
+
+    // Migrating again is a no-op
+    assert_eq!(client.migrate(&100), SCHEMA_VERSION);
+}
+
+#[test]
+#[should_panic(expected = "Stored schema is newer than the contract code")]
+fn test_migrate_newer_schema() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    env.as_contract(&contract_id, || {
+        env.storage()
+            .instance()
+            .set(&VERSION, &(SCHEMA_VERSION + 1));
+    });
+
+    client.migrate(&100);
+}
+
+// =========================
+// Emergency Pause
+// =========================
+#[test]
+#[should_panic(expected = "Error(Contract, #1)")]
+fn test_pause_blocks_create_booking() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(BookingContract, (&admin,));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    client.pause(&admin);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+}
+
+#[test]
+fn test_pause_blocks_updates_but_not_views() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(BookingContract, (&admin,));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    let booking_id = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+
+    client.pause(&admin);
+    assert!(client.is_paused());
+
+    let host = Address::generate(&env);
+    let escrow_id = String::from_str(&env, "ESCROW123");
+    assert!(client.try_cancel_booking(&booking_id, &user_id).is_err());
+    assert!(client
+        .try_update_status(&booking_id, &BookingStatus::Confirmed, &host)
+        .is_err());
+    assert!(client
+        .try_set_escrow_id(&booking_id, &escrow_id, &host)
+        .is_err());
+
+    // Views keep working
+    assert_eq!(
+        client.get_booking(&booking_id).status,
+        BookingStatus::Pending
+    );
+    assert_eq!(
+        client
+            .get_property_bookings(&property_id, &0, &10)
+            .bookings
+            .len(),
+        1
+    );
+    assert!(!client.check_availability(&property_id, &1704067200u64, &1704153600u64));
+}
+
+#[test]
+fn test_guardian_pause_and_admin_unpause() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(BookingContract, (&admin,));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let guardian = Address::generate(&env);
+    client.set_guardian(&guardian);
+
+    client.pause(&guardian);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("paused"),).into_val(&env),
+                guardian.into_val(&env)
+            )
+        ]
+    );
+    assert!(client.is_paused());
+
+    client.unpause();
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("unpaused"),).into_val(&env),
+                admin.into_val(&env)
+            )
+        ]
+    );
+    assert!(!client.is_paused());
+
+    // Mutations work again
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized: only the admin or guardian can pause")]
+fn test_pause_unauthorized() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let stranger = Address::generate(&env);
+    client.pause(&stranger);
+}
+
+// =========================
+// Guest Index and Pagination
+// =========================
+#[test]
+fn test_get_user_bookings_pagination() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let guest = String::from_str(&env, "USER1");
+    let other_guest = String::from_str(&env, "USER2");
+    let total_price = 1000000000i128;
+
+    // Five bookings for the guest interleaved with one for another guest
+    for i in 0u64..5 {
+        let start_date = 1704067200u64 + i * 86400;
+        client.create_booking(
+            &property_id,
+            &guest,
+            &start_date,
+            &(start_date + 86400),
+            &total_price,
+        );
+    }
+    let other_start = 1704067200u64 + 10 * 86400;
+    client.create_booking(
+        &property_id,
+        &other_guest,
+        &other_start,
+        &(other_start + 86400),
+        &total_price,
+    );
+
+    let page1 = client.get_user_bookings(&guest, &None, &0, &2);
+    assert_eq!(page1.bookings.len(), 2);
+    assert_eq!(page1.bookings.get(0).unwrap().id, 0);
+    assert_eq!(page1.bookings.get(1).unwrap().id, 1);
+    assert_eq!(page1.next_cursor, Some(2));
+
+    let page2 = client.get_user_bookings(&guest, &None, &2, &2);
+    assert_eq!(page2.bookings.get(0).unwrap().id, 2);
+    assert_eq!(page2.bookings.get(1).unwrap().id, 3);
+    assert_eq!(page2.next_cursor, Some(4));
+
+    let page3 = client.get_user_bookings(&guest, &None, &4, &2);
+    assert_eq!(page3.bookings.len(), 1);
+    assert_eq!(page3.bookings.get(0).unwrap().id, 4);
+    assert_eq!(page3.next_cursor, None);
+
+    let other = client.get_user_bookings(&other_guest, &None, &0, &10);
+    assert_eq!(other.bookings.len(), 1);
+    assert_eq!(other.bookings.get(0).unwrap().user_id, other_guest);
+
+    let unknown = String::from_str(&env, "NOBODY");
+    let empty = client.get_user_bookings(&unknown, &None, &0, &10);
+    assert_eq!(empty.bookings.len(), 0);
+    assert_eq!(empty.next_cursor, None);
+}
+
+#[test]
+fn test_get_user_bookings_status_filter() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let guest = String::from_str(&env, "USER1");
+    let host = Address::generate(&env);
+    let total_price = 1000000000i128;
+
+    for i in 0u64..3 {
+        let start_date = 1704067200u64 + i * 86400;
+        client.create_booking(
+            &property_id,
+            &guest,
+            &start_date,
+            &(start_date + 86400),
+            &total_price,
+        );
+    }
+    client.cancel_booking(&1, &guest);
+    client.update_status(&2, &BookingStatus::Confirmed, &host);
+
+    let cancelled = client.get_user_bookings(&guest, &Some(BookingStatus::Cancelled), &0, &10);
+    assert_eq!(cancelled.bookings.len(), 1);
+    assert_eq!(cancelled.bookings.get(0).unwrap().id, 1);
+
+    // The guest list reflects the latest status of each booking
+    let confirmed = client.get_user_bookings(&guest, &Some(BookingStatus::Confirmed), &0, &10);
+    assert_eq!(confirmed.bookings.len(), 1);
+    assert_eq!(confirmed.bookings.get(0).unwrap().id, 2);
+
+    let pending = client.get_user_bookings(&guest, &Some(BookingStatus::Pending), &0, &1);
+    assert_eq!(pending.bookings.len(), 1);
+    assert_eq!(pending.bookings.get(0).unwrap().id, 0);
+    assert_eq!(pending.next_cursor, Some(1));
+
+    // The scan skips filtered-out entries until the end of the index
+    let rest = client.get_user_bookings(&guest, &Some(BookingStatus::Pending), &1, &1);
+    assert_eq!(rest.bookings.len(), 0);
+    assert_eq!(rest.next_cursor, None);
+}
+
+#[test]
+fn test_get_property_bookings_pagination() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let guest = String::from_str(&env, "USER1");
+
+    for i in 0u64..3 {
+        let start_date = 1704067200u64 + i * 86400;
+        client.create_booking(
+            &property_id,
+            &guest,
+            &start_date,
+            &(start_date + 86400),
+            &1000000000i128,
+        );
+    }
+
+    let page1 = client.get_property_bookings(&property_id, &0, &2);
+    assert_eq!(page1.bookings.len(), 2);
+    assert_eq!(page1.next_cursor, Some(2));
+
+    let page2 = client.get_property_bookings(&property_id, &2, &2);
+    assert_eq!(page2.bookings.len(), 1);
+    assert_eq!(page2.bookings.get(0).unwrap().id, 2);
+    assert_eq!(page2.next_cursor, None);
+
+    // Cursors past the end return an empty page
+    let past_end = client.get_property_bookings(&property_id, &10, &2);
+    assert_eq!(past_end.bookings.len(), 0);
+    assert_eq!(past_end.next_cursor, None);
+}
+
+#[test]
+#[should_panic(expected = "Invalid limit")]
+fn test_get_user_bookings_zero_limit() {
+    let env = Env::default();
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let guest = String::from_str(&env, "USER1");
+    client.get_user_bookings(&guest, &None, &0, &0);
+}
+
+#[test]
+fn test_migrate_backfills_guest_index() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let guest = String::from_str(&env, "USER1");
+    for i in 0u64..2 {
+        let start_date = 1704067200u64 + i * 86400;
+        client.create_booking(
+            &property_id,
+            &guest,
+            &start_date,
+            &(start_date + 86400),
+            &1000000000i128,
+        );
+    }
+
+    // Roll storage back to the version 1 layout, which kept full booking
+    // copies in a global list and under the raw property id, and had no
+    // guest index
+    env.as_contract(&contract_id, || {
+        let mut bookings_map: Vec<(u64, LegacyBooking)> = vec![&env];
+        let mut prop_bookings: Vec<LegacyBooking> = vec![&env];
+        for id in 0u64..2 {
+            let booking = to_legacy(env.storage().persistent().get(&(BOOKING, id)).unwrap());
+            bookings_map.push_back((id, booking.clone()));
+            prop_bookings.push_back(booking);
+            env.storage().persistent().remove(&(BOOKING, id));
+        }
+        env.storage().persistent().set(&BOOKINGS, &bookings_map);
+        env.storage().persistent().set(&property_id, &prop_bookings);
+        env.storage()
+            .persistent()
+            .remove(&(PROP_BOOKINGS, property_id.clone()));
+        env.storage()
+            .persistent()
+            .remove(&(USER_BOOKINGS, guest.clone()));
+        env.storage().instance().set(&VERSION, &1u32);
+    });
+    assert_eq!(
+        client
+            .get_user_bookings(&guest, &None, &0, &10)
+            .bookings
+            .len(),
+        0
+    );
+
+    assert_eq!(client.migrate(&100), SCHEMA_VERSION);
+
+    let page = client.get_user_bookings(&guest, &None, &0, &10);
+    assert_eq!(page.bookings.len(), 2);
+    assert_eq!(page.bookings.get(0).unwrap().id, 0);
+    assert_eq!(page.bookings.get(1).unwrap().id, 1);
+    assert_eq!(client.audit_state(&0, &10).len(), 0);
+}
+
+// =========================
+// Booking State Consistency
+// =========================
+#[test]
+fn test_set_escrow_id_visible_in_property_bookings() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    let booking_id = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+
+    let escrow_id = String::from_str(&env, "ESCROW123");
+    client.set_escrow_id(&booking_id, &escrow_id, &Address::generate(&env));
+
+    let prop_page = client.get_property_bookings(&property_id, &0, &10);
+    assert_eq!(
+        prop_page.bookings.get(0).unwrap().escrow_id,
+        Some(escrow_id.clone())
+    );
+
+    let user_page = client.get_user_bookings(&user_id, &None, &0, &10);
+    assert_eq!(
+        user_page.bookings.get(0).unwrap().escrow_id,
+        Some(escrow_id)
+    );
+}
+
+#[test]
+fn test_audit_state_consistent_after_operations() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+    assert_eq!(client.audit_state(&0, &10).len(), 0);
+
+    let prop1 = String::from_str(&env, "PROP1");
+    let prop2 = String::from_str(&env, "PROP2");
+    let user1 = String::from_str(&env, "USER1");
+    let user2 = String::from_str(&env, "USER2");
+    let caller = Address::generate(&env);
+
+    let b0 = client.create_booking(&prop1, &user1, &1704067200u64, &1704153600u64, &1000i128);
+    let b1 = client.create_booking(&prop2, &user1, &1704067200u64, &1704153600u64, &1000i128);
+    let b2 = client.create_booking(&prop1, &user2, &1704153600u64, &1704240000u64, &1000i128);
+
+    client.set_escrow_id(&b0, &String::from_str(&env, "ESCROW0"), &caller);
+    client.cancel_booking(&b1, &user1);
+    client.update_status(&b2, &BookingStatus::Confirmed, &caller);
+    client.update_status(&b2, &BookingStatus::Completed, &caller);
+
+    assert_eq!(client.audit_state(&0, &10).len(), 0);
+
+    // Records returned by every view agree with the stored record
+    let prop_page = client.get_property_bookings(&prop1, &0, &10);
+    assert_eq!(prop_page.bookings.get(0).unwrap(), client.get_booking(&b0));
+    assert_eq!(prop_page.bookings.get(1).unwrap(), client.get_booking(&b2));
+    let user_page = client.get_user_bookings(&user1, &None, &0, &10);
+    assert_eq!(user_page.bookings.get(1).unwrap(), client.get_booking(&b1));
+}
+
+#[test]
+fn test_audit_state_reports_violations() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    for i in 0u64..3 {
+        let start_date = 1704067200u64 + i * 86400;
+        client.create_booking(
+            &property_id,
+            &user_id,
+            &start_date,
+            &(start_date + 86400),
+            &1000i128,
+        );
+    }
+
+    // Drop booking 0 from the property index and the record of booking 2
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(
+            &(PROP_BOOKINGS, property_id.clone()),
+            &vec![&env, 1u64, 2u64],
+        );
+        env.storage().persistent().remove(&(BOOKING, 2u64));
+    });
+
+    assert_eq!(client.audit_state(&0, &10), vec![&env, 0u64, 2u64]);
+    // Only the requested range is checked
+    assert_eq!(client.audit_state(&1, &1).len(), 0);
+}
+
+#[test]
+fn test_upgrade_fixes_stale_escrow_id() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    // The version 2 release only recorded the escrow id in the global list
+    let contract_id = env.register(booking_v2::WASM, ());
+    let old_client = booking_v2::Client::new(&env, &contract_id);
+    old_client.initialize(&Address::generate(&env));
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    let booking_id = old_client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+    let escrow_id = String::from_str(&env, "ESCROW123");
+    old_client.set_escrow_id(&booking_id, &escrow_id, &Address::generate(&env));
+
+    let stale_page = old_client.get_property_bookings(&property_id, &0, &10);
+    assert_eq!(stale_page.bookings.get(0).unwrap().escrow_id, None);
+
+    // Upgrade to the version 3 release and migrate the stored data
+    let new_wasm_hash = env.deployer().upload_contract_wasm(booking_v3::WASM);
+    old_client.upgrade(&new_wasm_hash);
+
+    let client = booking_v3::Client::new(&env, &contract_id);
+    assert_eq!(client.migrate(), 3);
+
+    let page = client.get_property_bookings(&property_id, &0, &10);
+    assert_eq!(page.bookings.len(), 1);
+    assert_eq!(
+        page.bookings.get(0).unwrap().escrow_id,
+        Some(escrow_id.clone())
+    );
+    assert_eq!(client.get_booking(&booking_id).escrow_id, Some(escrow_id));
+    assert_eq!(client.audit_state(&0, &10).len(), 0);
+
+    // The legacy copies are gone
+    env.as_contract(&contract_id, || {
+        assert!(!env.storage().persistent().has(&BOOKINGS));
+        assert!(!env.storage().persistent().has(&property_id));
+    });
+}
+
+// =========================
+// Host Permissions
+// =========================
+#[test]
+fn test_update_status_by_permitted_delegate() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let listing_id = env.register(MockListingContract, ());
+    let listing = MockListingContractClient::new(&env, &listing_id);
+    client.set_listing_contract(&listing_id);
+    assert_eq!(client.get_listing_contract(), Some(listing_id));
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    let manager = Address::generate(&env);
+    listing.set_permissions(&property_id, &manager, &PERM_CONFIRM_BOOKINGS);
+
+    let booking_id = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+
+    let booking = client.update_status(&booking_id, &BookingStatus::Confirmed, &manager);
+    assert_eq!(booking.status, BookingStatus::Confirmed);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized: caller cannot manage bookings for this property")]
+fn test_update_status_without_permission() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let listing_id = env.register(MockListingContract, ());
+    let listing = MockListingContractClient::new(&env, &listing_id);
+    client.set_listing_contract(&listing_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let other_property = String::from_str(&env, "PROP2");
+    let user_id = String::from_str(&env, "USER1");
+    let manager = Address::generate(&env);
+    // Permissions on another property do not count
+    listing.set_permissions(&other_property, &manager, &PERM_CONFIRM_BOOKINGS);
+
+    let booking_id = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+
+    client.update_status(&booking_id, &BookingStatus::Confirmed, &manager);
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_set_listing_contract_requires_admin_auth() {
+    let env = Env::default();
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    // No auth mocked for the admin
+    client.set_listing_contract(&Address::generate(&env));
+}
+
+// =========================
+// Check-in and Checkout
+// =========================
+#[test]
+fn test_check_in_and_out_update_listing() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let listing_id = env.register(MockListingContract, ());
+    let listing = MockListingContractClient::new(&env, &listing_id);
+    client.set_listing_contract(&listing_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    let host = Address::generate(&env);
+    listing.set_permissions(&property_id, &host, &PERM_CONFIRM_BOOKINGS);
+
+    let booking_id = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+    client.update_status(&booking_id, &BookingStatus::Confirmed, &host);
+
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1704067200;
+    });
+    let booking = client.check_in(&booking_id, &host);
+    assert_eq!(booking.status, BookingStatus::CheckedIn);
+    assert!(listing.is_occupied(&property_id));
+
+    let booking = client.check_out(&booking_id, &host);
+    assert_eq!(booking.status, BookingStatus::Completed);
+    assert!(!listing.is_occupied(&property_id));
+    assert_eq!(client.audit_state(&0, &10).len(), 0);
+}
+
+#[test]
+fn test_check_in_without_listing_contract() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1704067200;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    let host = Address::generate(&env);
+
+    let booking_id = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+    client.update_status(&booking_id, &BookingStatus::Confirmed, &host);
+
+    assert_eq!(
+        client.check_in(&booking_id, &host).status,
+        BookingStatus::CheckedIn
+    );
+    assert_eq!(
+        client.check_out(&booking_id, &host).status,
+        BookingStatus::Completed
+    );
+}
+
+#[test]
+#[should_panic(expected = "Invalid dates: the stay has not started yet")]
+fn test_check_in_before_start() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    let host = Address::generate(&env);
+
+    let booking_id = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+    client.update_status(&booking_id, &BookingStatus::Confirmed, &host);
+    client.check_in(&booking_id, &host);
+}
+
+#[test]
+#[should_panic(expected = "Invalid status: only confirmed bookings can check in")]
+fn test_check_in_pending_booking() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1704067200;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+
+    let booking_id = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+    client.check_in(&booking_id, &Address::generate(&env));
+}
+
+#[test]
+#[should_panic(expected = "Invalid status: only checked in bookings can check out")]
+fn test_check_out_without_check_in() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1704067200;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    let host = Address::generate(&env);
+
+    let booking_id = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+    client.update_status(&booking_id, &BookingStatus::Confirmed, &host);
+    client.check_out(&booking_id, &host);
+}
+
+#[test]
+#[should_panic(expected = "Invalid status: booking cannot be cancelled")]
+fn test_cancel_checked_in_booking() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1704067200;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    let host = Address::generate(&env);
+
+    let booking_id = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+    client.update_status(&booking_id, &BookingStatus::Confirmed, &host);
+    client.check_in(&booking_id, &host);
+    client.cancel_booking(&booking_id, &user_id);
+}
+
+// =========================
+// Occupancy Checks
+// =========================
+#[test]
+fn test_check_occupancy_with_listing_metadata() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let listing_id = env.register(MockListingContract, ());
+    let listing = MockListingContractClient::new(&env, &listing_id);
+    client.set_listing_contract(&listing_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let no_metadata = String::from_str(&env, "PROP2");
+    listing.set_max_guests(&property_id, &4);
+
+    assert!(client.check_occupancy(&property_id, &1));
+    assert!(client.check_occupancy(&property_id, &4));
+    assert!(!client.check_occupancy(&property_id, &5));
+    assert!(!client.check_occupancy(&property_id, &0));
+
+    // Properties without metadata accept any non-empty party
+    assert!(client.check_occupancy(&no_metadata, &10));
+    assert!(!client.check_occupancy(&no_metadata, &0));
+}
+
+#[test]
+fn test_check_occupancy_without_listing_contract() {
+    let env = Env::default();
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    assert!(client.check_occupancy(&property_id, &8));
+    assert!(!client.check_occupancy(&property_id, &0));
+}
+
+// =========================
+// Listing Versions
+// =========================
+#[test]
+fn test_booking_records_listing_version() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let listing_id = env.register(MockListingContract, ());
+    let listing = MockListingContractClient::new(&env, &listing_id);
+    client.set_listing_contract(&listing_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    listing.set_listing_version(&property_id, &2);
+    let first = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+
+    // Later bookings see the updated listing, earlier ones keep their version
+    listing.set_listing_version(&property_id, &3);
+    let second = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704153600u64,
+        &1704240000u64,
+        &1000000000i128,
+    );
+
+    assert_eq!(client.get_booking(&first).listing_version, 2);
+    assert_eq!(client.get_booking(&second).listing_version, 3);
+}
+
+#[test]
+fn test_booking_without_listing_contract_has_no_version() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let booking_id = client.create_booking(
+        &String::from_str(&env, "PROP1"),
+        &String::from_str(&env, "USER1"),
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+    assert_eq!(client.get_booking(&booking_id).listing_version, 0);
+}
+
+#[test]
+fn test_migrate_adds_listing_version() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    let booking_id = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+
+    // Roll the record back to the version 3 layout
+    env.as_contract(&contract_id, || {
+        let key = (BOOKING, booking_id);
+        let booking: Booking = env.storage().persistent().get(&key).unwrap();
+        env.storage().persistent().set(&key, &to_legacy(booking));
+        env.storage().instance().set(&VERSION, &3u32);
+    });
+
+    assert_eq!(client.migrate(&100), SCHEMA_VERSION);
+
+    let booking = client.get_booking(&booking_id);
+    assert_eq!(booking.property_id, property_id);
+    assert_eq!(booking.user_id, user_id);
+    assert_eq!(booking.status, BookingStatus::Pending);
+    assert_eq!(booking.listing_version, 0);
+    assert_eq!(client.audit_state(&0, &10).len(), 0);
+}
+
+#[test]
+fn test_upgrade_to_listing_versions() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(booking_v3::WASM, ());
+    let old_client = booking_v3::Client::new(&env, &contract_id);
+    old_client.initialize(&Address::generate(&env));
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    let booking_id = old_client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+    let escrow_id = String::from_str(&env, "ESCROW123");
+    old_client.set_escrow_id(&booking_id, &escrow_id, &Address::generate(&env));
+
+    let new_wasm_hash = env.deployer().upload_contract_wasm(booking_v4::WASM);
+    old_client.upgrade(&new_wasm_hash);
+
+    let client = booking_v4::Client::new(&env, &contract_id);
+    assert_eq!(client.migrate(), 4);
+
+    let booking = client.get_booking(&booking_id);
+    assert_eq!(booking.escrow_id, Some(escrow_id));
+    assert_eq!(booking.listing_version, 0);
+    assert_eq!(
+        client
+            .get_user_bookings(&user_id, &None, &0, &10)
+            .bookings
+            .len(),
+        1
+    );
+    assert_eq!(client.audit_state(&0, &10).len(), 0);
+}
+
+// =========================
+// Listing Removal
+// =========================
+#[test]
+fn test_stays_release_dates() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    let host = Address::generate(&env);
+    let mut ids = vec![&env];
+    for i in 0u64..3 {
+        let start_date = 1704067200u64 + i * 86400;
+        ids.push_back(client.create_booking(
+            &property_id,
+            &user_id,
+            &start_date,
+            &(start_date + 86400),
+            &1000000000i128,
+        ));
+    }
+    let stays = || -> Vec<BookedStay> {
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .get(&(STAYS, property_id.clone()))
+                .unwrap_or(vec![&env])
+        })
+    };
+    assert_eq!(stays().len(), 3);
+
+    // Cancelled and completed bookings release their dates
+    client.cancel_booking(&ids.get(0).unwrap(), &user_id);
+    client.update_status(&ids.get(1).unwrap(), &BookingStatus::Confirmed, &host);
+    client.update_status(&ids.get(1).unwrap(), &BookingStatus::Completed, &host);
+    assert_eq!(stays().len(), 1);
+    assert_eq!(stays().get(0).unwrap().booking_id, ids.get(2).unwrap());
+    assert!(client.check_availability(&property_id, &1704067200u64, &1704240000u64));
+    assert!(!client.check_availability(&property_id, &1704240000u64, &1704326400u64));
+
+    // Stays that ended without a check-in leave with the next booking
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1704326400;
+    });
+    let next_id = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704326400u64,
+        &1704412800u64,
+        &1000000000i128,
+    );
+    assert_eq!(stays().len(), 1);
+    assert_eq!(stays().get(0).unwrap().booking_id, next_id);
+
+    // Every booking stays listed for the property
+    assert_eq!(
+        client
+            .get_property_bookings(&property_id, &0, &10)
+            .bookings
+            .len(),
+        4
+    );
+}
+
+#[test]
+fn test_migrate_collects_stays() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    let cancelled = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+    client.cancel_booking(&cancelled, &user_id);
+    client.create_booking(
+        &property_id,
+        &user_id,
+        &1704153600u64,
+        &1704240000u64,
+        &1000000000i128,
+    );
+
+    // Roll the storage back to the version 5 layout
+    env.as_contract(&contract_id, || {
+        env.storage()
+            .persistent()
+            .remove(&(STAYS, property_id.clone()));
+        env.storage().instance().set(&VERSION, &5u32);
+    });
+    assert!(!client.has_active_bookings(&property_id));
+
+    assert_eq!(client.migrate(&100), SCHEMA_VERSION);
+
+    assert!(client.has_active_bookings(&property_id));
+    assert!(client.check_availability(&property_id, &1704067200u64, &1704153600u64));
+    assert!(!client.check_availability(&property_id, &1704153600u64, &1704240000u64));
+}
+
+#[test]
+fn test_has_active_bookings() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    let host = Address::generate(&env);
+    assert!(!client.has_active_bookings(&property_id));
+
+    let cancelled = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+    assert!(client.has_active_bookings(&property_id));
+    client.cancel_booking(&cancelled, &user_id);
+    assert!(!client.has_active_bookings(&property_id));
+
+    let booking_id = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+    client.update_status(&booking_id, &BookingStatus::Confirmed, &host);
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1704067200;
+    });
+    client.check_in(&booking_id, &host);
+
+    // A guest who stays past the end date still occupies the property
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1704240000;
+    });
+    assert!(client.has_active_bookings(&property_id));
+
+    client.check_out(&booking_id, &host);
+    assert!(!client.has_active_bookings(&property_id));
+}
+
+#[test]
+fn test_has_active_bookings_ignores_past_stays() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    client.create_booking(
+        &property_id,
+        &String::from_str(&env, "USER1"),
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+
+    // Pending stays that already ended no longer hold the property
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1704153600;
+    });
+    assert!(!client.has_active_bookings(&property_id));
+}
+
+// =========================
+// Instant Book
+// =========================
+#[test]
+fn test_instant_book() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let listing_id = env.register(MockListingContract, ());
+    let listing = MockListingContractClient::new(&env, &listing_id);
+    client.set_listing_contract(&listing_id);
+
+    let requirements = vec![&env, AttestationKind::Address, AttestationKind::Photos];
+    client.set_instant_book_requirements(&requirements);
+    assert_eq!(client.get_instant_book_requirements(), requirements);
+
+    let property_id = String::from_str(&env, "PROP1");
+    listing.set_attestations(
+        &property_id,
+        &vec![
+            &env,
+            attestation(&env, AttestationKind::Address, 1704067200, false),
+            attestation(&env, AttestationKind::Photos, 1704067200, false),
+        ],
+    );
+    assert!(client.can_instant_book(&property_id));
+
+    let booking_id = client.instant_book(
+        &property_id,
+        &String::from_str(&env, "USER1"),
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+    let booking = client.get_booking(&booking_id);
+    assert_eq!(booking.status, BookingStatus::Confirmed);
+    assert_eq!(
+        client
+            .get_property_bookings(&property_id, &0, &10)
+            .bookings
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_can_instant_book_requires_valid_attestations() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let valid = vec![
+        &env,
+        attestation(&env, AttestationKind::License, 1704067200, false),
+    ];
+
+    // Without a listing contract or requirements instant booking is disabled
+    assert!(!client.can_instant_book(&property_id));
+    let listing_id = env.register(MockListingContract, ());
+    let listing = MockListingContractClient::new(&env, &listing_id);
+    client.set_listing_contract(&listing_id);
+    listing.set_attestations(&property_id, &valid);
+    assert!(!client.can_instant_book(&property_id));
+
+    client.set_instant_book_requirements(&vec![&env, AttestationKind::License]);
+    assert!(client.can_instant_book(&property_id));
+
+    listing.set_attestations(
+        &property_id,
+        &vec![
+            &env,
+            attestation(&env, AttestationKind::License, 1704067200, true),
+        ],
+    );
+    assert!(!client.can_instant_book(&property_id));
+
+    listing.set_attestations(
+        &property_id,
+        &vec![
+            &env,
+            attestation(&env, AttestationKind::License, 1703980800, false),
+        ],
+    );
+    assert!(!client.can_instant_book(&property_id));
+
+    listing.set_attestations(
+        &property_id,
+        &vec![
+            &env,
+            attestation(&env, AttestationKind::Address, 1704067200, false),
+        ],
+    );
+    assert!(!client.can_instant_book(&property_id));
+
+    // Clearing the requirements disables instant booking again
+    listing.set_attestations(&property_id, &valid);
+    client.set_instant_book_requirements(&vec![&env]);
+    assert!(!client.can_instant_book(&property_id));
+}
+
+#[test]
+#[should_panic(expected = "Instant booking not allowed for this property")]
+fn test_instant_book_without_attestations() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let listing_id = env.register(MockListingContract, ());
+    client.set_listing_contract(&listing_id);
+    client.set_instant_book_requirements(&vec![&env, AttestationKind::Address]);
+
+    client.instant_book(
+        &String::from_str(&env, "PROP1"),
+        &String::from_str(&env, "USER1"),
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Booking overlap: dates conflict with existing reservation")]
+fn test_instant_book_checks_availability() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let listing_id = env.register(MockListingContract, ());
+    let listing = MockListingContractClient::new(&env, &listing_id);
+    client.set_listing_contract(&listing_id);
+    client.set_instant_book_requirements(&vec![&env, AttestationKind::Address]);
+
+    let property_id = String::from_str(&env, "PROP1");
+    listing.set_attestations(
+        &property_id,
+        &vec![
+            &env,
+            attestation(&env, AttestationKind::Address, 1704067200, false),
+        ],
+    );
+
+    client.create_booking(
+        &property_id,
+        &String::from_str(&env, "USER1"),
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+    client.instant_book(
+        &property_id,
+        &String::from_str(&env, "USER2"),
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+}
+
+// =========================
+// Annual Night Caps
+// =========================
+#[test]
+fn test_booked_nights_by_year() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let listing_id = env.register(MockListingContract, ());
+    let listing = MockListingContractClient::new(&env, &listing_id);
+    client.set_listing_contract(&listing_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    listing.set_annual_night_cap(&property_id, &90);
+
+    // Dec 31 2023 to Jan 3 2024: one night in 2023, two in 2024
+    let booking_id = client.create_booking(
+        &property_id,
+        &user_id,
+        &1703980800u64,
+        &1704240000u64,
+        &1000000000i128,
+    );
+    assert_eq!(client.get_booked_nights(&property_id, &2023), 1);
+    assert_eq!(client.get_booked_nights(&property_id, &2024), 2);
+
+    // A started day counts as a night
+    client.create_booking(
+        &property_id,
+        &user_id,
+        &1704240000u64,
+        &1704250000u64,
+        &1000000000i128,
+    );
+    assert_eq!(client.get_booked_nights(&property_id, &2024), 3);
+
+    client.cancel_booking(&booking_id, &user_id);
+    assert_eq!(client.get_booked_nights(&property_id, &2023), 0);
+    assert_eq!(client.get_booked_nights(&property_id, &2024), 1);
+
+    // Nights booked before the listing gets a cap count toward it
+    let uncapped_id = String::from_str(&env, "PROP2");
+    client.create_booking(
+        &uncapped_id,
+        &user_id,
+        &1704240000u64,
+        &1704412800u64,
+        &1000000000i128,
+    );
+    assert_eq!(client.get_booked_nights(&uncapped_id, &2024), 2);
+    listing.set_annual_night_cap(&uncapped_id, &3);
+    assert!(client
+        .try_create_booking(
+            &uncapped_id,
+            &user_id,
+            &1704412800u64,
+            &1704585600u64,
+            &1000000000i128,
+        )
+        .is_err());
+    client.create_booking(
+        &uncapped_id,
+        &user_id,
+        &1704412800u64,
+        &1704499200u64,
+        &1000000000i128,
+    );
+    assert_eq!(client.get_booked_nights(&uncapped_id, &2024), 3);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #2)")]
+fn test_stay_too_long() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let start_date = 1704067200u64;
+    client.create_booking(
+        &String::from_str(&env, "PROP1"),
+        &String::from_str(&env, "USER1"),
+        &start_date,
+        &(start_date + (MAX_STAY_NIGHTS + 1) * DAY_IN_SECONDS),
+        &1000000000i128,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_booking_date_out_of_range() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let listing_id = env.register(MockListingContract, ());
+    let listing = MockListingContractClient::new(&env, &listing_id);
+    client.set_listing_contract(&listing_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    listing.set_annual_night_cap(&property_id, &90);
+
+    client.create_booking(
+        &property_id,
+        &String::from_str(&env, "USER1"),
+        &(u64::MAX - DAY_IN_SECONDS),
+        &u64::MAX,
+        &1000000000i128,
+    );
+}
+
+#[test]
+fn test_annual_night_cap() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let listing_id = env.register(MockListingContract, ());
+    let listing = MockListingContractClient::new(&env, &listing_id);
+    client.set_listing_contract(&listing_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    let host = Address::generate(&env);
+    listing.set_permissions(&property_id, &host, &PERM_CONFIRM_BOOKINGS);
+    listing.set_annual_night_cap(&property_id, &2);
+
+    // Jan 1 to Jan 3 2024 uses up the cap for 2024
+    let booking_id = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704240000u64,
+        &1000000000i128,
+    );
+    let result = client.try_create_booking(
+        &property_id,
+        &user_id,
+        &1704326400u64,
+        &1704412800u64,
+        &1000000000i128,
+    );
+    assert!(result.is_err());
+
+    // The cap applies per calendar year
+    client.create_booking(
+        &property_id,
+        &user_id,
+        &1703980800u64,
+        &1704067200u64,
+        &1000000000i128,
+    );
+    assert_eq!(client.get_booked_nights(&property_id, &2023), 1);
+
+    // Cancelled bookings free their nights
+    client.update_status(&booking_id, &BookingStatus::Cancelled, &host);
+    client.create_booking(
+        &property_id,
+        &user_id,
+        &1704326400u64,
+        &1704412800u64,
+        &1000000000i128,
+    );
+    assert_eq!(client.get_booked_nights(&property_id, &2024), 1);
+}
+
+#[test]
+fn test_nightly_rate() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let listing_id = env.register(MockListingContract, ());
+    let listing = MockListingContractClient::new(&env, &listing_id);
+    client.set_listing_contract(&listing_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    listing.set_nightly_rate(&property_id, &100);
+
+    // Jan 1 to Jan 3 2024 is two nights
+    let result = client.try_create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704240000u64,
+        &199i128,
+    );
+    assert!(result.is_err());
+    assert!(client
+        .try_instant_book(
+            &property_id,
+            &user_id,
+            &1704067200u64,
+            &1704240000u64,
+            &199i128
+        )
+        .is_err());
+
+    client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704240000u64,
+        &200i128,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Night cap exceeded: property is booked too many nights this year")]
+fn test_instant_book_respects_night_cap() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let listing_id = env.register(MockListingContract, ());
+    let listing = MockListingContractClient::new(&env, &listing_id);
+    client.set_listing_contract(&listing_id);
+    client.set_instant_book_requirements(&vec![&env, AttestationKind::License]);
+
+    let property_id = String::from_str(&env, "PROP1");
+    listing.set_attestations(
+        &property_id,
+        &vec![
+            &env,
+            attestation(&env, AttestationKind::License, 1735689600, false),
+        ],
+    );
+    listing.set_annual_night_cap(&property_id, &1);
+
+    client.instant_book(
+        &property_id,
+        &String::from_str(&env, "USER1"),
+        &1704067200u64,
+        &1704240000u64,
+        &1000000000i128,
+    );
+}
+
+#[test]
+fn test_migrate_counts_booked_nights() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let listing_id = env.register(MockListingContract, ());
+    let listing = MockListingContractClient::new(&env, &listing_id);
+    client.set_listing_contract(&listing_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    listing.set_annual_night_cap(&property_id, &90);
+    client.create_booking(
+        &property_id,
+        &user_id,
+        &1703980800u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+    let cancelled = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704153600u64,
+        &1704240000u64,
+        &1000000000i128,
+    );
+    client.cancel_booking(&cancelled, &user_id);
+
+    // Roll the storage back to the version 4 layout
+    env.as_contract(&contract_id, || {
+        env.storage()
+            .persistent()
+            .remove(&(NIGHTS, property_id.clone(), 2023u32));
+        env.storage()
+            .persistent()
+            .remove(&(NIGHTS, property_id.clone(), 2024u32));
+        env.storage().instance().set(&VERSION, &4u32);
+    });
+    assert_eq!(client.get_booked_nights(&property_id, &2023), 0);
+
+    // Each call converts at most `limit` bookings, and the version is only
+    // bumped once a step has gone through every booking
+    assert_eq!(client.migrate(&1), 4);
+    assert_eq!(client.version(), 4);
+    assert_eq!(client.get_booked_nights(&property_id, &2023), 1);
+
+    assert_eq!(client.migrate(&1), 5);
+    assert_eq!(client.migrate(&100), SCHEMA_VERSION);
+
+    assert_eq!(client.get_booked_nights(&property_id, &2023), 1);
+    assert_eq!(client.get_booked_nights(&property_id, &2024), 1);
+}
+
+#[test]
+fn test_migrate_counts_nights_of_uncapped_listings() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    let counted = client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+    client.create_booking(
+        &property_id,
+        &user_id,
+        &1704153600u64,
+        &1704326400u64,
+        &1000000000i128,
+    );
+
+    // Roll the storage back to the version 5 layout, where only the first
+    // booking was counted because the listing had a cap at the time
+    env.as_contract(&contract_id, || {
+        env.storage()
+            .persistent()
+            .set(&(NIGHTS, property_id.clone(), 2024u32), &1u32);
+        env.storage().persistent().set(&(NIGHTS, counted), &true);
+        env.storage().instance().set(&VERSION, &5u32);
+    });
+
+    assert_eq!(client.migrate(&100), SCHEMA_VERSION);
+
+    assert_eq!(client.get_booked_nights(&property_id, &2024), 3);
+    env.as_contract(&contract_id, || {
+        assert!(!env.storage().persistent().has(&(NIGHTS, counted)));
+    });
+}
+
+#[test]
+fn test_upgrade_to_night_caps() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(booking_v4::WASM, ());
+    let old_client = booking_v4::Client::new(&env, &contract_id);
+    old_client.initialize(&Address::generate(&env));
+
+    let property_id = String::from_str(&env, "PROP1");
+    let booking_id = old_client.create_booking(
+        &property_id,
+        &String::from_str(&env, "USER1"),
+        &1704067200u64,
+        &1704240000u64,
+        &1000000000i128,
+    );
+
+    let new_wasm_hash = env.deployer().upload_contract_wasm(booking_v5::WASM);
+    old_client.upgrade(&new_wasm_hash);
+
+    let client = booking_v5::Client::new(&env, &contract_id);
+    assert_eq!(client.migrate(&100), 5);
+
+    // The version 5 release only counted the nights of capped listings
+    assert_eq!(client.get_booked_nights(&property_id, &2024), 0);
+    assert_eq!(client.get_booking(&booking_id).listing_version, 0);
+    assert_eq!(client.audit_state(&0, &10).len(), 0);
+}
+
+#[test]
+fn test_deploy_release_build() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    // The constructor of the release build sets the admin and the version
+    let admin = Address::generate(&env);
+    let contract_id = env.register(booking_v6::WASM, (admin.clone(),));
+    let client = booking_v6::Client::new(&env, &contract_id);
+    assert_eq!(client.version(), SCHEMA_VERSION);
+    assert_eq!(client.migrate(&10), SCHEMA_VERSION);
+
+    client.pause(&admin);
+    assert!(client.is_paused());
+}
+
+#[test]
+fn test_upgrade_to_open_stays() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    // Book an uncapped listing on the version 5 release, which neither
+    // counted its nights nor kept its open stays
+    let contract_id = env.register(booking_v5::WASM, (Address::generate(&env),));
+    let old_client = booking_v5::Client::new(&env, &contract_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let user_id = String::from_str(&env, "USER1");
+    let cancelled = old_client.create_booking(
+        &property_id,
+        &user_id,
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+    old_client.cancel_booking(&cancelled, &user_id);
+    for start in [1704153600u64, 1704240000u64] {
+        old_client.create_booking(
+            &property_id,
+            &user_id,
+            &start,
+            &(start + 86400),
+            &1000000000i128,
+        );
+    }
+    assert_eq!(old_client.get_booked_nights(&property_id, &2024), 0);
+
+    // Upgrade to the current release and migrate in batches
+    let new_wasm_hash = env.deployer().upload_contract_wasm(booking_v6::WASM);
+    old_client.upgrade(&new_wasm_hash);
+
+    let client = booking_v6::Client::new(&env, &contract_id);
+    assert_eq!(client.migrate(&2), 5);
+    assert_eq!(client.migrate(&2), SCHEMA_VERSION);
+
+    assert_eq!(client.get_booked_nights(&property_id, &2024), 2);
+    assert!(client.has_active_bookings(&property_id));
+    assert!(client.check_availability(&property_id, &1704067200u64, &1704153600u64));
+    assert!(!client.check_availability(&property_id, &1704240000u64, &1704326400u64));
+}
+
+// =========================
+// Revenue Shares
+// =========================
+#[test]
+fn test_completion_splits_payout() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let listing_id = env.register(MockListingContract, ());
+    let listing = MockListingContractClient::new(&env, &listing_id);
+    client.set_listing_contract(&listing_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let owner = Address::generate(&env);
+    let co_owner = Address::generate(&env);
+    let investor = Address::generate(&env);
+    listing.set_permissions(&property_id, &owner, &PERM_CONFIRM_BOOKINGS);
+    listing.set_revenue_shares(
+        &property_id,
+        &vec![
+            &env,
+            RevenueShare {
+                beneficiary: owner.clone(),
+                bps: 6000,
+            },
+            RevenueShare {
+                beneficiary: co_owner.clone(),
+                bps: 3000,
+            },
+            RevenueShare {
+                beneficiary: investor.clone(),
+                bps: 1000,
+            },
+        ],
+    );
+
+    let booking_id = client.create_booking(
+        &property_id,
+        &String::from_str(&env, "USER1"),
+        &1704067200u64,
+        &1704153600u64,
+        &1000000001i128,
+    );
+    client.update_status(&booking_id, &BookingStatus::Confirmed, &owner);
+    assert_eq!(client.get_payout(&booking_id).len(), 0);
+
+    client.update_status(&booking_id, &BookingStatus::Completed, &owner);
+
+    // The rounding leftover goes to the first beneficiary
+    let payouts = vec![
+        &env,
+        Payout {
+            beneficiary: owner,
+            amount: 600000001,
+        },
+        Payout {
+            beneficiary: co_owner,
+            amount: 300000000,
+        },
+        Payout {
+            beneficiary: investor,
+            amount: 100000000,
+        },
+    ];
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("payout"), booking_id).into_val(&env),
+                payouts.into_val(&env)
+            )
+        ]
+    );
+    assert_eq!(client.get_payout(&booking_id), payouts);
+}
+
+#[test]
+fn test_check_out_records_payout() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let listing_id = env.register(MockListingContract, ());
+    let listing = MockListingContractClient::new(&env, &listing_id);
+    client.set_listing_contract(&listing_id);
+
+    let property_id = String::from_str(&env, "PROP1");
+    let owner = Address::generate(&env);
+    listing.set_permissions(&property_id, &owner, &PERM_CONFIRM_BOOKINGS);
+    listing.set_revenue_shares(
+        &property_id,
+        &vec![
+            &env,
+            RevenueShare {
+                beneficiary: owner.clone(),
+                bps: TOTAL_SHARE_BPS,
+            },
+        ],
+    );
+
+    let booking_id = client.create_booking(
+        &property_id,
+        &String::from_str(&env, "USER1"),
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+    client.update_status(&booking_id, &BookingStatus::Confirmed, &owner);
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1704067200;
+    });
+    client.check_in(&booking_id, &owner);
+    client.check_out(&booking_id, &owner);
+
+    assert_eq!(
+        client.get_payout(&booking_id),
+        vec![
+            &env,
+            Payout {
+                beneficiary: owner,
+                amount: 1000000000,
+            }
+        ]
+    );
+}
+
+#[test]
+fn test_no_payout_without_listing_contract() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+
+    let contract_id = env.register(BookingContract, (Address::generate(&env),));
+    let client = BookingContractClient::new(&env, &contract_id);
+
+    let host = Address::generate(&env);
+    let booking_id = client.create_booking(
+        &String::from_str(&env, "PROP1"),
+        &String::from_str(&env, "USER1"),
+        &1704067200u64,
+        &1704153600u64,
+        &1000000000i128,
+    );
+    client.update_status(&booking_id, &BookingStatus::Confirmed, &host);
+    client.update_status(&booking_id, &BookingStatus::Completed, &host);
+
+    assert_eq!(client.get_payout(&booking_id).len(), 0);
+}
@@ -0,0 +1,538 @@
+#![no_std]
+
+use soroban_sdk::{
+    contract, contractclient, contracterror, contractimpl, contracttype, panic_with_error,
+    symbol_short, token, vec, Address, BytesN, Env, IntoVal, String, Symbol, Val, Vec,
+};
+
+// Listing status as returned by the listing contract. Must match
+// `PropertyStatus` in the listing contract.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PropertyStatus {
+    Available,
+    Booked,
+    Maintenance,
+    Inactive,
+}
+
+// Listing as returned by the listing contract. Must match `PropertyListing`
+// in the listing contract.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PropertyListing {
+    pub id: String,
+    pub data_hash: BytesN<32>,
+    pub owner: Address,
+    pub status: PropertyStatus,
+}
+
+// Kind of a property. Must match `PropertyType` in the listing contract.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PropertyType {
+    Apartment,
+    House,
+    Room,
+    Villa,
+    Cabin,
+    Other,
+}
+
+// Details of a property as returned by the listing contract. Must match
+// `ListingMetadata` in the listing contract.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListingMetadata {
+    pub property_type: PropertyType,
+    pub bedrooms: u32,
+    pub bathrooms: u32,
+    pub max_guests: u32,
+    pub amenities: u32,
+    pub country_code: String,
+    pub region_code: String,
+    pub geohash: String,
+}
+
+// Bid of a listing owner for a featured slot
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeaturedBid {
+    pub listing_id: String,
+    pub bidder: Address,
+    pub amount: i128,
+}
+
+// English auction of the featured slots of a region or tag. Only the
+// highest `slots` bids are held, highest first; outbid amounts are refunded
+// right away.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SlotAuction {
+    pub token: Address,
+    pub slots: u32,
+    pub min_bid: i128,
+    // First ledger at which bids are refused and the auction can be settled
+    pub end_ledger: u32,
+    // Number of ledgers the winners stay featured, counted from `end_ledger`
+    pub period: u32,
+    pub bids: Vec<FeaturedBid>,
+}
+
+// Winners of the last settled auction of a region
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeaturedSlots {
+    // Listing ids, highest bid first
+    pub listing_ids: Vec<String>,
+    // First ledger at which the listings are no longer featured
+    pub expires_ledger: u32,
+}
+
+// Interface of the property listing contract used to check bidders
+#[contractclient(name = "PropertyListingClient")]
+pub trait PropertyListingInterface {
+    fn get_listing(env: Env, id: String) -> Option<PropertyListing>;
+    fn get_metadata(env: Env, id: String) -> Option<ListingMetadata>;
+    fn get_listing_tags(env: Env, id: String) -> Vec<Symbol>;
+}
+
+// Storage keys
+const ADMIN: Symbol = symbol_short!("ADMIN");
+const VERSION: Symbol = symbol_short!("VERSION");
+const GUARDIAN: Symbol = symbol_short!("GUARDIAN");
+const PAUSED: Symbol = symbol_short!("PAUSED");
+const LISTING_CONTRACT: Symbol = symbol_short!("LISTING");
+// Receives the winning bids of settled auctions
+const TREASURY: Symbol = symbol_short!("TREASURY");
+// Running auction of a region: (AUCTION, region) -> SlotAuction
+const AUCTION: Symbol = symbol_short!("AUCTION");
+// Featured listings of a region: (FEATURED, region) -> FeaturedSlots
+const FEATURED: Symbol = symbol_short!("FEATURED");
+
+/// Version of the storage layout written by this code. Bump it together with
+/// a new arm in `migrate` whenever the layout of stored data changes.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Maximum number of featured slots auctioned in a region
+pub const MAX_FEATURED_SLOTS: u32 = 10;
+
+/// Longest geohash accepted by the listing contract
+pub const MAX_GEOHASH_LEN: u32 = 12;
+
+// Storage TTL policy. Ledgers close roughly every 5 seconds, so one day is
+// about 17280 ledgers. Entries are bumped back to the full bump amount
+// whenever they are read or written and their remaining TTL has dropped
+// below the threshold.
+pub const DAY_IN_LEDGERS: u32 = 17_280;
+/// Instance storage (admin and contract config) is kept alive for 30 days.
+pub const INSTANCE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+/// Persistent entries (auctions, featured listings) are kept alive for 90 days.
+pub const PERSISTENT_BUMP_AMOUNT: u32 = 90 * DAY_IN_LEDGERS;
+pub const PERSISTENT_LIFETIME_THRESHOLD: u32 = PERSISTENT_BUMP_AMOUNT - 7 * DAY_IN_LEDGERS;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum AuctionError {
+    ContractPaused = 1,
+}
+
+#[contract]
+pub struct FeaturedAuctionContract;
+
+#[contractimpl]
+impl FeaturedAuctionContract {
+    /// Set the admin, the property listing contract bidders must own
+    /// listings in, and the treasury receiving winning bids when the contract
+    /// is deployed
+    pub fn __constructor(env: Env, admin: Address, listing_contract: Address, treasury: Address) {
+        env.storage().instance().set(&ADMIN, &admin);
+        env.storage().instance().set(&VERSION, &SCHEMA_VERSION);
+        env.storage()
+            .instance()
+            .set(&LISTING_CONTRACT, &listing_contract);
+        env.storage().instance().set(&TREASURY, &treasury);
+
+        Self::extend_instance_ttl(&env);
+    }
+
+    /// Start an auction of `slots` featured slots in a region or tag, open
+    /// to bids of at least `min_bid` in `token` until `end_ledger`. The
+    /// winners are featured for `period` ledgers from `end_ledger`. Only
+    /// callable by the admin.
+    pub fn start_auction(
+        env: Env,
+        region: Symbol,
+        token: Address,
+        slots: u32,
+        min_bid: i128,
+        end_ledger: u32,
+        period: u32,
+    ) -> SlotAuction {
+        Self::require_not_paused(&env);
+
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        let key = (AUCTION, region.clone());
+        if env.storage().persistent().has(&key) {
+            panic!("Auction already running");
+        }
+        if slots == 0 || slots > MAX_FEATURED_SLOTS {
+            panic!("Invalid slots: must be between 1 and the maximum");
+        }
+        if min_bid <= 0 {
+            panic!("Invalid minimum bid: must be positive");
+        }
+        if end_ledger <= env.ledger().sequence() || period == 0 {
+            panic!("Invalid schedule: must end in the future and feature for a period");
+        }
+
+        let auction = SlotAuction {
+            token,
+            slots,
+            min_bid,
+            end_ledger,
+            period,
+            bids: vec![&env],
+        };
+        env.storage().persistent().set(&key, &auction);
+        Self::extend_persistent_ttl(&env, &key);
+        Self::extend_instance_ttl(&env);
+
+        env.events()
+            .publish((symbol_short!("auc_start"), region), end_ledger);
+
+        auction
+    }
+
+    /// Bid `amount` for a featured slot for a listing. The bid replaces the
+    /// previous bid of the listing, or the lowest bid once all slots are
+    /// taken, and must beat it; the replaced bid is refunded. Only callable
+    /// by the owner of an active listing carrying the region as a tag or
+    /// located in it, when the region is a geohash prefix.
+    pub fn bid(env: Env, region: Symbol, listing_id: String, amount: i128) -> SlotAuction {
+        Self::require_not_paused(&env);
+
+        let listing = Self::get_listing_client(&env)
+            .get_listing(&listing_id)
+            .unwrap_or_else(|| panic!("Listing not found"));
+
+        // Verify the owner is the caller
+        listing.owner.require_auth();
+
+        if listing.status == PropertyStatus::Inactive {
+            panic!("Listing is inactive");
+        }
+        if !Self::is_in_region(&env, &listing_id, &region) {
+            panic!("Listing is not in the auction region");
+        }
+
+        let key = (AUCTION, region.clone());
+        let mut auction = Self::get_auction(env.clone(), region.clone())
+            .unwrap_or_else(|| panic!("Auction not found"));
+        if env.ledger().sequence() >= auction.end_ledger {
+            panic!("Auction has ended");
+        }
+
+        let outbid = match auction
+            .bids
+            .iter()
+            .position(|bid| bid.listing_id == listing_id)
+        {
+            Some(index) => {
+                let bid = auction.bids.get_unchecked(index as u32);
+                auction.bids.remove(index as u32);
+                Some(bid)
+            }
+            None if auction.bids.len() == auction.slots => auction.bids.pop_back(),
+            None => None,
+        };
+        if amount < auction.min_bid || outbid.as_ref().is_some_and(|bid| amount <= bid.amount) {
+            panic!("Bid too low");
+        }
+
+        let token = token::Client::new(&env, &auction.token);
+        token.transfer(&listing.owner, &env.current_contract_address(), &amount);
+        if let Some(bid) = outbid {
+            token.transfer(&env.current_contract_address(), &bid.bidder, &bid.amount);
+        }
+
+        // Earlier bids keep their place over later bids of the same amount
+        let index = auction
+            .bids
+            .iter()
+            .position(|bid| bid.amount < amount)
+            .unwrap_or(auction.bids.len() as usize);
+        auction.bids.insert(
+            index as u32,
+            FeaturedBid {
+                listing_id: listing_id.clone(),
+                bidder: listing.owner,
+                amount,
+            },
+        );
+
+        env.storage().persistent().set(&key, &auction);
+        Self::extend_persistent_ttl(&env, &key);
+        Self::extend_instance_ttl(&env);
+
+        env.events()
+            .publish((symbol_short!("bid"), region, listing_id), amount);
+
+        auction
+    }
+
+    /// Settle an auction once it has ended: the winning bids go to the
+    /// treasury and their listings replace the featured listings of the
+    /// region. Callable by anyone.
+    pub fn settle_auction(env: Env, region: Symbol) -> FeaturedSlots {
+        Self::require_not_paused(&env);
+
+        let auction = Self::get_auction(env.clone(), region.clone())
+            .unwrap_or_else(|| panic!("Auction not found"));
+        if env.ledger().sequence() < auction.end_ledger {
+            panic!("Auction is still running");
+        }
+
+        let mut total = 0;
+        let mut listing_ids = vec![&env];
+        for bid in auction.bids.iter() {
+            total += bid.amount;
+            listing_ids.push_back(bid.listing_id);
+        }
+        if total > 0 {
+            let treasury: Address = env.storage().instance().get(&TREASURY).unwrap();
+            token::Client::new(&env, &auction.token).transfer(
+                &env.current_contract_address(),
+                &treasury,
+                &total,
+            );
+        }
+
+        let featured = FeaturedSlots {
+            listing_ids,
+            expires_ledger: auction.end_ledger.saturating_add(auction.period),
+        };
+        env.storage()
+            .persistent()
+            .remove(&(AUCTION, region.clone()));
+        let key = (FEATURED, region.clone());
+        env.storage().persistent().set(&key, &featured);
+        Self::extend_persistent_ttl(&env, &key);
+        Self::extend_instance_ttl(&env);
+
+        env.events()
+            .publish((symbol_short!("auc_settl"), region), total);
+
+        featured
+    }
+
+    /// Get the running auction of a region, if any
+    pub fn get_auction(env: Env, region: Symbol) -> Option<SlotAuction> {
+        let key = (AUCTION, region);
+        let auction: Option<SlotAuction> = env.storage().persistent().get(&key);
+        if auction.is_some() {
+            Self::extend_persistent_ttl(&env, &key);
+        }
+        auction
+    }
+
+    /// Get the ids of the featured listings of a region, highest bid first.
+    /// Empty once the featured period has expired; listings removed or made
+    /// inactive since the auction was settled are left out.
+    pub fn get_featured(env: Env, region: Symbol) -> Vec<String> {
+        let key = (FEATURED, region);
+        let featured: FeaturedSlots = match env.storage().persistent().get(&key) {
+            Some(featured) => featured,
+            None => return vec![&env],
+        };
+        Self::extend_persistent_ttl(&env, &key);
+        if env.ledger().sequence() >= featured.expires_ledger {
+            return vec![&env];
+        }
+
+        let client = Self::get_listing_client(&env);
+        let mut ids = vec![&env];
+        for id in featured.listing_ids.iter() {
+            if let Some(listing) = client.get_listing(&id) {
+                if listing.status != PropertyStatus::Inactive {
+                    ids.push_back(id);
+                }
+            }
+        }
+        ids
+    }
+
+    /// Set the treasury receiving the winning bids. Only callable by the
+    /// admin.
+    pub fn set_treasury(env: Env, treasury: Address) {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        env.storage().instance().set(&TREASURY, &treasury);
+        Self::extend_instance_ttl(&env);
+    }
+
+    /// Get the treasury receiving the winning bids
+    pub fn get_treasury(env: Env) -> Option<Address> {
+        env.storage().instance().get(&TREASURY)
+    }
+
+    /// Set the property listing contract bidders must own listings in. Only
+    /// callable by the admin.
+    pub fn set_listing_contract(env: Env, listing_contract: Address) {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        env.storage()
+            .instance()
+            .set(&LISTING_CONTRACT, &listing_contract);
+        Self::extend_instance_ttl(&env);
+    }
+
+    /// Get the configured property listing contract
+    pub fn get_listing_contract(env: Env) -> Option<Address> {
+        env.storage().instance().get(&LISTING_CONTRACT)
+    }
+
+    /// Upgrade the contract code. Storage is kept as is, so `migrate` must be
+    /// called afterwards if the new code expects a newer schema version.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    /// Get the schema version of the data in storage
+    pub fn version(env: Env) -> u32 {
+        env.storage().instance().get(&VERSION).unwrap_or(0)
+    }
+
+    /// Migrate stored data to the schema version of the current code
+    pub fn migrate(env: Env) -> u32 {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        let version = Self::version(env.clone());
+        if version > SCHEMA_VERSION {
+            panic!("Stored schema is newer than the contract code");
+        }
+        if version < SCHEMA_VERSION {
+            panic!("No migration path for schema version");
+        }
+        Self::extend_instance_ttl(&env);
+
+        version
+    }
+
+    /// Set the guardian allowed to pause the contract. Only callable by the admin.
+    pub fn set_guardian(env: Env, guardian: Address) {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        env.storage().instance().set(&GUARDIAN, &guardian);
+        Self::extend_instance_ttl(&env);
+    }
+
+    /// Pause all state-changing entrypoints. Callable by the admin or the guardian.
+    pub fn pause(env: Env, caller: Address) {
+        caller.require_auth();
+
+        let guardian: Option<Address> = env.storage().instance().get(&GUARDIAN);
+        if caller != Self::get_admin(&env) && Some(caller.clone()) != guardian {
+            panic!("Unauthorized: only the admin or guardian can pause");
+        }
+
+        env.storage().instance().set(&PAUSED, &true);
+        Self::extend_instance_ttl(&env);
+
+        env.events().publish((symbol_short!("paused"),), caller);
+    }
+
+    /// Resume state-changing entrypoints. Only callable by the admin.
+    pub fn unpause(env: Env) {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        env.storage().instance().set(&PAUSED, &false);
+        Self::extend_instance_ttl(&env);
+
+        env.events().publish((symbol_short!("unpaused"),), admin);
+    }
+
+    /// Check whether state-changing entrypoints are paused
+    pub fn is_paused(env: Env) -> bool {
+        env.storage().instance().get(&PAUSED).unwrap_or(false)
+    }
+
+    /// Internal helper to reject state changes while paused
+    fn require_not_paused(env: &Env) {
+        if Self::is_paused(env.clone()) {
+            panic_with_error!(env, AuctionError::ContractPaused);
+        }
+    }
+
+    /// Internal helper to read the admin
+    fn get_admin(env: &Env) -> Address {
+        env.storage()
+            .instance()
+            .get(&ADMIN)
+            .unwrap_or_else(|| panic!("Contract not initialized"))
+    }
+
+    /// Internal helper to build a client for the listing contract
+    fn get_listing_client(env: &Env) -> PropertyListingClient<'_> {
+        let listing_contract = Self::get_listing_contract(env.clone())
+            .unwrap_or_else(|| panic!("Contract not initialized"));
+        PropertyListingClient::new(env, &listing_contract)
+    }
+
+    /// Internal helper to check that a listing is tagged with a region or
+    /// has a geohash starting with it
+    fn is_in_region(env: &Env, listing_id: &String, region: &Symbol) -> bool {
+        let client = Self::get_listing_client(env);
+        if client.get_listing_tags(listing_id).contains(region) {
+            return true;
+        }
+
+        let Some(metadata) = client.get_metadata(listing_id) else {
+            return false;
+        };
+        let mut buf = [0u8; MAX_GEOHASH_LEN as usize];
+        let len = metadata.geohash.len() as usize;
+        if len > buf.len() {
+            return false;
+        }
+        metadata.geohash.copy_into_slice(&mut buf[..len]);
+        (1..=len).any(|prefix_len| {
+            core::str::from_utf8(&buf[..prefix_len])
+                .is_ok_and(|prefix| Symbol::new(env, prefix) == *region)
+        })
+    }
+
+    /// Internal helper to keep the contract instance alive
+    fn extend_instance_ttl(env: &Env) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+    }
+
+    /// Internal helper to keep a persistent entry alive
+    fn extend_persistent_ttl<K>(env: &Env, key: &K)
+    where
+        K: IntoVal<Env, Val>,
+    {
+        env.storage().persistent().extend_ttl(
+            key,
+            PERSISTENT_LIFETIME_THRESHOLD,
+            PERSISTENT_BUMP_AMOUNT,
+        );
+    }
+}
+
+#[cfg(test)]
+mod test;
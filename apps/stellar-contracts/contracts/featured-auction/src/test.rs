@@ -0,0 +1,505 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{
+    testutils::{Address as _, Events, Ledger},
+    vec, Address, BytesN, Env, String,
+};
+
+// Stand-in for the property listing contract that stores listings as given
+#[contract]
+pub struct MockListingContract;
+
+#[contractimpl]
+impl MockListingContract {
+    pub fn set_listing(env: Env, listing: PropertyListing) {
+        env.storage().persistent().set(&listing.id, &listing);
+    }
+
+    pub fn get_listing(env: Env, id: String) -> Option<PropertyListing> {
+        env.storage().persistent().get(&id)
+    }
+
+    pub fn set_metadata(env: Env, id: String, metadata: ListingMetadata) {
+        env.storage()
+            .persistent()
+            .set(&(symbol_short!("META"), id), &metadata);
+    }
+
+    pub fn get_metadata(env: Env, id: String) -> Option<ListingMetadata> {
+        env.storage().persistent().get(&(symbol_short!("META"), id))
+    }
+
+    pub fn set_listing_tags(env: Env, id: String, tags: Vec<Symbol>) {
+        env.storage()
+            .persistent()
+            .set(&(symbol_short!("TAGS"), id), &tags);
+    }
+
+    pub fn get_listing_tags(env: Env, id: String) -> Vec<Symbol> {
+        env.storage()
+            .persistent()
+            .get(&(symbol_short!("TAGS"), id))
+            .unwrap_or(Vec::new(&env))
+    }
+}
+
+// Register a token and mint `amount` to `holder`
+fn create_token(env: &Env, holder: &Address, amount: i128) -> token::Client<'static> {
+    let token_admin = Address::generate(env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(token_admin)
+        .address();
+    token::StellarAssetClient::new(env, &token_id).mint(holder, &amount);
+    token::Client::new(env, &token_id)
+}
+
+// Register the auction contract with a mock listing contract
+fn setup(
+    env: &Env,
+) -> (
+    FeaturedAuctionContractClient<'static>,
+    MockListingContractClient<'static>,
+    Address,
+) {
+    let listing_id = env.register(MockListingContract, ());
+    let admin = Address::generate(env);
+    let contract_id = env.register(
+        FeaturedAuctionContract,
+        (&admin, &listing_id, Address::generate(env)),
+    );
+    let client = FeaturedAuctionContractClient::new(env, &contract_id);
+    (
+        client,
+        MockListingContractClient::new(env, &listing_id),
+        admin,
+    )
+}
+
+// Store a listing of `owner` tagged `lisbon` in the mock listing contract
+fn add_listing(
+    listings: &MockListingContractClient,
+    id: &str,
+    owner: &Address,
+    status: PropertyStatus,
+) -> String {
+    let env = &listings.env;
+    let id = String::from_str(env, id);
+    listings.set_listing(&PropertyListing {
+        id: id.clone(),
+        data_hash: BytesN::from_array(env, &[0; 32]),
+        owner: owner.clone(),
+        status,
+    });
+    listings.set_listing_tags(&id, &vec![env, symbol_short!("lisbon")]);
+    id
+}
+
+#[test]
+fn test_initialize() {
+    let env = Env::default();
+    let (client, listings, _) = setup(&env);
+
+    assert_eq!(client.get_listing_contract(), Some(listings.address));
+    assert!(client.get_treasury().is_some());
+    assert_eq!(client.version(), SCHEMA_VERSION);
+    assert!(!client.is_paused());
+}
+
+#[test]
+fn test_start_auction() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _, admin) = setup(&env);
+    let token = create_token(&env, &admin, 0);
+
+    let region = symbol_short!("lisbon");
+    let auction = client.start_auction(&region, &token.address, &3, &100, &1000, &5000);
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(auction.slots, 3);
+    assert_eq!(auction.min_bid, 100);
+    assert_eq!(auction.end_ledger, 1000);
+    assert_eq!(auction.period, 5000);
+    assert_eq!(auction.bids.len(), 0);
+    assert_eq!(client.get_auction(&region), Some(auction));
+    assert_eq!(client.get_auction(&symbol_short!("porto")), None);
+
+    // One auction runs per region at a time
+    let result = client.try_start_auction(&region, &token.address, &3, &100, &1000, &5000);
+    assert!(result.is_err());
+    client.start_auction(
+        &symbol_short!("porto"),
+        &token.address,
+        &1,
+        &100,
+        &1000,
+        &5000,
+    );
+}
+
+#[test]
+fn test_start_auction_validation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.sequence_number = 100);
+    let (client, _, admin) = setup(&env);
+    let token = create_token(&env, &admin, 0);
+    let region = symbol_short!("lisbon");
+
+    let invalid = [
+        (0, 100, 1000, 5000),
+        (MAX_FEATURED_SLOTS + 1, 100, 1000, 5000),
+        (3, 0, 1000, 5000),
+        (3, 100, 100, 5000),
+        (3, 100, 1000, 0),
+    ];
+    for (slots, min_bid, end_ledger, period) in invalid {
+        let result = client.try_start_auction(
+            &region,
+            &token.address,
+            &slots,
+            &min_bid,
+            &end_ledger,
+            &period,
+        );
+        assert!(result.is_err());
+    }
+    assert_eq!(client.get_auction(&region), None);
+}
+
+#[test]
+fn test_bid() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, listings, admin) = setup(&env);
+
+    let host1 = Address::generate(&env);
+    let host2 = Address::generate(&env);
+    let id1 = add_listing(&listings, "PROP1", &host1, PropertyStatus::Available);
+    let id2 = add_listing(&listings, "PROP2", &host2, PropertyStatus::Booked);
+    let token = create_token(&env, &host1, 1000);
+    token::StellarAssetClient::new(&env, &token.address).mint(&host2, &1000);
+
+    let region = symbol_short!("lisbon");
+    client.start_auction(&region, &token.address, &3, &100, &1000, &5000);
+
+    client.bid(&region, &id1, &200);
+    assert_eq!(env.auths()[0].0, host1);
+    assert_eq!(
+        vec![&env, env.events().all().last().unwrap()],
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (symbol_short!("bid"), region.clone(), id1.clone()).into_val(&env),
+                200i128.into_val(&env),
+            ),
+        ]
+    );
+    let auction = client.bid(&region, &id2, &300);
+    assert_eq!(env.auths()[0].0, host2);
+
+    // Bids are kept highest first
+    assert_eq!(
+        auction.bids,
+        vec![
+            &env,
+            FeaturedBid {
+                listing_id: id2,
+                bidder: host2.clone(),
+                amount: 300,
+            },
+            FeaturedBid {
+                listing_id: id1,
+                bidder: host1.clone(),
+                amount: 200,
+            },
+        ]
+    );
+    assert_eq!(token.balance(&host1), 800);
+    assert_eq!(token.balance(&host2), 700);
+    assert_eq!(token.balance(&client.address), 500);
+    assert_eq!(token.balance(&admin), 0);
+}
+
+#[test]
+fn test_bid_replaces_previous_bid() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, listings, _) = setup(&env);
+
+    let host1 = Address::generate(&env);
+    let host2 = Address::generate(&env);
+    let id1 = add_listing(&listings, "PROP1", &host1, PropertyStatus::Available);
+    let id2 = add_listing(&listings, "PROP2", &host2, PropertyStatus::Available);
+    let token = create_token(&env, &host1, 1000);
+    token::StellarAssetClient::new(&env, &token.address).mint(&host2, &1000);
+
+    let region = symbol_short!("lisbon");
+    client.start_auction(&region, &token.address, &3, &100, &1000, &5000);
+    client.bid(&region, &id1, &200);
+    client.bid(&region, &id2, &300);
+
+    // Raising a bid refunds the previous one and moves the listing up
+    let auction = client.bid(&region, &id1, &400);
+    assert_eq!(auction.bids.len(), 2);
+    assert_eq!(auction.bids.get(0).unwrap().listing_id, id1);
+    assert_eq!(auction.bids.get(0).unwrap().amount, 400);
+    assert_eq!(token.balance(&host1), 600);
+    assert_eq!(token.balance(&client.address), 700);
+
+    // Bids must beat the bid they replace
+    assert!(client.try_bid(&region, &id1, &400).is_err());
+    assert!(client.try_bid(&region, &id2, &250).is_err());
+}
+
+#[test]
+fn test_bid_outbids_lowest_when_slots_taken() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, listings, _) = setup(&env);
+
+    let hosts = [
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    ];
+    let names = ["PROP1", "PROP2", "PROP3"];
+    let ids =
+        [0, 1, 2].map(|i| add_listing(&listings, names[i], &hosts[i], PropertyStatus::Available));
+    let token = create_token(&env, &hosts[0], 1000);
+    for host in hosts.iter().skip(1) {
+        token::StellarAssetClient::new(&env, &token.address).mint(host, &1000);
+    }
+
+    let region = symbol_short!("lisbon");
+    client.start_auction(&region, &token.address, &2, &100, &1000, &5000);
+    client.bid(&region, &ids[0], &200);
+    client.bid(&region, &ids[1], &300);
+
+    // A bid that does not beat the lowest bid is refused once slots are taken
+    assert!(client.try_bid(&region, &ids[2], &200).is_err());
+
+    // Beating the lowest bid takes its slot and refunds it
+    let auction = client.bid(&region, &ids[2], &250);
+    assert_eq!(auction.bids.len(), 2);
+    assert_eq!(auction.bids.get(0).unwrap().listing_id, ids[1]);
+    assert_eq!(auction.bids.get(1).unwrap().listing_id, ids[2]);
+    assert_eq!(token.balance(&hosts[0]), 1000);
+    assert_eq!(token.balance(&client.address), 550);
+}
+
+#[test]
+fn test_bid_ties_keep_earlier_bid_first() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, listings, _) = setup(&env);
+
+    let host = Address::generate(&env);
+    let id1 = add_listing(&listings, "PROP1", &host, PropertyStatus::Available);
+    let id2 = add_listing(&listings, "PROP2", &host, PropertyStatus::Available);
+    let token = create_token(&env, &host, 1000);
+
+    let region = symbol_short!("lisbon");
+    client.start_auction(&region, &token.address, &3, &100, &1000, &5000);
+    client.bid(&region, &id1, &200);
+    let auction = client.bid(&region, &id2, &200);
+    assert_eq!(auction.bids.get(0).unwrap().listing_id, id1);
+    assert_eq!(auction.bids.get(1).unwrap().listing_id, id2);
+}
+
+#[test]
+fn test_bid_rejections() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, listings, _) = setup(&env);
+
+    let host = Address::generate(&env);
+    let id = add_listing(&listings, "PROP1", &host, PropertyStatus::Available);
+    let inactive = add_listing(&listings, "PROP2", &host, PropertyStatus::Inactive);
+    let token = create_token(&env, &host, 1000);
+
+    let region = symbol_short!("lisbon");
+    assert!(client.try_bid(&region, &id, &200).is_err());
+
+    client.start_auction(&region, &token.address, &3, &100, &1000, &5000);
+    assert!(client
+        .try_bid(&region, &String::from_str(&env, "MISSING"), &200)
+        .is_err());
+    assert!(client.try_bid(&region, &inactive, &200).is_err());
+    assert!(client.try_bid(&region, &id, &99).is_err());
+
+    env.ledger().with_mut(|li| li.sequence_number = 1000);
+    assert!(client.try_bid(&region, &id, &200).is_err());
+    assert_eq!(token.balance(&host), 1000);
+}
+
+#[test]
+#[should_panic(expected = "Listing is not in the auction region")]
+fn test_bid_outside_region() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, listings, _) = setup(&env);
+
+    let host = Address::generate(&env);
+    let id = add_listing(&listings, "PROP1", &host, PropertyStatus::Available);
+    let token = create_token(&env, &host, 1000);
+
+    let region = symbol_short!("porto");
+    client.start_auction(&region, &token.address, &3, &100, &1000, &5000);
+    client.bid(&region, &id, &200);
+}
+
+#[test]
+fn test_bid_in_geohash_region() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, listings, _) = setup(&env);
+
+    let host = Address::generate(&env);
+    let id = add_listing(&listings, "PROP1", &host, PropertyStatus::Available);
+    listings.set_metadata(
+        &id,
+        &ListingMetadata {
+            property_type: PropertyType::Apartment,
+            bedrooms: 2,
+            bathrooms: 1,
+            max_guests: 4,
+            amenities: 0,
+            country_code: String::from_str(&env, "PT"),
+            region_code: String::from_str(&env, "11"),
+            geohash: String::from_str(&env, "eycs0p8ukc7v"),
+        },
+    );
+    let token = create_token(&env, &host, 1000);
+
+    let region = symbol_short!("eycs");
+    client.start_auction(&region, &token.address, &3, &100, &1000, &5000);
+    let auction = client.bid(&region, &id, &200);
+    assert_eq!(auction.bids.get(0).unwrap().listing_id, id);
+
+    let other = symbol_short!("eycb");
+    client.start_auction(&other, &token.address, &3, &100, &1000, &5000);
+    assert!(client.try_bid(&other, &id, &200).is_err());
+}
+
+#[test]
+fn test_settle_auction() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, listings, _) = setup(&env);
+    let treasury = Address::generate(&env);
+    client.set_treasury(&treasury);
+    assert_eq!(client.get_treasury(), Some(treasury.clone()));
+
+    let host = Address::generate(&env);
+    let id1 = add_listing(&listings, "PROP1", &host, PropertyStatus::Available);
+    let id2 = add_listing(&listings, "PROP2", &host, PropertyStatus::Available);
+    let token = create_token(&env, &host, 1000);
+
+    let region = symbol_short!("lisbon");
+    client.start_auction(&region, &token.address, &3, &100, &1000, &5000);
+    client.bid(&region, &id1, &200);
+    client.bid(&region, &id2, &300);
+    assert!(client.try_settle_auction(&region).is_err());
+    assert_eq!(client.get_featured(&region).len(), 0);
+
+    env.ledger().with_mut(|li| li.sequence_number = 1000);
+    let featured = client.settle_auction(&region);
+    assert_eq!(
+        featured,
+        FeaturedSlots {
+            listing_ids: vec![&env, id2.clone(), id1.clone()],
+            expires_ledger: 6000,
+        }
+    );
+    assert_eq!(
+        vec![&env, env.events().all().last().unwrap()],
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (symbol_short!("auc_settl"), region.clone()).into_val(&env),
+                500i128.into_val(&env),
+            ),
+        ]
+    );
+    assert_eq!(token.balance(&treasury), 500);
+    assert_eq!(token.balance(&client.address), 0);
+    assert_eq!(client.get_auction(&region), None);
+    assert_eq!(client.get_featured(&region), vec![&env, id2, id1.clone()]);
+
+    // The next auction of the region can start right away
+    client.start_auction(&region, &token.address, &3, &100, &2000, &5000);
+    assert_eq!(client.get_featured(&region).len(), 2);
+
+    // Settling an auction without bids clears the featured listings
+    env.ledger().with_mut(|li| li.sequence_number = 2000);
+    client.settle_auction(&region);
+    assert_eq!(client.get_featured(&region).len(), 0);
+    assert_eq!(token.balance(&treasury), 500);
+}
+
+#[test]
+fn test_get_featured_filters_and_expires() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, listings, _) = setup(&env);
+
+    let host = Address::generate(&env);
+    let id1 = add_listing(&listings, "PROP1", &host, PropertyStatus::Available);
+    let id2 = add_listing(&listings, "PROP2", &host, PropertyStatus::Available);
+    let token = create_token(&env, &host, 1000);
+
+    let region = symbol_short!("lisbon");
+    client.start_auction(&region, &token.address, &3, &100, &1000, &5000);
+    client.bid(&region, &id1, &200);
+    client.bid(&region, &id2, &300);
+    env.ledger().with_mut(|li| li.sequence_number = 1000);
+    client.settle_auction(&region);
+
+    // Listings made inactive after the auction are left out
+    add_listing(&listings, "PROP2", &host, PropertyStatus::Inactive);
+    assert_eq!(client.get_featured(&region), vec![&env, id1]);
+    assert_eq!(client.get_featured(&symbol_short!("porto")).len(), 0);
+
+    env.ledger().with_mut(|li| li.sequence_number = 6000);
+    assert_eq!(client.get_featured(&region).len(), 0);
+}
+
+#[test]
+fn test_pause_blocks_bids() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, listings, admin) = setup(&env);
+
+    let host = Address::generate(&env);
+    let id = add_listing(&listings, "PROP1", &host, PropertyStatus::Available);
+    let token = create_token(&env, &host, 1000);
+    let region = symbol_short!("lisbon");
+    client.start_auction(&region, &token.address, &3, &100, &1000, &5000);
+
+    let guardian = Address::generate(&env);
+    client.set_guardian(&guardian);
+    client.pause(&guardian);
+    assert!(client.is_paused());
+    assert_eq!(
+        client.try_bid(&region, &id, &200),
+        Err(Ok(soroban_sdk::Error::from(AuctionError::ContractPaused)))
+    );
+    assert!(client.get_auction(&region).is_some());
+
+    client.unpause();
+    assert_eq!(env.auths()[0].0, admin);
+    client.bid(&region, &id, &200);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized: only the admin or guardian can pause")]
+fn test_pause_unauthorized() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _, _) = setup(&env);
+
+    client.pause(&Address::generate(&env));
+}
@@ -1,12 +1,12 @@
 #![no_std]
 use soroban_sdk::{
-
-    contract, contractimpl, contracttype, symbol_short, vec, Address, Env, Symbol, Vec, String,
-
+    contract, contractclient, contracterror, contractimpl, contracttype, panic_with_error,
+    symbol_short, token, vec, xdr::ToXdr, Address, BytesN, Env, IntoVal, Map, String, Symbol,
+    TryFromVal, Val, Vec,
 };
 
 #[contracttype]
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum PropertyStatus {
     Available,
     Booked,
@@ -17,128 +17,2949 @@ pub enum PropertyStatus {
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct PropertyListing {
+    pub id: String,
+    // SHA-256 of the off-chain listing JSON
+    pub data_hash: BytesN<32>,
+    pub owner: Address,
+    pub status: PropertyStatus,
+}
+
+// Listing record written before schema version 6, which kept the data hash
+// as a free-form string. Only read by migrations.
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct LegacyPropertyListing {
     pub id: String,
     pub data_hash: String,
     pub owner: Address,
     pub status: PropertyStatus,
 }
 
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PropertyType {
+    Apartment,
+    House,
+    Room,
+    Villa,
+    Cabin,
+    Other,
+}
+
+// Structured, verifiable details of a property. The full listing content
+// stays off-chain behind `data_hash`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListingMetadata {
+    pub property_type: PropertyType,
+    pub bedrooms: u32,
+    pub bathrooms: u32,
+    pub max_guests: u32,
+    // Bitwise OR of the AMENITY_* flags
+    pub amenities: u32,
+    // ISO 3166-1 alpha-2 country code, e.g. "AR"
+    pub country_code: String,
+    // Subdivision part of the ISO 3166-2 code, e.g. "B" for AR-B
+    pub region_code: String,
+    pub geohash: String,
+}
+
+// Short-term rental registration of a listing
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListingCompliance {
+    // Code of the regulating jurisdiction, e.g. "US-NY" or "ES-B-BCN"
+    pub jurisdiction: String,
+    // Registration or license number issued by the jurisdiction
+    pub license_id: String,
+    // Maximum number of nights the listing may be booked per calendar year
+    pub annual_night_cap: Option<u32>,
+}
+
+// Entry of the status history of a listing
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StatusChange {
+    pub from: PropertyStatus,
+    pub to: PropertyStatus,
+    pub changed_by: Address,
+    pub timestamp: u64,
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum PropertyListingError {
+    InvalidTransition = 1,
+    ListingAlreadyExists = 2,
+    ListingNotFound = 3,
+    InvalidId = 4,
+    ListingRemoved = 5,
+    Unauthorized = 6,
+    DuplicateItem = 7,
+    BatchTooLarge = 8,
+    ContractPaused = 9,
+}
+
+// Listing to create with `create_listings_batch`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NewListing {
+    pub id: String,
+    pub data_hash: BytesN<32>,
+}
+
+// Status change to apply with `update_status_batch`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StatusUpdate {
+    pub id: String,
+    pub status: PropertyStatus,
+}
+
+// Reason a batch item was rejected, with its position in the batch
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchItemError {
+    pub index: u32,
+    // PropertyListingError code
+    pub error: u32,
+}
+
+// Roles an owner can grant to other addresses on a listing
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ListingRole {
+    CoHost,
+    Manager,
+    CalendarOnly,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Delegate {
+    pub role: ListingRole,
+    // Bitwise OR of the PERM_* flags granted to the delegate
+    pub permissions: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListingPage {
+    pub listings: Vec<PropertyListing>,
+    // Cursor to pass to the next call, None when there are no more entries
+    pub next_cursor: Option<u32>,
+}
+
+// Entry of the version history of a listing's advertised content
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListingVersion {
+    pub version: u32,
+    pub data_hash: BytesN<32>,
+    pub updated_by: Address,
+    pub timestamp: u64,
+}
+
+// Content version written before schema version 6. Only read by migrations.
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct LegacyListingVersion {
+    pub version: u32,
+    pub data_hash: String,
+    pub updated_by: Address,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListingHistoryPage {
+    pub versions: Vec<ListingVersion>,
+    // Cursor to pass to the next call, None when there are no more entries
+    pub next_cursor: Option<u32>,
+}
+
+// Record kept for a removed listing so its id cannot be taken by someone else
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListingTombstone {
+    pub owner: Address,
+    // False while the archived listing is still readable, true once deleted
+    pub deleted: bool,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListingIdPage {
+    pub ids: Vec<String>,
+    // Cursor to pass to the next call, None when there are no more entries
+    pub next_cursor: Option<u32>,
+}
+
+// Address allowed to transfer a listing token on behalf of its owner
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenApproval {
+    pub approved: Address,
+    // Last ledger sequence in which the approval can be used
+    pub live_until_ledger: u32,
+}
+
+// Part of the host proceeds of a listing paid to a co-owner
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RevenueShare {
+    pub beneficiary: Address,
+    // Share in basis points; the shares of a listing sum to TOTAL_SHARE_BPS
+    pub bps: u32,
+}
+
+// Bond owners must lock when creating a listing
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BondConfig {
+    pub token: Address,
+    pub amount: i128,
+    // Seconds between archiving a listing and being able to withdraw its bond
+    pub cooldown: u64,
+}
+
+// Bond locked for a listing, on the terms in force when it was created
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListingBond {
+    pub token: Address,
+    // Amount still locked, reduced by slashing
+    pub amount: i128,
+    pub cooldown: u64,
+    // Time from which the owner can withdraw, set once the listing is removed
+    pub unlock_at: Option<u64>,
+}
+
+// Facts about a listing that a verifier can attest to
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AttestationKind {
+    Address,
+    Photos,
+    License,
+}
+
+// Statement by a registered verifier that a fact about a listing was checked
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Attestation {
+    pub kind: AttestationKind,
+    pub verifier: Address,
+    // SHA-256 of the off-chain evidence, e.g. the checked license document
+    pub evidence_hash: BytesN<32>,
+    pub issued_at: u64,
+    pub expires_at: u64,
+    pub revoked: bool,
+}
+
+// Interface of the booking contract used to protect listings with upcoming stays
+#[contractclient(name = "BookingClient")]
+pub trait BookingInterface {
+    fn has_active_bookings(env: Env, property_id: String) -> bool;
+}
+
 const LISTINGS: Symbol = symbol_short!("LISTINGS");
+const ADMIN: Symbol = symbol_short!("ADMIN");
+const VERSION: Symbol = symbol_short!("VERSION");
+const GUARDIAN: Symbol = symbol_short!("GUARDIAN");
+const PAUSED: Symbol = symbol_short!("PAUSED");
+const BOOKING_CONTRACT: Symbol = symbol_short!("BOOKING");
+const ARBITRATOR: Symbol = symbol_short!("ARBITER");
+const BOND_CONFIG: Symbol = symbol_short!("BONDCFG");
+// Tags owners can attach to listings
+const TAG_VOCABULARY: Symbol = symbol_short!("TAGVOCAB");
+// Position reached in the listing index by an unfinished migration step
+const MIGRATION: Symbol = symbol_short!("MIGRATION");
+// Listing id index: ids are appended in creation order to fixed-size shards
+// keyed by (LIST_INDEX, shard number), with the position of each id kept
+// under (LIST_POS, id)
+const LIST_INDEX: Symbol = symbol_short!("LISTIDX");
+const LIST_POS: Symbol = symbol_short!("LISTPOS");
+const LIST_COUNT: Symbol = symbol_short!("LISTCOUNT");
+// Sparse indexes are split into shards like the listing index: the number of
+// slots is kept under the scope of the index, the shards under (scope, shard
+// number) and the slot of each listing under (scope, id). A listing keeps its
+// slot once given one, and the slot is emptied while it is not listed.
+// Owner index: the listing ids of each owner, sharded like the listing index
+// under (OWNER_INDEX, owner) with the number of ids kept under the same key
+// and the position of each id under (OWNER_POS, id). Schema version 6 kept
+// them in a single entry under (OWNLISTS, owner), left to expire.
+const OWNER_INDEX: Symbol = symbol_short!("OWNIDX");
+const OWNER_POS: Symbol = symbol_short!("OWNPOS");
+// Pending ownership transfers: (PENDING_TRANSFER, id) -> proposed new owner
+const PENDING_TRANSFER: Symbol = symbol_short!("PENDXFER");
+// Listing delegates: (DELEGATES, id) -> map of delegate address to role
+const DELEGATES: Symbol = symbol_short!("DELEGATES");
+// Listing metadata: (METADATA, id) -> ListingMetadata
+const METADATA: Symbol = symbol_short!("METADATA");
+// Location index: a sparse index under (GEO_INDEX, geohash prefix) of the
+// listings whose geohash starts with the prefix, for each indexed prefix
+// length. Inactive listings keep their slot but are not listed in it.
+const GEO_INDEX: Symbol = symbol_short!("GEOINDEX");
+// Content versions: (LIST_VERSION, id, version) -> ListingVersion, with the
+// latest version number under (LIST_VERSION_COUNT, id). Versions start at 1.
+const LIST_VERSION: Symbol = symbol_short!("LISTVER");
+const LIST_VERSION_COUNT: Symbol = symbol_short!("LISTVERS");
+// Status history: (STATUS_HISTORY, id) -> most recent status changes
+const STATUS_HISTORY: Symbol = symbol_short!("STATHIST");
+// Removed listings: (TOMBSTONE, id) -> ListingTombstone. Never cleared.
+const TOMBSTONE: Symbol = symbol_short!("TOMBSTONE");
+// Rental registrations: (COMPLIANCE, id) -> ListingCompliance
+const COMPLIANCE: Symbol = symbol_short!("COMPLY");
+// Nightly rates: (NIGHTLY_RATE, id) -> lowest price per night bookings of the
+// listing may be made at
+const NIGHTLY_RATE: Symbol = symbol_short!("RATE");
+// Listing bonds: (BOND, id) -> ListingBond, removed once fully withdrawn or
+// slashed
+const BOND: Symbol = symbol_short!("BOND");
+// Listing tokens: (TOKENIZED, id) -> true once a listing is minted as a
+// token, with the approval of each token under (TOKEN_APPROVAL, id). The
+// token owner is the owner of the listing record.
+const TOKENIZED: Symbol = symbol_short!("TOKENIZED");
+const TOKEN_APPROVAL: Symbol = symbol_short!("TOKENAPPR");
+// Listing tags: (LISTING_TAGS, id) -> tags of the listing, with a sparse
+// index of the listings carrying each tag under (TAG_INDEX, tag). Inactive
+// listings keep their slot but are not listed in it.
+const LISTING_TAGS: Symbol = symbol_short!("LISTTAGS");
+const TAG_INDEX: Symbol = symbol_short!("TAGIDX");
+// Revenue shares: (REVENUE_SHARES, id) -> shares of the host proceeds
+const REVENUE_SHARES: Symbol = symbol_short!("REVSHARES");
+// Registered verifiers: (VERIFIER, address) -> true
+const VERIFIER: Symbol = symbol_short!("VERIFIER");
+// Listing attestations: (ATTESTATIONS, id) -> latest attestation of each kind
+const ATTESTATIONS: Symbol = symbol_short!("ATTESTS");
+
+// Delegate permission flags. The booking contract checks
+// PERM_CONFIRM_BOOKINGS through `has_permission`, so the values are part of
+// the public interface and must not change.
+pub const PERM_UPDATE_DATA: u32 = 1 << 0;
+pub const PERM_UPDATE_STATUS: u32 = 1 << 1;
+pub const PERM_UPDATE_PRICING: u32 = 1 << 2;
+pub const PERM_CONFIRM_BOOKINGS: u32 = 1 << 3;
+pub const PERM_ALL: u32 =
+    PERM_UPDATE_DATA | PERM_UPDATE_STATUS | PERM_UPDATE_PRICING | PERM_CONFIRM_BOOKINGS;
+/// Permissions a calendar-only delegate may hold
+pub const PERM_CALENDAR: u32 = PERM_UPDATE_STATUS | PERM_CONFIRM_BOOKINGS;
+
+/// Maximum number of delegates per listing
+pub const MAX_DELEGATES: u32 = 10;
+
+/// Maximum number of status changes kept per listing; older entries are dropped
+pub const MAX_STATUS_HISTORY: u32 = 50;
+
+// Amenity flags for `ListingMetadata::amenities`. Stored on-chain, so the
+// values must not change.
+pub const AMENITY_WIFI: u32 = 1 << 0;
+pub const AMENITY_KITCHEN: u32 = 1 << 1;
+pub const AMENITY_PARKING: u32 = 1 << 2;
+pub const AMENITY_POOL: u32 = 1 << 3;
+pub const AMENITY_AIR_CONDITIONING: u32 = 1 << 4;
+pub const AMENITY_HEATING: u32 = 1 << 5;
+pub const AMENITY_WASHER: u32 = 1 << 6;
+pub const AMENITY_WORKSPACE: u32 = 1 << 7;
+pub const AMENITY_PETS_ALLOWED: u32 = 1 << 8;
+pub const AMENITY_ACCESSIBLE: u32 = 1 << 9;
+pub const AMENITY_ALL: u32 = (1 << 10) - 1;
+
+/// Maximum length of a listing id. Ids may only contain ASCII letters,
+/// digits, '-' and '_', which fits the UUIDs used by the backend.
+pub const MAX_ID_LEN: u32 = 64;
+
+// Metadata bounds
+pub const MAX_ROOMS: u32 = 50;
+pub const MAX_GUESTS: u32 = 100;
+pub const MAX_REGION_CODE_LEN: u32 = 3;
+pub const MAX_GEOHASH_LEN: u32 = 12;
+
+/// Shortest geohash prefix with its own search bucket (cells of ~156 km)
+pub const GEOHASH_MIN_PRECISION: u32 = 3;
+/// Longest geohash prefix with its own search bucket (cells of ~5 km).
+/// Longer search prefixes filter the bucket of this length.
+pub const GEOHASH_MAX_PRECISION: u32 = 5;
+
+/// Basis points the revenue shares of a listing add up to
+pub const TOTAL_SHARE_BPS: u32 = 10_000;
+/// Maximum number of items in `create_listings_batch` and
+/// `update_status_batch`. Each created listing writes five ledger entries of
+/// its own, six with a bond, plus up to eight shared index and token balance
+/// entries per batch. A full batch therefore writes at most 50 entries, the
+/// limit of write entries per Soroban transaction.
+pub const MAX_BATCH_SIZE: u32 = 7;
+/// Maximum number of ledger entries the updates of `update_status_batch` may
+/// write between them. Each update writes the listing and its status
+/// history, and one entering or leaving the inactive status also writes its
+/// slot in up to three location buckets and one index per tag, so a few
+/// such updates can fill a transaction on their own.
+pub const MAX_STATUS_BATCH_WRITES: u32 = 50;
+
+/// Maximum number of tags in the vocabulary and on a single listing
+pub const MAX_VOCABULARY_SIZE: u32 = 100;
+pub const MAX_TAGS_PER_LISTING: u32 = 10;
+
+/// Maximum number of beneficiaries sharing the revenue of a listing
+pub const MAX_BENEFICIARIES: u32 = 20;
+
+// Bounds of the compliance fields
+pub const MAX_JURISDICTION_LEN: u32 = 12;
+pub const MAX_LICENSE_ID_LEN: u32 = 64;
+pub const MAX_ANNUAL_NIGHT_CAP: u32 = 366;
+
+/// Version of the storage layout written by this code. Bump it together with
+/// a new arm in `migrate` whenever the layout of stored data changes.
+pub const SCHEMA_VERSION: u32 = 7;
+
+/// Maximum number of listing ids stored in a single index shard
+pub const INDEX_SHARD_SIZE: u32 = 100;
+
+/// Maximum number of listings returned by a single page
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+// Storage TTL policy. Ledgers close roughly every 5 seconds, so one day is
+// about 17280 ledgers. Entries are bumped back to the full bump amount
+// whenever they are read or written and their remaining TTL has dropped
+// below the threshold.
+pub const DAY_IN_LEDGERS: u32 = 17_280;
+/// Instance storage (admin and contract config) is kept alive for 30 days.
+pub const INSTANCE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+/// Persistent entries (listings and the listing index) are kept alive for 90 days.
+pub const PERSISTENT_BUMP_AMOUNT: u32 = 90 * DAY_IN_LEDGERS;
+pub const PERSISTENT_LIFETIME_THRESHOLD: u32 = PERSISTENT_BUMP_AMOUNT - 7 * DAY_IN_LEDGERS;
 
 #[contract]
 pub struct PropertyListingContract;
 
 #[contractimpl]
 impl PropertyListingContract {
+    /// Set the admin when the contract is deployed, so no one can claim it
+    /// between deployment and setup
+    pub fn __constructor(env: Env, admin: Address) {
+        env.storage().instance().set(&ADMIN, &admin);
+        env.storage().instance().set(&VERSION, &SCHEMA_VERSION);
+        Self::extend_instance_ttl(&env);
+    }
+
     /// Create a new property listing
     pub fn create_listing(
         env: Env,
         id: String,
-        data_hash: String,
+        data_hash: BytesN<32>,
         owner: Address,
     ) -> PropertyListing {
+        Self::require_not_paused(&env);
+
         // Verify the owner is the caller
         owner.require_auth();
 
-        let key = (LISTINGS.clone(), id.clone());
-
-        // Check if listing already exists
-        if env.storage().persistent().has(&key) {
-            panic!("Property listing already exists");
+        if let Some(error) = Self::check_new_listing(&env, &id) {
+            panic_with_error!(&env, error);
         }
 
-        let listing = PropertyListing {
-            id: id.clone(),
-            data_hash,
-            owner,
-            status: PropertyStatus::Available,
-        };
-
-        // Store the listing
-        env.storage().persistent().set(&key, &listing);
+        let listing = Self::insert_listing(&env, id, data_hash, owner);
+        Self::extend_instance_ttl(&env);
 
         listing
     }
 
+    /// Create several listings for one owner in a single transaction. Every
+    /// item is validated first: if any is rejected, nothing is created and
+    /// the rejected items are returned with their position in the batch.
+    /// An empty result means all listings were created. At most
+    /// MAX_BATCH_SIZE listings can be created at once.
+    pub fn create_listings_batch(
+        env: Env,
+        owner: Address,
+        listings: Vec<NewListing>,
+    ) -> Vec<BatchItemError> {
+        Self::require_not_paused(&env);
+
+        // Verify the owner is the caller
+        owner.require_auth();
+
+        Self::require_batch_size(&env, listings.len());
+
+        let mut errors = vec![&env];
+        for (index, item) in listings.iter().enumerate() {
+            let duplicate = listings.iter().take(index).any(|other| other.id == item.id);
+            let error = if duplicate {
+                Some(PropertyListingError::DuplicateItem)
+            } else {
+                Self::check_new_listing(&env, &item.id)
+            };
+            if let Some(error) = error {
+                errors.push_back(BatchItemError {
+                    index: index as u32,
+                    error: error as u32,
+                });
+            }
+        }
+        if !errors.is_empty() {
+            return errors;
+        }
+
+        for item in listings.iter() {
+            Self::insert_listing(&env, item.id, item.data_hash, owner.clone());
+        }
+        Self::extend_instance_ttl(&env);
+
+        errors
+    }
+
     /// Update an existing property listing
     pub fn update_listing(
         env: Env,
         id: String,
-        data_hash: String,
-        owner: Address,
+        data_hash: BytesN<32>,
+        caller: Address,
     ) -> PropertyListing {
-        // Verify the owner is the caller
-        owner.require_auth();
+        Self::require_not_paused(&env);
+
+        // Verify the caller is the signer
+        caller.require_auth();
 
         let key = (LISTINGS.clone(), id.clone());
-        
+
         // Get existing listing
-        let mut listing: PropertyListing = env
-            .storage()
-            .persistent()
-            .get(&key)
-            .unwrap_or_else(|| panic!("Property listing not found"));
+        let mut listing = Self::get_existing_listing(&env, &id);
 
-        // Verify ownership
-        if listing.owner != owner {
+        // Verify the caller is the owner or a permitted delegate
+        if !Self::check_permission(&env, &listing, &caller, PERM_UPDATE_DATA) {
             panic!("Only the owner can update the listing");
         }
 
-        // Update the data hash
-        listing.data_hash = data_hash;
+        // Update the data hash and record the new content version
+        Self::append_version(&env, &id, &data_hash, &caller);
+        listing.data_hash = data_hash.clone();
 
         // Store updated listing
         env.storage().persistent().set(&key, &listing);
+        Self::extend_persistent_ttl(&env, &key);
+        Self::extend_instance_ttl(&env);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "property_updated"),
+                id,
+                listing.owner.clone(),
+            ),
+            data_hash,
+        );
 
         listing
     }
 
+    /// Get a page of the content versions of a listing, oldest first
+    pub fn get_listing_history(
+        env: Env,
+        id: String,
+        cursor: u32,
+        limit: u32,
+    ) -> ListingHistoryPage {
+        let limit = Self::validate_limit(limit);
+        let count = Self::get_listing_version(env.clone(), id.clone());
+
+        let mut versions = vec![&env];
+        let mut i = cursor;
+        while i < count && versions.len() < limit {
+            let key = (LIST_VERSION, id.clone(), i + 1);
+            if let Some(version) = env.storage().persistent().get::<_, ListingVersion>(&key) {
+                Self::extend_persistent_ttl(&env, &key);
+                versions.push_back(version);
+            }
+            i += 1;
+        }
+
+        let next_cursor = if i < count { Some(i) } else { None };
+
+        ListingHistoryPage {
+            versions,
+            next_cursor,
+        }
+    }
+
+    /// Get the current content version of a listing, or 0 if it has none.
+    /// Recorded by the booking contract with each booking.
+    pub fn get_listing_version(env: Env, id: String) -> u32 {
+        let key = (LIST_VERSION_COUNT, id);
+        let count: Option<u32> = env.storage().persistent().get(&key);
+        if count.is_some() {
+            Self::extend_persistent_ttl(&env, &key);
+        }
+        count.unwrap_or(0)
+    }
+
     /// Update property status
     pub fn update_status(
         env: Env,
         id: String,
-        owner: Address,
+        caller: Address,
         status: PropertyStatus,
     ) -> PropertyListing {
-        // Verify the owner is the caller
-        owner.require_auth();
+        Self::require_not_paused(&env);
+
+        // Verify the caller is the signer
+        caller.require_auth();
 
-        let key = (LISTINGS.clone(), id.clone());
-        
         // Get existing listing
-        let mut listing: PropertyListing = env
-            .storage()
-            .persistent()
-            .get(&key)
-            .unwrap_or_else(|| panic!("Property listing not found"));
+        let listing = Self::get_existing_listing(&env, &id);
 
-        // Verify ownership
-        if listing.owner != owner {
+        // Verify the caller is the owner or a permitted delegate
+        if !Self::check_permission(&env, &listing, &caller, PERM_UPDATE_STATUS) {
             panic!("Only the owner can update the status");
         }
 
-        // Update status
-        listing.status = status;
+        // `Booked` is reserved for the booking contract, and a booked
+        // listing only becomes available again through `set_occupancy`
+        if !Self::is_valid_transition(listing.status, status) {
+            panic_with_error!(&env, PropertyListingError::InvalidTransition);
+        }
 
-        // Store updated listing
-        env.storage().persistent().set(&key, &listing);
+        let listing = Self::apply_status(&env, listing, status, &caller);
+        Self::extend_instance_ttl(&env);
 
         listing
     }
 
-    /// Get a property listing by ID
-    pub fn get_listing(env: Env, id: String) -> Option<PropertyListing> {
-        let key = (LISTINGS.clone(), id);
-        env.storage().persistent().get(&key)
+    /// Update the status of several listings in a single transaction, with
+    /// the same checks as `update_status`. Every item is validated first: if
+    /// any is rejected, no status changes and the rejected items are
+    /// returned with their position in the batch. An empty result means all
+    /// updates were applied. At most MAX_BATCH_SIZE updates can be applied
+    /// at once, and only while they write no more than
+    /// MAX_STATUS_BATCH_WRITES ledger entries.
+    pub fn update_status_batch(
+        env: Env,
+        caller: Address,
+        updates: Vec<StatusUpdate>,
+    ) -> Vec<BatchItemError> {
+        Self::require_not_paused(&env);
+
+        // Verify the caller is the signer
+        caller.require_auth();
+
+        Self::require_batch_size(&env, updates.len());
+
+        let mut listings = vec![&env];
+        let mut errors = vec![&env];
+        let mut writes = 0;
+        for (index, update) in updates.iter().enumerate() {
+            let duplicate = updates
+                .iter()
+                .take(index)
+                .any(|other| other.id == update.id);
+            let result = if duplicate {
+                Err(PropertyListingError::DuplicateItem)
+            } else {
+                Self::check_status_update(&env, &update, &caller)
+            };
+            match result {
+                Ok(listing) => {
+                    writes += Self::count_status_writes(&env, &listing, update.status);
+                    listings.push_back(listing);
+                }
+                Err(error) => errors.push_back(BatchItemError {
+                    index: index as u32,
+                    error: error as u32,
+                }),
+            }
+        }
+        if !errors.is_empty() {
+            return errors;
+        }
+        if writes > MAX_STATUS_BATCH_WRITES {
+            panic_with_error!(&env, PropertyListingError::BatchTooLarge);
+        }
+
+        for (listing, update) in listings.iter().zip(updates.iter()) {
+            Self::apply_status(&env, listing, update.status, &caller);
+        }
+        Self::extend_instance_ttl(&env);
+
+        errors
+    }
+
+    /// Set the structured metadata of a listing. Callable by the owner and
+    /// delegates allowed to update the listing data.
+    pub fn set_metadata(
+        env: Env,
+        id: String,
+        caller: Address,
+        metadata: ListingMetadata,
+    ) -> ListingMetadata {
+        Self::require_not_paused(&env);
+
+        // Verify the caller is the signer
+        caller.require_auth();
+
+        let listing = Self::get_existing_listing(&env, &id);
+        if !Self::check_permission(&env, &listing, &caller, PERM_UPDATE_DATA) {
+            panic!("Only the owner can update the listing");
+        }
+
+        Self::validate_metadata(&metadata);
+
+        let previous = Self::get_metadata(env.clone(), id.clone());
+        let previous_geohash = Self::searchable_geohash(previous.as_ref());
+        let geohash = Self::searchable_geohash(Some(&metadata));
+        if previous_geohash != geohash {
+            if let Some(previous_geohash) = previous_geohash {
+                Self::update_geo_index(&env, &id, &previous_geohash, false);
+            }
+            if let Some(geohash) = geohash {
+                let listed = listing.status != PropertyStatus::Inactive;
+                Self::update_geo_index(&env, &id, &geohash, listed);
+            }
+        }
+
+        let key = (METADATA, id);
+        env.storage().persistent().set(&key, &metadata);
+        Self::extend_persistent_ttl(&env, &key);
+        Self::extend_instance_ttl(&env);
+
+        metadata
+    }
+
+    /// Get the structured metadata of a listing, if it has been set
+    pub fn get_metadata(env: Env, id: String) -> Option<ListingMetadata> {
+        let key = (METADATA, id);
+        let metadata: Option<ListingMetadata> = env.storage().persistent().get(&key);
+        if metadata.is_some() {
+            Self::extend_persistent_ttl(&env, &key);
+        }
+        metadata
+    }
+
+    /// Set the rental registration of a listing. Only callable by the owner
+    /// and delegates allowed to update the listing data.
+    pub fn set_compliance(
+        env: Env,
+        id: String,
+        caller: Address,
+        compliance: ListingCompliance,
+    ) -> ListingCompliance {
+        Self::require_not_paused(&env);
+
+        // Verify the caller is the signer
+        caller.require_auth();
+
+        let listing = Self::get_existing_listing(&env, &id);
+        if !Self::check_permission(&env, &listing, &caller, PERM_UPDATE_DATA) {
+            panic!("Only the owner can update the listing");
+        }
+
+        Self::validate_compliance(&compliance);
+
+        let key = (COMPLIANCE, id);
+        env.storage().persistent().set(&key, &compliance);
+        Self::extend_persistent_ttl(&env, &key);
+        Self::extend_instance_ttl(&env);
+
+        compliance
+    }
+
+    /// Get the rental registration of a listing, if it has been set
+    pub fn get_compliance(env: Env, id: String) -> Option<ListingCompliance> {
+        let key = (COMPLIANCE, id);
+        let compliance: Option<ListingCompliance> = env.storage().persistent().get(&key);
+        if compliance.is_some() {
+            Self::extend_persistent_ttl(&env, &key);
+        }
+        compliance
+    }
+
+    /// Get the annual night cap of a listing, if its jurisdiction sets one.
+    /// Used by the booking contract to enforce the cap.
+    pub fn get_annual_night_cap(env: Env, id: String) -> Option<u32> {
+        Self::get_compliance(env, id).and_then(|compliance| compliance.annual_night_cap)
+    }
+
+    /// Set the lowest price per night the listing can be booked at, in the
+    /// units of the booking payment. Callable by the owner and delegates
+    /// allowed to update the pricing.
+    pub fn set_nightly_rate(env: Env, id: String, caller: Address, rate: i128) {
+        Self::require_not_paused(&env);
+
+        // Verify the caller is the signer
+        caller.require_auth();
+
+        let listing = Self::get_existing_listing(&env, &id);
+        if !Self::check_permission(&env, &listing, &caller, PERM_UPDATE_PRICING) {
+            panic!("Only the owner can update the listing");
+        }
+        if rate <= 0 {
+            panic!("Invalid rate: must be greater than zero");
+        }
+
+        let key = (NIGHTLY_RATE, id);
+        env.storage().persistent().set(&key, &rate);
+        Self::extend_persistent_ttl(&env, &key);
+        Self::extend_instance_ttl(&env);
+    }
+
+    /// Get the nightly rate of a listing, if it has been set. Used by the
+    /// booking contract to reject underpriced bookings.
+    pub fn get_nightly_rate(env: Env, id: String) -> Option<i128> {
+        let key = (NIGHTLY_RATE, id);
+        let rate: Option<i128> = env.storage().persistent().get(&key);
+        if rate.is_some() {
+            Self::extend_persistent_ttl(&env, &key);
+        }
+        rate
+    }
+
+    /// Get a page of the ids of active listings whose geohash starts with the
+    /// given prefix. Inactive listings and listings without a geohash are
+    /// not searchable.
+    pub fn search_by_geohash(env: Env, prefix: String, cursor: u32, limit: u32) -> ListingIdPage {
+        let limit = Self::validate_limit(limit);
+        if prefix.len() < GEOHASH_MIN_PRECISION
+            || prefix.len() > MAX_GEOHASH_LEN
+            || !Self::has_only_chars(&prefix, Self::is_geohash_char)
+        {
+            panic!("Invalid geohash prefix");
+        }
+
+        let bucket_prefix = Self::geohash_prefix(&env, &prefix, GEOHASH_MAX_PRECISION);
+        let scope = (GEO_INDEX, bucket_prefix).into_val(&env);
+        // Prefixes longer than the bucket length are matched against the
+        // stored geohash of each candidate
+        let exact = prefix.len() <= GEOHASH_MAX_PRECISION;
+
+        Self::get_sparse_page(&env, scope, cursor, limit, |id| {
+            exact
+                || Self::get_metadata(env.clone(), id.clone()).is_some_and(|metadata| {
+                    metadata.geohash.len() >= prefix.len()
+                        && Self::geohash_prefix(&env, &metadata.geohash, prefix.len()) == prefix
+                })
+        })
+    }
+
+    /// Set the tags of a listing, replacing its current tags. Tags must be
+    /// in the vocabulary and appear once. Only callable by the owner and
+    /// delegates allowed to update the listing data.
+    pub fn set_listing_tags(env: Env, id: String, caller: Address, tags: Vec<Symbol>) {
+        Self::require_not_paused(&env);
+
+        // Verify the caller is the signer
+        caller.require_auth();
+
+        let listing = Self::get_existing_listing(&env, &id);
+        if !Self::check_permission(&env, &listing, &caller, PERM_UPDATE_DATA) {
+            panic!("Only the owner can update the listing");
+        }
+
+        if tags.len() > MAX_TAGS_PER_LISTING {
+            panic!("Invalid tags: too many tags");
+        }
+        let vocabulary = Self::get_tag_vocabulary(env.clone());
+        for (i, tag) in tags.iter().enumerate() {
+            if !vocabulary.contains(&tag) {
+                panic!("Invalid tags: unknown tag");
+            }
+            if tags.iter().skip(i + 1).any(|other| other == tag) {
+                panic!("Invalid tags: duplicate tag");
+            }
+        }
+
+        let previous = Self::get_listing_tags(env.clone(), id.clone());
+        Self::update_tag_index(&env, &id, &previous, false);
+        let listed = listing.status != PropertyStatus::Inactive;
+        Self::update_tag_index(&env, &id, &tags, listed);
+
+        let key = (LISTING_TAGS, id);
+        env.storage().persistent().set(&key, &tags);
+        Self::extend_persistent_ttl(&env, &key);
+        Self::extend_instance_ttl(&env);
     }
 
+    /// Get the tags of a listing
+    pub fn get_listing_tags(env: Env, id: String) -> Vec<Symbol> {
+        let key = (LISTING_TAGS, id);
+        match env.storage().persistent().get(&key) {
+            Some(tags) => {
+                Self::extend_persistent_ttl(&env, &key);
+                tags
+            }
+            None => vec![&env],
+        }
+    }
+
+    /// Get a page of the listings with a tag, excluding inactive listings
+    pub fn get_listings_by_tag(env: Env, tag: Symbol, cursor: u32, limit: u32) -> ListingPage {
+        let limit = Self::validate_limit(limit);
+        let scope = (TAG_INDEX, tag).into_val(&env);
+        let page = Self::get_sparse_page(&env, scope, cursor, limit, |_| true);
+
+        let mut listings = vec![&env];
+        for id in page.ids.iter() {
+            if let Some(listing) = Self::get_listing(env.clone(), id) {
+                listings.push_back(listing);
+            }
+        }
 
-    /// Get all listings (simplified implementation)
-    pub fn get_all_listings(env: Env) -> Vec<PropertyListing> {
-        // Note: This is a simplified implementation
-        // In production, you'd want pagination and proper iteration
-        vec![&env]
+        ListingPage {
+            listings,
+            next_cursor: page.next_cursor,
+        }
+    }
 
+    /// Get the maximum number of guests of a listing, if its metadata has
+    /// been set. Used by the booking contract for occupancy checks.
+    pub fn get_max_guests(env: Env, id: String) -> Option<u32> {
+        Self::get_metadata(env, id).map(|metadata| metadata.max_guests)
+    }
+
+    /// Mark a listing as occupied when a guest checks in, or as available
+    /// again when they check out. Only callable by the booking contract.
+    pub fn set_occupancy(env: Env, id: String, occupied: bool) -> PropertyListing {
+        Self::require_not_paused(&env);
+
+        let booking_contract: Address = env
+            .storage()
+            .instance()
+            .get(&BOOKING_CONTRACT)
+            .unwrap_or_else(|| panic!("Booking contract not set"));
+        booking_contract.require_auth();
+
+        let key = (LISTINGS.clone(), id.clone());
+        let mut listing = Self::get_existing_listing(&env, &id);
+
+        let status = match (listing.status, occupied) {
+            (PropertyStatus::Available, true) => PropertyStatus::Booked,
+            (PropertyStatus::Booked, false) => PropertyStatus::Available,
+            _ => panic_with_error!(&env, PropertyListingError::InvalidTransition),
+        };
+
+        Self::record_status_change(&env, &id, listing.status, status, &booking_contract);
+        listing.status = status;
+
+        env.storage().persistent().set(&key, &listing);
+        Self::extend_persistent_ttl(&env, &key);
+        Self::extend_instance_ttl(&env);
+
+        Self::publish_status_change(&env, &listing);
+
+        listing
+    }
+
+    /// Get the most recent status changes of a listing, oldest first
+    pub fn get_status_history(env: Env, id: String) -> Vec<StatusChange> {
+        let key = (STATUS_HISTORY, id);
+        match env.storage().persistent().get(&key) {
+            Some(history) => {
+                Self::extend_persistent_ttl(&env, &key);
+                history
+            }
+            None => vec![&env],
+        }
+    }
+
+    /// Grant a role on a listing to another address, replacing any role it
+    /// already holds. Only callable by the owner.
+    pub fn grant_role(
+        env: Env,
+        id: String,
+        delegate: Address,
+        role: ListingRole,
+        permissions: u32,
+    ) {
+        Self::require_not_paused(&env);
+
+        let listing = Self::get_existing_listing(&env, &id);
+
+        // Verify the owner is the caller
+        listing.owner.require_auth();
+
+        if delegate == listing.owner {
+            panic!("Cannot grant a role to the owner");
+        }
+        if permissions == 0 || permissions & !PERM_ALL != 0 {
+            panic!("Invalid permissions");
+        }
+        if role == ListingRole::CalendarOnly && permissions & !PERM_CALENDAR != 0 {
+            panic!("Permissions not allowed for role");
+        }
+
+        let key = (DELEGATES, id);
+        let mut delegates = Self::get_delegates(env.clone(), listing.id);
+        if !delegates.contains_key(delegate.clone()) && delegates.len() >= MAX_DELEGATES {
+            panic!("Too many delegates");
+        }
+        delegates.set(delegate, Delegate { role, permissions });
+
+        env.storage().persistent().set(&key, &delegates);
+        Self::extend_persistent_ttl(&env, &key);
+        Self::extend_instance_ttl(&env);
+    }
+
+    /// Revoke the role of a delegate on a listing. Only callable by the owner.
+    pub fn revoke_role(env: Env, id: String, delegate: Address) {
+        Self::require_not_paused(&env);
+
+        let listing = Self::get_existing_listing(&env, &id);
+
+        // Verify the owner is the caller
+        listing.owner.require_auth();
+
+        let key = (DELEGATES, id);
+        let mut delegates = Self::get_delegates(env.clone(), listing.id);
+        if delegates.remove(delegate).is_none() {
+            panic!("Delegate not found");
+        }
+
+        env.storage().persistent().set(&key, &delegates);
+        Self::extend_persistent_ttl(&env, &key);
+        Self::extend_instance_ttl(&env);
+    }
+
+    /// Get the delegates of a listing and their roles
+    pub fn get_delegates(env: Env, id: String) -> Map<Address, Delegate> {
+        let key = (DELEGATES, id);
+        match env.storage().persistent().get(&key) {
+            Some(delegates) => {
+                Self::extend_persistent_ttl(&env, &key);
+                delegates
+            }
+            None => Map::new(&env),
+        }
+    }
+
+    /// Check whether an address holds all of the given permission flags on a
+    /// listing. Owners hold every permission; missing and archived listings
+    /// grant none.
+    pub fn has_permission(env: Env, id: String, caller: Address, permission: u32) -> bool {
+        if Self::get_tombstone(env.clone(), id.clone()).is_some() {
+            return false;
+        }
+        match Self::get_listing(env.clone(), id) {
+            Some(listing) => Self::check_permission(&env, &listing, &caller, permission),
+            None => false,
+        }
+    }
+
+    /// Propose transferring a listing to a new owner. The transfer only takes
+    /// effect once the new owner accepts it. Replaces any pending proposal.
+    pub fn propose_transfer(env: Env, id: String, new_owner: Address) {
+        Self::require_not_paused(&env);
+
+        let listing = Self::get_existing_listing(&env, &id);
+
+        // Verify the owner is the caller
+        listing.owner.require_auth();
+
+        if listing.owner == new_owner {
+            panic!("Cannot transfer to the current owner");
+        }
+        if Self::is_tokenized(env.clone(), id.clone()) {
+            panic!("Tokenized listings are transferred as tokens");
+        }
+
+        let key = (PENDING_TRANSFER, id.clone());
+        env.storage().persistent().set(&key, &new_owner);
+        Self::extend_persistent_ttl(&env, &key);
+        Self::extend_instance_ttl(&env);
+
+        env.events()
+            .publish((symbol_short!("xfer_prop"), id, listing.owner), new_owner);
+    }
+
+    /// Accept a pending transfer. Must be authorized by the proposed owner.
+    /// The delegates are dropped and the previous owner's revenue share
+    /// moves to the new owner.
+    pub fn accept_transfer(env: Env, id: String) -> PropertyListing {
+        Self::require_not_paused(&env);
+
+        let mut listing = Self::get_existing_listing(&env, &id);
+        let pending_key = (PENDING_TRANSFER, id.clone());
+        let new_owner: Address = env
+            .storage()
+            .persistent()
+            .get(&pending_key)
+            .unwrap_or_else(|| panic!("No pending transfer"));
+
+        // Verify the proposed owner is the caller
+        new_owner.require_auth();
+
+        let previous_owner = listing.owner.clone();
+        listing.owner = new_owner.clone();
+
+        // Delegates were chosen by the previous owner and do not carry over
+        let key = (LISTINGS.clone(), id.clone());
+        env.storage().persistent().set(&key, &listing);
+        env.storage().persistent().remove(&pending_key);
+        env.storage().persistent().remove(&(DELEGATES, id.clone()));
+        Self::remove_from_owner_index(&env, &previous_owner, &id);
+        Self::add_to_owner_index(&env, &new_owner, &id);
+        Self::move_revenue_share(&env, &id, &previous_owner, &new_owner);
+        Self::extend_persistent_ttl(&env, &key);
+        Self::extend_instance_ttl(&env);
+
+        env.events()
+            .publish((symbol_short!("xfer_done"), id, new_owner), previous_owner);
+
+        listing
+    }
+
+    /// Cancel a pending transfer. Only callable by the current owner.
+    pub fn cancel_transfer(env: Env, id: String) {
+        Self::require_not_paused(&env);
+
+        let listing = Self::get_existing_listing(&env, &id);
+
+        // Verify the owner is the caller
+        listing.owner.require_auth();
+
+        let pending_key = (PENDING_TRANSFER, id.clone());
+        let new_owner: Address = env
+            .storage()
+            .persistent()
+            .get(&pending_key)
+            .unwrap_or_else(|| panic!("No pending transfer"));
+        env.storage().persistent().remove(&pending_key);
+        Self::extend_instance_ttl(&env);
+
+        env.events()
+            .publish((symbol_short!("xfer_cncl"), id, listing.owner), new_owner);
+    }
+
+    /// Get the proposed new owner of a listing, if a transfer is pending
+    pub fn get_pending_transfer(env: Env, id: String) -> Option<Address> {
+        let key = (PENDING_TRANSFER, id);
+        let new_owner: Option<Address> = env.storage().persistent().get(&key);
+        if new_owner.is_some() {
+            Self::extend_persistent_ttl(&env, &key);
+        }
+        new_owner
+    }
+
+    /// Mint a listing as a non-fungible token whose id is the listing id.
+    /// From then on the listing changes hands only by transferring the
+    /// token, which drops the delegates and hands the owner's revenue share
+    /// to the new owner. Only callable by the owner.
+    pub fn tokenize_listing(env: Env, id: String) {
+        Self::require_not_paused(&env);
+
+        let listing = Self::get_existing_listing(&env, &id);
+
+        // Verify the owner is the caller
+        listing.owner.require_auth();
+
+        let key = (TOKENIZED, id.clone());
+        if env.storage().persistent().has(&key) {
+            panic!("Property listing is already tokenized");
+        }
+        env.storage().persistent().set(&key, &true);
+        env.storage()
+            .persistent()
+            .remove(&(PENDING_TRANSFER, id.clone()));
+        Self::extend_persistent_ttl(&env, &key);
+        Self::extend_instance_ttl(&env);
+
+        env.events()
+            .publish((symbol_short!("mint"), id, listing.owner), ());
+    }
+
+    /// Check whether a listing is represented by a token
+    pub fn is_tokenized(env: Env, id: String) -> bool {
+        let key = (TOKENIZED, id);
+        let tokenized = env.storage().persistent().has(&key);
+        if tokenized {
+            Self::extend_persistent_ttl(&env, &key);
+        }
+        tokenized
+    }
+
+    /// Get the owner of a listing token
+    pub fn owner_of(env: Env, token_id: String) -> Address {
+        if !Self::is_tokenized(env.clone(), token_id.clone()) {
+            panic!("Token not found");
+        }
+        Self::get_listing(env, token_id)
+            .unwrap_or_else(|| panic!("Token not found"))
+            .owner
+    }
+
+    /// Get the number of listing tokens held by an address
+    pub fn balance(env: Env, owner: Address) -> u32 {
+        let scope = (OWNER_INDEX, owner).into_val(&env);
+        let count = Self::get_slot_count(&env, scope);
+        let mut balance = 0;
+        for shard_number in 0..count.div_ceil(INDEX_SHARD_SIZE) {
+            for id in Self::get_index_shard(&env, scope, shard_number).iter() {
+                if Self::is_tokenized(env.clone(), id) {
+                    balance += 1;
+                }
+            }
+        }
+        balance
+    }
+
+    /// Transfer a listing token. Only callable by the token owner.
+    pub fn transfer(env: Env, from: Address, to: Address, token_id: String) {
+        Self::require_not_paused(&env);
+        from.require_auth();
+
+        let listing = Self::get_token_listing(&env, &token_id);
+        if listing.owner != from {
+            panic!("Unauthorized: only the token owner can transfer");
+        }
+        Self::transfer_token(&env, listing, to);
+    }
+
+    /// Transfer a listing token on behalf of its owner. Only callable by the
+    /// address approved for the token.
+    pub fn transfer_from(env: Env, spender: Address, from: Address, to: Address, token_id: String) {
+        Self::require_not_paused(&env);
+        spender.require_auth();
+
+        let listing = Self::get_token_listing(&env, &token_id);
+        if listing.owner != from {
+            panic!("Unauthorized: from is not the token owner");
+        }
+        if Self::get_approved(env.clone(), token_id) != Some(spender) {
+            panic!("Unauthorized: spender is not approved");
+        }
+        Self::transfer_token(&env, listing, to);
+    }
+
+    /// Approve an address to transfer a listing token until the given ledger
+    /// sequence, replacing any earlier approval. A ledger in the past
+    /// revokes the approval. Only callable by the token owner.
+    pub fn approve(env: Env, token_id: String, approved: Address, live_until_ledger: u32) {
+        Self::require_not_paused(&env);
+
+        let listing = Self::get_token_listing(&env, &token_id);
+
+        // Verify the owner is the caller
+        listing.owner.require_auth();
+
+        let key = (TOKEN_APPROVAL, token_id.clone());
+        if live_until_ledger < env.ledger().sequence() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(
+                &key,
+                &TokenApproval {
+                    approved: approved.clone(),
+                    live_until_ledger,
+                },
+            );
+            Self::extend_persistent_ttl(&env, &key);
+        }
+        Self::extend_instance_ttl(&env);
+
+        env.events().publish(
+            (symbol_short!("approve"), listing.owner, token_id),
+            (approved, live_until_ledger),
+        );
+    }
+
+    /// Get the address approved to transfer a listing token, if the approval
+    /// has not expired
+    pub fn get_approved(env: Env, token_id: String) -> Option<Address> {
+        let key = (TOKEN_APPROVAL, token_id);
+        let approval: TokenApproval = env.storage().persistent().get(&key)?;
+        if approval.live_until_ledger < env.ledger().sequence() {
+            return None;
+        }
+        Self::extend_persistent_ttl(&env, &key);
+        Some(approval.approved)
+    }
+
+    /// Archive a listing: it is taken off every index and made read-only,
+    /// but stays readable through `get_listing`. Only callable by the owner,
+    /// and refused while the booking contract reports active bookings.
+    pub fn archive_listing(env: Env, id: String) -> PropertyListing {
+        Self::require_not_paused(&env);
+
+        let key = (LISTINGS.clone(), id.clone());
+        let mut listing = Self::get_existing_listing(&env, &id);
+
+        // Verify the owner is the caller
+        listing.owner.require_auth();
+
+        Self::require_removable(&env, &listing);
+
+        Self::remove_from_indexes(&env, &listing);
+        if listing.status != PropertyStatus::Inactive {
+            Self::record_status_change(
+                &env,
+                &id,
+                listing.status,
+                PropertyStatus::Inactive,
+                &listing.owner,
+            );
+            listing.status = PropertyStatus::Inactive;
+        }
+        env.storage().persistent().remove(&(DELEGATES, id.clone()));
+        env.storage()
+            .persistent()
+            .remove(&(PENDING_TRANSFER, id.clone()));
+        env.storage()
+            .persistent()
+            .remove(&(TOKEN_APPROVAL, id.clone()));
+        Self::set_tombstone(&env, &id, &listing.owner, false);
+        Self::start_bond_cooldown(&env, &id);
+
+        env.storage().persistent().set(&key, &listing);
+        Self::extend_persistent_ttl(&env, &key);
+        Self::extend_instance_ttl(&env);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "property_archived"),
+                id,
+                listing.owner.clone(),
+            ),
+            listing.status,
+        );
+
+        listing
+    }
+
+    /// Delete a listing and its metadata, keeping its content and status
+    /// history for existing bookings. Archived listings can be deleted too.
+    /// Only callable by the owner, and refused while the booking contract
+    /// reports active bookings.
+    pub fn delete_listing(env: Env, id: String) {
+        Self::require_not_paused(&env);
+
+        let key = (LISTINGS.clone(), id.clone());
+        let listing: PropertyListing = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic_with_error!(&env, PropertyListingError::ListingNotFound));
+
+        // Verify the owner is the caller
+        listing.owner.require_auth();
+
+        Self::require_removable(&env, &listing);
+
+        Self::remove_from_indexes(&env, &listing);
+        env.storage().persistent().remove(&key);
+        env.storage().persistent().remove(&(METADATA, id.clone()));
+        env.storage().persistent().remove(&(COMPLIANCE, id.clone()));
+        env.storage()
+            .persistent()
+            .remove(&(NIGHTLY_RATE, id.clone()));
+        env.storage()
+            .persistent()
+            .remove(&(LISTING_TAGS, id.clone()));
+        env.storage()
+            .persistent()
+            .remove(&(REVENUE_SHARES, id.clone()));
+        env.storage()
+            .persistent()
+            .remove(&(ATTESTATIONS, id.clone()));
+        env.storage().persistent().remove(&(DELEGATES, id.clone()));
+        env.storage()
+            .persistent()
+            .remove(&(PENDING_TRANSFER, id.clone()));
+        env.storage().persistent().remove(&(TOKENIZED, id.clone()));
+        env.storage()
+            .persistent()
+            .remove(&(TOKEN_APPROVAL, id.clone()));
+        Self::set_tombstone(&env, &id, &listing.owner, true);
+        Self::start_bond_cooldown(&env, &id);
+        Self::extend_instance_ttl(&env);
+
+        env.events().publish(
+            (Symbol::new(&env, "property_deleted"), id, listing.owner),
+            (),
+        );
+    }
+
+    /// Split the host proceeds of a listing between beneficiaries. Shares
+    /// must be positive, name each beneficiary once and sum to
+    /// TOTAL_SHARE_BPS; an empty list pays everything to the owner again.
+    /// Accepted transfers and transferred listing tokens hand the owner's
+    /// share to the new owner. Only callable by the owner.
+    pub fn set_revenue_shares(env: Env, id: String, shares: Vec<RevenueShare>) {
+        Self::require_not_paused(&env);
+
+        let listing = Self::get_existing_listing(&env, &id);
+
+        // Verify the owner is the caller
+        listing.owner.require_auth();
+
+        let key = (REVENUE_SHARES, id.clone());
+        if shares.is_empty() {
+            env.storage().persistent().remove(&key);
+        } else {
+            Self::validate_revenue_shares(&shares);
+            env.storage().persistent().set(&key, &shares);
+            Self::extend_persistent_ttl(&env, &key);
+        }
+        Self::extend_instance_ttl(&env);
+
+        env.events()
+            .publish((symbol_short!("rev_share"), id, listing.owner), shares);
+    }
+
+    /// Get the shares of the host proceeds of a listing. Listings without
+    /// shares pay everything to the owner; missing listings have none.
+    /// Used by the booking contract to split payouts.
+    pub fn get_revenue_shares(env: Env, id: String) -> Vec<RevenueShare> {
+        let key = (REVENUE_SHARES, id.clone());
+        if let Some(shares) = env.storage().persistent().get(&key) {
+            Self::extend_persistent_ttl(&env, &key);
+            return shares;
+        }
+        match Self::get_listing(env.clone(), id) {
+            Some(listing) => vec![
+                &env,
+                RevenueShare {
+                    beneficiary: listing.owner,
+                    bps: TOTAL_SHARE_BPS,
+                },
+            ],
+            None => vec![&env],
+        }
+    }
+
+    /// Register an address allowed to attest to listings. Only callable by
+    /// the admin.
+    pub fn add_verifier(env: Env, verifier: Address) {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        let key = (VERIFIER, verifier);
+        env.storage().persistent().set(&key, &true);
+        Self::extend_persistent_ttl(&env, &key);
+        Self::extend_instance_ttl(&env);
+    }
+
+    /// Unregister a verifier. Attestations it already issued are kept until
+    /// they expire or are revoked. Only callable by the admin.
+    pub fn remove_verifier(env: Env, verifier: Address) {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        let key = (VERIFIER, verifier);
+        if !env.storage().persistent().has(&key) {
+            panic!("Verifier not found");
+        }
+        env.storage().persistent().remove(&key);
+        Self::extend_instance_ttl(&env);
+    }
+
+    /// Check whether an address is a registered verifier
+    pub fn is_verifier(env: Env, address: Address) -> bool {
+        let key = (VERIFIER, address);
+        let registered = env.storage().persistent().has(&key);
+        if registered {
+            Self::extend_persistent_ttl(&env, &key);
+        }
+        registered
+    }
+
+    /// Attest to a fact about a listing until `expires_at`, replacing any
+    /// attestation of the same kind. Only callable by a registered verifier.
+    pub fn attest(
+        env: Env,
+        id: String,
+        verifier: Address,
+        kind: AttestationKind,
+        evidence_hash: BytesN<32>,
+        expires_at: u64,
+    ) -> Attestation {
+        Self::require_not_paused(&env);
+
+        // Verify the verifier is the caller
+        verifier.require_auth();
+
+        if !Self::is_verifier(env.clone(), verifier.clone()) {
+            panic!("Unauthorized: only registered verifiers can attest");
+        }
+        let listing = Self::get_existing_listing(&env, &id);
+        let issued_at = env.ledger().timestamp();
+        if expires_at <= issued_at {
+            panic!("Invalid expiry: must be in the future");
+        }
+
+        let attestation = Attestation {
+            kind,
+            verifier: verifier.clone(),
+            evidence_hash,
+            issued_at,
+            expires_at,
+            revoked: false,
+        };
+        let key = (ATTESTATIONS, id.clone());
+        let mut attestations = Self::get_attestations(env.clone(), listing.id);
+        match attestations.iter().position(|entry| entry.kind == kind) {
+            Some(index) => attestations.set(index as u32, attestation.clone()),
+            None => attestations.push_back(attestation.clone()),
+        }
+
+        env.storage().persistent().set(&key, &attestations);
+        Self::extend_persistent_ttl(&env, &key);
+        Self::extend_instance_ttl(&env);
+
+        env.events()
+            .publish((symbol_short!("attested"), id, verifier), kind);
+
+        attestation
+    }
+
+    /// Revoke the attestation of the given kind on a listing. Callable by
+    /// the verifier that issued it or by the admin.
+    pub fn revoke_attestation(env: Env, id: String, kind: AttestationKind, caller: Address) {
+        Self::require_not_paused(&env);
+        caller.require_auth();
+
+        let key = (ATTESTATIONS, id.clone());
+        let mut attestations = Self::get_attestations(env.clone(), id.clone());
+        let index = attestations
+            .iter()
+            .position(|entry| entry.kind == kind)
+            .unwrap_or_else(|| panic!("Attestation not found")) as u32;
+        let mut attestation = attestations.get(index).unwrap();
+
+        if caller != attestation.verifier && caller != Self::get_admin(&env) {
+            panic!("Unauthorized: only the verifier or admin can revoke");
+        }
+        if attestation.revoked {
+            panic!("Attestation already revoked");
+        }
+        attestation.revoked = true;
+        attestations.set(index, attestation);
+
+        env.storage().persistent().set(&key, &attestations);
+        Self::extend_persistent_ttl(&env, &key);
+        Self::extend_instance_ttl(&env);
+
+        env.events()
+            .publish((symbol_short!("att_revok"), id, caller), kind);
+    }
+
+    /// Get the attestations of a listing, including expired and revoked
+    /// ones. Callers check `expires_at` and `revoked` themselves.
+    pub fn get_attestations(env: Env, id: String) -> Vec<Attestation> {
+        let key = (ATTESTATIONS, id);
+        match env.storage().persistent().get(&key) {
+            Some(attestations) => {
+                Self::extend_persistent_ttl(&env, &key);
+                attestations
+            }
+            None => vec![&env],
+        }
+    }
+
+    /// Get the bond locked for a listing
+    pub fn get_bond(env: Env, id: String) -> Option<ListingBond> {
+        let key = (BOND, id);
+        let bond: Option<ListingBond> = env.storage().persistent().get(&key);
+        if bond.is_some() {
+            Self::extend_persistent_ttl(&env, &key);
+        }
+        bond
+    }
+
+    /// Return the bond of a removed listing to its owner once the cooldown
+    /// has passed. Only callable by the owner the listing was removed by.
+    pub fn withdraw_bond(env: Env, id: String) -> i128 {
+        Self::require_not_paused(&env);
+
+        let tombstone = Self::get_tombstone(env.clone(), id.clone())
+            .unwrap_or_else(|| panic!("Property listing is not archived"));
+
+        // Verify the owner is the caller
+        tombstone.owner.require_auth();
+
+        let key = (BOND, id.clone());
+        let bond =
+            Self::get_bond(env.clone(), id.clone()).unwrap_or_else(|| panic!("Bond not found"));
+        match bond.unlock_at {
+            Some(unlock_at) if env.ledger().timestamp() >= unlock_at => {}
+            _ => panic!("Bond is still in cooldown"),
+        }
+
+        env.storage().persistent().remove(&key);
+        token::Client::new(&env, &bond.token).transfer(
+            &env.current_contract_address(),
+            &tombstone.owner,
+            &bond.amount,
+        );
+        Self::extend_instance_ttl(&env);
+
+        env.events().publish(
+            (symbol_short!("bond_rel"), id, tombstone.owner),
+            bond.amount,
+        );
+
+        bond.amount
+    }
+
+    /// Slash part or all of the bond of a listing proven fraudulent, paying
+    /// it to an affected guest or a treasury. Bonds can be slashed during
+    /// the cooldown too. Callable by the admin or the arbitrator.
+    pub fn slash_bond(env: Env, id: String, caller: Address, recipient: Address, amount: i128) {
+        caller.require_auth();
+
+        let arbitrator: Option<Address> = env.storage().instance().get(&ARBITRATOR);
+        if caller != Self::get_admin(&env) && Some(caller.clone()) != arbitrator {
+            panic!("Unauthorized: only the admin or arbitrator can slash bonds");
+        }
+
+        let key = (BOND, id.clone());
+        let mut bond =
+            Self::get_bond(env.clone(), id.clone()).unwrap_or_else(|| panic!("Bond not found"));
+        if amount <= 0 || amount > bond.amount {
+            panic!("Invalid amount: must be positive and at most the bond");
+        }
+
+        bond.amount -= amount;
+        if bond.amount == 0 {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &bond);
+            Self::extend_persistent_ttl(&env, &key);
+        }
+        token::Client::new(&env, &bond.token).transfer(
+            &env.current_contract_address(),
+            &recipient,
+            &amount,
+        );
+        Self::extend_instance_ttl(&env);
+
+        env.events()
+            .publish((symbol_short!("bond_slsh"), id, recipient), amount);
+    }
+
+    /// Get the tombstone of an archived or deleted listing
+    pub fn get_tombstone(env: Env, id: String) -> Option<ListingTombstone> {
+        let key = (TOMBSTONE, id);
+        let tombstone: Option<ListingTombstone> = env.storage().persistent().get(&key);
+        if tombstone.is_some() {
+            Self::extend_persistent_ttl(&env, &key);
+        }
+        tombstone
+    }
+
+    /// Get a property listing by ID
+    pub fn get_listing(env: Env, id: String) -> Option<PropertyListing> {
+        let key = (LISTINGS.clone(), id);
+        let listing: Option<PropertyListing> = env.storage().persistent().get(&key);
+        if listing.is_some() {
+            Self::extend_persistent_ttl(&env, &key);
+        }
+        listing
+    }
+
+    /// Get a page of listings in index order. Listings are appended in
+    /// creation order; removing one moves the last listing into its place.
+    pub fn get_all_listings(env: Env, cursor: u32, limit: u32) -> ListingPage {
+        let count = Self::listing_count(env.clone());
+        Self::get_index_page(&env, LIST_INDEX.to_val(), count, cursor, limit)
+    }
+
+    /// Get the number of listings in the index, which excludes archived and
+    /// deleted listings
+    pub fn listing_count(env: Env) -> u32 {
+        env.storage().persistent().get(&LIST_COUNT).unwrap_or(0)
+    }
+
+    /// Get a page of the listings of an owner in index order. Listings are
+    /// appended when created or received; removing or transferring one moves
+    /// the last listing of the owner into its place.
+    pub fn get_listings_by_owner(env: Env, owner: Address, cursor: u32, limit: u32) -> ListingPage {
+        let scope = (OWNER_INDEX, owner).into_val(&env);
+        let count = Self::get_slot_count(&env, scope);
+        Self::get_index_page(&env, scope, count, cursor, limit)
+    }
+
+    /// Add existing listings to the listing and owner indexes and start their
+    /// content history. Used to backfill listings created before the listing
+    /// index existed, converting them to the current layout. Archived and
+    /// deleted listings are converted but stay out of the indexes; missing or
+    /// already indexed ids are skipped. Only callable by the admin.
+    pub fn index_listings(env: Env, ids: Vec<String>) -> u32 {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        let mut added = 0;
+        for id in ids.iter() {
+            if env.storage().persistent().has(&(TOMBSTONE, id.clone())) {
+                Self::convert_removed_listing(&env, &id);
+            } else if let Some(listing) = Self::load_unindexed_listing(&env, &id) {
+                Self::append_to_index(
+                    &env,
+                    LIST_INDEX.to_val(),
+                    LIST_COUNT.to_val(),
+                    LIST_POS,
+                    &id,
+                );
+                Self::add_to_owner_index(&env, &listing.owner, &id);
+                if Self::get_listing_version(env.clone(), id.clone()) == 0 {
+                    Self::append_version(&env, &id, &listing.data_hash, &listing.owner);
+                }
+                added += 1;
+            }
+        }
+        Self::extend_instance_ttl(&env);
+
+        added
+    }
+
+    /// Extend the TTL of the contract instance, the listing index and
+    /// everything stored for the given listings: their records, tombstones
+    /// and bonds, their metadata, content versions, status history,
+    /// delegates, compliance records, nightly rates, tags, revenue shares,
+    /// attestations, transfers and tokens, and their positions in the owner,
+    /// location and tag indexes. Missing entries are skipped. Only callable by the admin.
+    pub fn bump_ttl(env: Env, ids: Vec<String>) {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        Self::extend_instance_ttl(&env);
+
+        for id in ids.iter() {
+            for prefix in [
+                LISTINGS,
+                LIST_POS,
+                OWNER_POS,
+                TOMBSTONE,
+                BOND,
+                METADATA,
+                LIST_VERSION_COUNT,
+                STATUS_HISTORY,
+                DELEGATES,
+                COMPLIANCE,
+                NIGHTLY_RATE,
+                LISTING_TAGS,
+                REVENUE_SHARES,
+                ATTESTATIONS,
+                PENDING_TRANSFER,
+                TOKENIZED,
+                TOKEN_APPROVAL,
+            ] {
+                Self::extend_persistent_ttl_if_present(&env, &(prefix, id.clone()));
+            }
+            for version in 1..=Self::get_listing_version(env.clone(), id.clone()) {
+                Self::extend_persistent_ttl_if_present(&env, &(LIST_VERSION, id.clone(), version));
+            }
+            let metadata = Self::get_metadata(env.clone(), id.clone());
+            if let Some(geohash) = Self::searchable_geohash(metadata.as_ref()) {
+                for scope in Self::geo_scopes(&env, &geohash).iter() {
+                    Self::extend_index_slot(&env, scope, &id);
+                }
+            }
+            for tag in Self::get_listing_tags(env.clone(), id.clone()).iter() {
+                Self::extend_index_slot(&env, (TAG_INDEX, tag).into_val(&env), &id);
+            }
+            let position: Option<u32> = env.storage().persistent().get(&(OWNER_POS, id.clone()));
+            if let (Some(listing), Some(position)) = (Self::get_listing(env.clone(), id), position)
+            {
+                let scope = (OWNER_INDEX, listing.owner);
+                Self::extend_persistent_ttl(&env, &scope);
+                Self::extend_persistent_ttl(&env, &(scope, position / INDEX_SHARD_SIZE));
+            }
+        }
+
+        let count = Self::listing_count(env.clone());
+        if count > 0 {
+            Self::extend_persistent_ttl(&env, &LIST_COUNT);
+            for shard_number in 0..count.div_ceil(INDEX_SHARD_SIZE) {
+                Self::extend_persistent_ttl(&env, &(LIST_INDEX, shard_number));
+            }
+        }
+    }
+
+    /// Upgrade the contract code. Storage is kept as is, so `migrate` must be
+    /// called afterwards if the new code expects a newer schema version.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    /// Get the schema version of the data in storage
+    pub fn version(env: Env) -> u32 {
+        env.storage().instance().get(&VERSION).unwrap_or(0)
+    }
+
+    /// Migrate stored data to the schema version of the current code,
+    /// converting at most `limit` listings per call so that large data sets
+    /// fit in a transaction. Each call continues where the previous one
+    /// stopped, and the stored version is only bumped once a step has
+    /// converted every listing. Call it until it returns SCHEMA_VERSION and
+    /// keep the contract paused meanwhile. Only callable by the admin.
+    pub fn migrate(env: Env, limit: u32) -> u32 {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        if limit == 0 {
+            panic!("Invalid limit: must be greater than zero");
+        }
+        let mut version = Self::version(env.clone());
+        if version > SCHEMA_VERSION {
+            panic!("Stored schema is newer than the contract code");
+        }
+
+        let mut cursor: u32 = env.storage().instance().get(&MIGRATION).unwrap_or(0);
+        let mut budget = limit;
+        while version < SCHEMA_VERSION {
+            // Contracts deployed before schema versioning was introduced
+            // already use the version 1 layout. Version 2 adds the listing
+            // index. Existing listings cannot be enumerated from storage, so
+            // the index starts empty and is backfilled with `index_listings`.
+            // Later steps go through the listings in the listing index.
+            let size = if version < 2 {
+                0
+            } else {
+                Self::listing_count(env.clone())
+            };
+            let end = size.min(cursor.saturating_add(budget));
+
+            let mut shard = vec![&env];
+            for position in cursor..end {
+                if position == cursor || position % INDEX_SHARD_SIZE == 0 {
+                    let shard_number = position / INDEX_SHARD_SIZE;
+                    shard = Self::get_index_shard(&env, LIST_INDEX.to_val(), shard_number);
+                }
+                let id = shard.get(position % INDEX_SHARD_SIZE).unwrap();
+                match version {
+                    // The owner index of version 3 is rebuilt by the version
+                    // 7 step
+                    2 => {}
+                    // So is the location index of version 4
+                    3 => {}
+                    4 => Self::migrate_v4_to_v5(&env, &id),
+                    5 => Self::migrate_v5_to_v6(&env, &id),
+                    6 => Self::migrate_v6_to_v7(&env, &id),
+                    _ => panic!("No migration path for schema version"),
+                }
+            }
+            budget -= end - cursor;
+
+            if end < size {
+                cursor = end;
+                break;
+            }
+            cursor = 0;
+            version += 1;
+        }
+
+        if cursor == 0 {
+            env.storage().instance().remove(&MIGRATION);
+        } else {
+            env.storage().instance().set(&MIGRATION, &cursor);
+        }
+        env.storage().instance().set(&VERSION, &version);
+        Self::extend_instance_ttl(&env);
+
+        version
+    }
+
+    /// Version 5 adds the content history. Each indexed listing gets a first
+    /// version holding its current data hash, attributed to its owner.
+    fn migrate_v4_to_v5(env: &Env, id: &String) {
+        if Self::get_listing_version(env.clone(), id.clone()) > 0 {
+            return;
+        }
+        if let Some(listing) = Self::get_legacy_listing(env, id) {
+            let key = (LIST_VERSION, id.clone(), 1u32);
+            let count_key = (LIST_VERSION_COUNT, id.clone());
+            env.storage().persistent().set(
+                &key,
+                &LegacyListingVersion {
+                    version: 1,
+                    data_hash: listing.data_hash,
+                    updated_by: listing.owner,
+                    timestamp: env.ledger().timestamp(),
+                },
+            );
+            env.storage().persistent().set(&count_key, &1u32);
+            Self::extend_persistent_ttl(env, &key);
+            Self::extend_persistent_ttl(env, &count_key);
+        }
+    }
+
+    /// Version 6 stores data hashes as 32 bytes. Indexed listings and their
+    /// content versions are converted here. Listings outside the index,
+    /// including archived and deleted listings, cannot be enumerated and are
+    /// converted when they are passed to `index_listings`.
+    fn migrate_v5_to_v6(env: &Env, id: &String) {
+        if let Some(listing) = Self::get_legacy_listing(env, id) {
+            Self::convert_legacy_listing(env, listing);
+        }
+    }
+
+    /// Version 7 splits the owner index, location buckets and tag indexes
+    /// into shards. They are rebuilt here from the listings in the listing
+    /// index, and the single-entry indexes of earlier versions are left to
+    /// expire.
+    fn migrate_v6_to_v7(env: &Env, id: &String) {
+        if let Some(listing) = Self::get_listing(env.clone(), id.clone()) {
+            Self::add_to_owner_index(env, &listing.owner, id);
+            let listed = listing.status != PropertyStatus::Inactive;
+            let metadata = Self::get_metadata(env.clone(), id.clone());
+            if let Some(geohash) = Self::searchable_geohash(metadata.as_ref()) {
+                Self::update_geo_index(env, id, &geohash, listed);
+            }
+            let tags = Self::get_listing_tags(env.clone(), id.clone());
+            Self::update_tag_index(env, id, &tags, listed);
+        }
+    }
+
+    /// Set the booking contract allowed to mark listings as booked. Only
+    /// callable by the admin.
+    pub fn set_booking_contract(env: Env, booking_contract: Address) {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        env.storage()
+            .instance()
+            .set(&BOOKING_CONTRACT, &booking_contract);
+        Self::extend_instance_ttl(&env);
+    }
+
+    /// Get the booking contract allowed to mark listings as booked
+    pub fn get_booking_contract(env: Env) -> Option<Address> {
+        env.storage().instance().get(&BOOKING_CONTRACT)
+    }
+
+    /// Require new listings to lock a bond of `amount` in `token`, which can
+    /// be withdrawn `cooldown` seconds after the listing is removed. An
+    /// amount of zero stops requiring bonds. Existing bonds keep their terms.
+    /// Only callable by the admin.
+    pub fn set_bond_config(env: Env, token: Address, amount: i128, cooldown: u64) {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        if amount < 0 {
+            panic!("Invalid amount: must not be negative");
+        }
+        if amount == 0 {
+            env.storage().instance().remove(&BOND_CONFIG);
+        } else {
+            env.storage().instance().set(
+                &BOND_CONFIG,
+                &BondConfig {
+                    token,
+                    amount,
+                    cooldown,
+                },
+            );
+        }
+        Self::extend_instance_ttl(&env);
+    }
+
+    /// Get the bond required from new listings, if any
+    pub fn get_bond_config(env: Env) -> Option<BondConfig> {
+        env.storage().instance().get(&BOND_CONFIG)
+    }
+
+    /// Set the arbitrator allowed to slash listing bonds. Only callable by
+    /// the admin.
+    pub fn set_arbitrator(env: Env, arbitrator: Address) {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        env.storage().instance().set(&ARBITRATOR, &arbitrator);
+        Self::extend_instance_ttl(&env);
+    }
+
+    /// Add a tag to the vocabulary owners pick listing tags from. Only
+    /// callable by the admin.
+    pub fn add_tag(env: Env, tag: Symbol) {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        let mut vocabulary = Self::get_tag_vocabulary(env.clone());
+        if vocabulary.contains(&tag) {
+            panic!("Tag already exists");
+        }
+        if vocabulary.len() >= MAX_VOCABULARY_SIZE {
+            panic!("Too many tags");
+        }
+        vocabulary.push_back(tag);
+
+        env.storage().instance().set(&TAG_VOCABULARY, &vocabulary);
+        Self::extend_instance_ttl(&env);
+    }
+
+    /// Remove a tag from the vocabulary. Listings keep the tag until their
+    /// tags are next updated. Only callable by the admin.
+    pub fn remove_tag(env: Env, tag: Symbol) {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        let mut vocabulary = Self::get_tag_vocabulary(env.clone());
+        let index = vocabulary
+            .first_index_of(&tag)
+            .unwrap_or_else(|| panic!("Tag not found"));
+        vocabulary.remove(index);
+
+        env.storage().instance().set(&TAG_VOCABULARY, &vocabulary);
+        Self::extend_instance_ttl(&env);
+    }
+
+    /// Get the tags owners can attach to listings
+    pub fn get_tag_vocabulary(env: Env) -> Vec<Symbol> {
+        env.storage()
+            .instance()
+            .get(&TAG_VOCABULARY)
+            .unwrap_or_else(|| vec![&env])
+    }
+
+    /// Set the guardian allowed to pause the contract. Only callable by the admin.
+    pub fn set_guardian(env: Env, guardian: Address) {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        env.storage().instance().set(&GUARDIAN, &guardian);
+        Self::extend_instance_ttl(&env);
+    }
+
+    /// Pause all state-changing entrypoints. Callable by the admin or the guardian.
+    pub fn pause(env: Env, caller: Address) {
+        caller.require_auth();
+
+        let guardian: Option<Address> = env.storage().instance().get(&GUARDIAN);
+        if caller != Self::get_admin(&env) && Some(caller.clone()) != guardian {
+            panic!("Unauthorized: only the admin or guardian can pause");
+        }
+
+        env.storage().instance().set(&PAUSED, &true);
+        Self::extend_instance_ttl(&env);
+
+        env.events().publish((symbol_short!("paused"),), caller);
+    }
+
+    /// Resume state-changing entrypoints. Only callable by the admin.
+    pub fn unpause(env: Env) {
+        let admin = Self::get_admin(&env);
+        admin.require_auth();
+
+        env.storage().instance().set(&PAUSED, &false);
+        Self::extend_instance_ttl(&env);
+
+        env.events().publish((symbol_short!("unpaused"),), admin);
+    }
+
+    /// Check whether state-changing entrypoints are paused
+    pub fn is_paused(env: Env) -> bool {
+        env.storage().instance().get(&PAUSED).unwrap_or(false)
+    }
+
+    /// Internal helper to reject state changes while paused
+    fn require_not_paused(env: &Env) {
+        if Self::is_paused(env.clone()) {
+            panic_with_error!(env, PropertyListingError::ContractPaused);
+        }
+    }
+
+    /// Internal helper to read a listing that must exist and not be archived
+    fn get_existing_listing(env: &Env, id: &String) -> PropertyListing {
+        let listing = env
+            .storage()
+            .persistent()
+            .get(&(LISTINGS.clone(), id.clone()))
+            .unwrap_or_else(|| panic_with_error!(env, PropertyListingError::ListingNotFound));
+        if env.storage().persistent().has(&(TOMBSTONE, id.clone())) {
+            panic_with_error!(env, PropertyListingError::ListingRemoved);
+        }
+        listing
+    }
+
+    /// Internal helper to check whether a listing id is well formed
+    fn is_valid_id(id: &String) -> bool {
+        !id.is_empty()
+            && id.len() <= MAX_ID_LEN
+            && Self::has_only_chars(id, |c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_')
+    }
+
+    /// Internal helper returning why a listing cannot be created under an
+    /// id, if it cannot
+    fn check_new_listing(env: &Env, id: &String) -> Option<PropertyListingError> {
+        if !Self::is_valid_id(id) {
+            Some(PropertyListingError::InvalidId)
+        } else if env
+            .storage()
+            .persistent()
+            .has(&(LISTINGS.clone(), id.clone()))
+        {
+            Some(PropertyListingError::ListingAlreadyExists)
+        } else if env.storage().persistent().has(&(TOMBSTONE, id.clone())) {
+            Some(PropertyListingError::ListingRemoved)
+        } else {
+            None
+        }
+    }
+
+    /// Internal helper to store a new listing, lock its bond and add it to
+    /// the content history and indexes
+    fn insert_listing(
+        env: &Env,
+        id: String,
+        data_hash: BytesN<32>,
+        owner: Address,
+    ) -> PropertyListing {
+        let key = (LISTINGS.clone(), id.clone());
+        let listing = PropertyListing {
+            id: id.clone(),
+            data_hash: data_hash.clone(),
+            owner: owner.clone(),
+            status: PropertyStatus::Available,
+        };
+
+        // Store the listing
+        env.storage().persistent().set(&key, &listing);
+        Self::extend_persistent_ttl(env, &key);
+        Self::lock_bond(env, &id, &owner);
+        Self::append_version(env, &id, &data_hash, &owner);
+        Self::append_to_index(env, LIST_INDEX.to_val(), LIST_COUNT.to_val(), LIST_POS, &id);
+        Self::add_to_owner_index(env, &owner, &id);
+
+        env.events()
+            .publish((Symbol::new(env, "property_created"), id, owner), data_hash);
+
+        listing
+    }
+
+    /// Internal helper returning the listing a batch status update applies
+    /// to, or why it is rejected
+    fn check_status_update(
+        env: &Env,
+        update: &StatusUpdate,
+        caller: &Address,
+    ) -> Result<PropertyListing, PropertyListingError> {
+        let listing = Self::get_listing(env.clone(), update.id.clone())
+            .ok_or(PropertyListingError::ListingNotFound)?;
+        if Self::get_tombstone(env.clone(), update.id.clone()).is_some() {
+            return Err(PropertyListingError::ListingRemoved);
+        }
+        if !Self::check_permission(env, &listing, caller, PERM_UPDATE_STATUS) {
+            return Err(PropertyListingError::Unauthorized);
+        }
+        if !Self::is_valid_transition(listing.status, update.status) {
+            return Err(PropertyListingError::InvalidTransition);
+        }
+        Ok(listing)
+    }
+
+    /// Internal helper counting the ledger entries `apply_status` writes.
+    /// Listings given their index slots before keep them, so each slot is a
+    /// single entry.
+    fn count_status_writes(env: &Env, listing: &PropertyListing, status: PropertyStatus) -> u32 {
+        let mut writes = 2;
+        if (listing.status == PropertyStatus::Inactive) != (status == PropertyStatus::Inactive) {
+            writes += Self::get_listing_tags(env.clone(), listing.id.clone()).len();
+            let metadata = Self::get_metadata(env.clone(), listing.id.clone());
+            if let Some(geohash) = Self::searchable_geohash(metadata.as_ref()) {
+                writes += Self::geo_scopes(env, &geohash).len();
+            }
+        }
+        writes
+    }
+
+    /// Internal helper to store a new status, record it in the status
+    /// history and list or unlist the listing in the location and tag
+    /// indexes
+    fn apply_status(
+        env: &Env,
+        mut listing: PropertyListing,
+        status: PropertyStatus,
+        caller: &Address,
+    ) -> PropertyListing {
+        let id = listing.id.clone();
+        let key = (LISTINGS.clone(), id.clone());
+
+        Self::record_status_change(env, &id, listing.status, status, caller);
+        if (listing.status == PropertyStatus::Inactive) != (status == PropertyStatus::Inactive) {
+            let metadata = Self::get_metadata(env.clone(), id.clone());
+            if let Some(geohash) = Self::searchable_geohash(metadata.as_ref()) {
+                Self::update_geo_index(env, &id, &geohash, status != PropertyStatus::Inactive);
+            }
+            let tags = Self::get_listing_tags(env.clone(), id.clone());
+            Self::update_tag_index(env, &id, &tags, status != PropertyStatus::Inactive);
+        }
+        listing.status = status;
+
+        // Store updated listing
+        env.storage().persistent().set(&key, &listing);
+        Self::extend_persistent_ttl(env, &key);
+
+        Self::publish_status_change(env, &listing);
+
+        listing
+    }
+
+    /// Internal helper to reject batches over the maximum size
+    fn require_batch_size(env: &Env, len: u32) {
+        if len > MAX_BATCH_SIZE {
+            panic_with_error!(env, PropertyListingError::BatchTooLarge);
+        }
+    }
+
+    /// Internal helper to read a listing in the layout used before schema
+    /// version 6
+    fn get_legacy_listing(env: &Env, id: &String) -> Option<LegacyPropertyListing> {
+        env.storage()
+            .persistent()
+            .get(&(LISTINGS.clone(), id.clone()))
+    }
+
+    /// Internal helper to read a listing that is not in the listing index yet,
+    /// converting it if it still uses the layout from before schema version 6
+    fn load_unindexed_listing(env: &Env, id: &String) -> Option<PropertyListing> {
+        let key = (LISTINGS.clone(), id.clone());
+        if env.storage().persistent().has(&(LIST_POS, id.clone()))
+            || env.storage().persistent().has(&(TOMBSTONE, id.clone()))
+        {
+            return None;
+        }
+
+        let raw: Val = env.storage().persistent().get(&key)?;
+        match LegacyPropertyListing::try_from_val(env, &raw) {
+            Ok(legacy) => Some(Self::convert_legacy_listing(env, legacy)),
+            Err(_) => Some(PropertyListing::try_from_val(env, &raw).unwrap()),
+        }
+    }
+
+    /// Internal helper to rewrite a legacy listing and its content versions
+    /// with 32-byte data hashes
+    fn convert_legacy_listing(env: &Env, legacy: LegacyPropertyListing) -> PropertyListing {
+        let id = legacy.id.clone();
+        let listing = PropertyListing {
+            id: legacy.id,
+            data_hash: Self::convert_legacy_hash(env, &legacy.data_hash),
+            owner: legacy.owner,
+            status: legacy.status,
+        };
+        let key = (LISTINGS.clone(), id.clone());
+        env.storage().persistent().set(&key, &listing);
+        Self::extend_persistent_ttl(env, &key);
+        Self::convert_legacy_versions(env, &id);
+
+        listing
+    }
+
+    /// Internal helper to convert the record and content versions of an
+    /// archived or deleted listing from before schema version 6. Deleted
+    /// listings only keep their content versions.
+    fn convert_removed_listing(env: &Env, id: &String) {
+        let raw: Option<Val> = env
+            .storage()
+            .persistent()
+            .get(&(LISTINGS.clone(), id.clone()));
+        match raw.map(|raw| LegacyPropertyListing::try_from_val(env, &raw)) {
+            Some(Ok(legacy)) => {
+                Self::convert_legacy_listing(env, legacy);
+            }
+            Some(Err(_)) => {}
+            None => Self::convert_legacy_versions(env, id),
+        }
+    }
+
+    /// Internal helper to rewrite the content versions of a listing that
+    /// still hold string hashes. Converted versions are left as they are.
+    fn convert_legacy_versions(env: &Env, id: &String) {
+        for version in 1..=Self::get_listing_version(env.clone(), id.clone()) {
+            let version_key = (LIST_VERSION, id.clone(), version);
+            let raw: Option<Val> = env.storage().persistent().get(&version_key);
+            let entry = raw.and_then(|raw| LegacyListingVersion::try_from_val(env, &raw).ok());
+            if let Some(entry) = entry {
+                env.storage().persistent().set(
+                    &version_key,
+                    &ListingVersion {
+                        version: entry.version,
+                        data_hash: Self::convert_legacy_hash(env, &entry.data_hash),
+                        updated_by: entry.updated_by,
+                        timestamp: entry.timestamp,
+                    },
+                );
+                Self::extend_persistent_ttl(env, &version_key);
+            }
+        }
+    }
+
+    /// Internal helper to turn a legacy string hash into 32 bytes. The backend
+    /// stored hex encoded SHA-256 digests, which are decoded as is; any other
+    /// value is replaced by the SHA-256 of its XDR encoding.
+    fn convert_legacy_hash(env: &Env, data_hash: &String) -> BytesN<32> {
+        let mut hex = [0u8; 64];
+        if data_hash.len() == 64 {
+            data_hash.copy_into_slice(&mut hex);
+            let mut bytes = [0u8; 32];
+            let decoded = bytes.iter_mut().enumerate().all(|(i, byte)| {
+                match (Self::hex_value(hex[2 * i]), Self::hex_value(hex[2 * i + 1])) {
+                    (Some(high), Some(low)) => {
+                        *byte = high << 4 | low;
+                        true
+                    }
+                    _ => false,
+                }
+            });
+            if decoded {
+                return BytesN::from_array(env, &bytes);
+            }
+        }
+        env.crypto().sha256(&data_hash.clone().to_xdr(env)).into()
+    }
+
+    /// Internal helper to decode a hex digit
+    fn hex_value(c: u8) -> Option<u8> {
+        match c {
+            b'0'..=b'9' => Some(c - b'0'),
+            b'a'..=b'f' => Some(c - b'a' + 10),
+            b'A'..=b'F' => Some(c - b'A' + 10),
+            _ => None,
+        }
+    }
+
+    /// Internal helper to read the listing behind a token, which must exist
+    /// and not be archived
+    fn get_token_listing(env: &Env, token_id: &String) -> PropertyListing {
+        if !Self::is_tokenized(env.clone(), token_id.clone()) {
+            panic!("Token not found");
+        }
+        Self::get_existing_listing(env, token_id)
+    }
+
+    /// Internal helper to hand a tokenized listing to a new owner. Like an
+    /// accepted transfer it drops the delegates, and the previous owner's
+    /// revenue share moves to the new owner.
+    fn transfer_token(env: &Env, mut listing: PropertyListing, to: Address) {
+        let id = listing.id.clone();
+        let from = listing.owner.clone();
+        if from == to {
+            panic!("Cannot transfer to the current owner");
+        }
+        listing.owner = to.clone();
+
+        let key = (LISTINGS.clone(), id.clone());
+        env.storage().persistent().set(&key, &listing);
+        env.storage().persistent().remove(&(DELEGATES, id.clone()));
+        env.storage()
+            .persistent()
+            .remove(&(TOKEN_APPROVAL, id.clone()));
+        Self::remove_from_owner_index(env, &from, &id);
+        Self::add_to_owner_index(env, &to, &id);
+        Self::move_revenue_share(env, &id, &from, &to);
+        Self::extend_persistent_ttl(env, &key);
+        Self::extend_instance_ttl(env);
+
+        env.events()
+            .publish((symbol_short!("transfer"), id, from, to), ());
+    }
+
+    /// Internal helper to give the revenue share of one beneficiary to
+    /// another, merging it into any share the new beneficiary already holds
+    fn move_revenue_share(env: &Env, id: &String, from: &Address, to: &Address) {
+        let key = (REVENUE_SHARES, id.clone());
+        let shares: Vec<RevenueShare> = match env.storage().persistent().get(&key) {
+            Some(shares) => shares,
+            None => return,
+        };
+        let moved = match shares.iter().find(|share| share.beneficiary == *from) {
+            Some(share) => share.bps,
+            None => return,
+        };
+
+        let mut updated = vec![env];
+        for share in shares.iter() {
+            if share.beneficiary == *from {
+                if !shares.iter().any(|other| other.beneficiary == *to) {
+                    updated.push_back(RevenueShare {
+                        beneficiary: to.clone(),
+                        bps: moved,
+                    });
+                }
+            } else if share.beneficiary == *to {
+                updated.push_back(RevenueShare {
+                    beneficiary: to.clone(),
+                    bps: share.bps + moved,
+                });
+            } else {
+                updated.push_back(share);
+            }
+        }
+        env.storage().persistent().set(&key, &updated);
+        Self::extend_persistent_ttl(env, &key);
+    }
+
+    /// Internal helper to lock the configured bond for a new listing
+    fn lock_bond(env: &Env, id: &String, owner: &Address) {
+        let config = match Self::get_bond_config(env.clone()) {
+            Some(config) => config,
+            None => return,
+        };
+
+        token::Client::new(env, &config.token).transfer(
+            owner,
+            &env.current_contract_address(),
+            &config.amount,
+        );
+
+        let key = (BOND, id.clone());
+        env.storage().persistent().set(
+            &key,
+            &ListingBond {
+                token: config.token,
+                amount: config.amount,
+                cooldown: config.cooldown,
+                unlock_at: None,
+            },
+        );
+        Self::extend_persistent_ttl(env, &key);
+
+        env.events().publish(
+            (symbol_short!("bond_lock"), id.clone(), owner.clone()),
+            config.amount,
+        );
+    }
+
+    /// Internal helper to start the cooldown of the bond of a removed
+    /// listing. The cooldown of an archived listing keeps running when it
+    /// is deleted.
+    fn start_bond_cooldown(env: &Env, id: &String) {
+        let key = (BOND, id.clone());
+        if let Some(mut bond) = Self::get_bond(env.clone(), id.clone()) {
+            if bond.unlock_at.is_none() {
+                bond.unlock_at = Some(env.ledger().timestamp().saturating_add(bond.cooldown));
+                env.storage().persistent().set(&key, &bond);
+                Self::extend_persistent_ttl(env, &key);
+            }
+        }
+    }
+
+    /// Internal helper to refuse removing a listing that guests still rely on
+    fn require_removable(env: &Env, listing: &PropertyListing) {
+        if listing.status == PropertyStatus::Booked {
+            panic_with_error!(env, PropertyListingError::InvalidTransition);
+        }
+        if let Some(booking_contract) = Self::get_booking_contract(env.clone()) {
+            if BookingClient::new(env, &booking_contract).has_active_bookings(&listing.id) {
+                panic!("Property listing has active bookings");
+            }
+        }
+    }
+
+    /// Internal helper to take a listing off the listing, owner, location
+    /// and tag indexes
+    fn remove_from_indexes(env: &Env, listing: &PropertyListing) {
+        Self::remove_from_index(
+            env,
+            LIST_INDEX.to_val(),
+            LIST_COUNT.to_val(),
+            LIST_POS,
+            &listing.id,
+        );
+        Self::remove_from_owner_index(env, &listing.owner, &listing.id);
+        let metadata = Self::get_metadata(env.clone(), listing.id.clone());
+        if let Some(geohash) = Self::searchable_geohash(metadata.as_ref()) {
+            Self::update_geo_index(env, &listing.id, &geohash, false);
+        }
+        let tags = Self::get_listing_tags(env.clone(), listing.id.clone());
+        Self::update_tag_index(env, &listing.id, &tags, false);
+    }
+
+    /// Internal helper to record a removed listing. The owner and time of an
+    /// earlier archive are kept when an archived listing is deleted.
+    fn set_tombstone(env: &Env, id: &String, owner: &Address, deleted: bool) {
+        let key = (TOMBSTONE, id.clone());
+        let tombstone = match Self::get_tombstone(env.clone(), id.clone()) {
+            Some(tombstone) => ListingTombstone {
+                deleted,
+                ..tombstone
+            },
+            None => ListingTombstone {
+                owner: owner.clone(),
+                deleted,
+                timestamp: env.ledger().timestamp(),
+            },
+        };
+        env.storage().persistent().set(&key, &tombstone);
+        Self::extend_persistent_ttl(env, &key);
+    }
+
+    /// Internal helper implementing the status transition table for owners
+    /// and delegates
+    fn is_valid_transition(from: PropertyStatus, to: PropertyStatus) -> bool {
+        matches!(
+            (from, to),
+            (PropertyStatus::Available, PropertyStatus::Maintenance)
+                | (PropertyStatus::Available, PropertyStatus::Inactive)
+                | (PropertyStatus::Maintenance, PropertyStatus::Available)
+                | (PropertyStatus::Maintenance, PropertyStatus::Inactive)
+                | (PropertyStatus::Inactive, PropertyStatus::Available)
+                | (PropertyStatus::Inactive, PropertyStatus::Maintenance)
+        )
+    }
+
+    /// Internal helper to announce the new status of a listing
+    fn publish_status_change(env: &Env, listing: &PropertyListing) {
+        env.events().publish(
+            (
+                Symbol::new(env, "property_status"),
+                listing.id.clone(),
+                listing.owner.clone(),
+            ),
+            listing.status,
+        );
+    }
+
+    /// Internal helper to append a content version
+    fn append_version(env: &Env, id: &String, data_hash: &BytesN<32>, updated_by: &Address) {
+        let version = Self::get_listing_version(env.clone(), id.clone()) + 1;
+        let key = (LIST_VERSION, id.clone(), version);
+        let count_key = (LIST_VERSION_COUNT, id.clone());
+
+        env.storage().persistent().set(
+            &key,
+            &ListingVersion {
+                version,
+                data_hash: data_hash.clone(),
+                updated_by: updated_by.clone(),
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+        env.storage().persistent().set(&count_key, &version);
+        Self::extend_persistent_ttl(env, &key);
+        Self::extend_persistent_ttl(env, &count_key);
+    }
+
+    /// Internal helper to append an entry to the status history
+    fn record_status_change(
+        env: &Env,
+        id: &String,
+        from: PropertyStatus,
+        to: PropertyStatus,
+        changed_by: &Address,
+    ) {
+        let key = (STATUS_HISTORY, id.clone());
+        let mut history = Self::get_status_history(env.clone(), id.clone());
+        if history.len() >= MAX_STATUS_HISTORY {
+            history.pop_front();
+        }
+        history.push_back(StatusChange {
+            from,
+            to,
+            changed_by: changed_by.clone(),
+            timestamp: env.ledger().timestamp(),
+        });
+
+        env.storage().persistent().set(&key, &history);
+        Self::extend_persistent_ttl(env, &key);
+    }
+
+    /// Internal helper to check the permissions of a caller on a listing
+    fn check_permission(
+        env: &Env,
+        listing: &PropertyListing,
+        caller: &Address,
+        permission: u32,
+    ) -> bool {
+        if *caller == listing.owner {
+            return true;
+        }
+
+        let delegates = Self::get_delegates(env.clone(), listing.id.clone());
+        match delegates.get(caller.clone()) {
+            Some(delegate) => delegate.permissions & permission == permission,
+            None => false,
+        }
+    }
+
+    /// Internal helper to check metadata for sane bounds
+    fn validate_metadata(metadata: &ListingMetadata) {
+        if metadata.bedrooms > MAX_ROOMS || metadata.bathrooms > MAX_ROOMS {
+            panic!("Invalid metadata: too many rooms");
+        }
+        if metadata.max_guests == 0 || metadata.max_guests > MAX_GUESTS {
+            panic!("Invalid metadata: max guests out of range");
+        }
+        if metadata.amenities & !AMENITY_ALL != 0 {
+            panic!("Invalid metadata: unknown amenities");
+        }
+        if metadata.country_code.len() != 2
+            || !Self::has_only_chars(&metadata.country_code, |c| c.is_ascii_uppercase())
+        {
+            panic!("Invalid metadata: country code");
+        }
+        if metadata.region_code.len() > MAX_REGION_CODE_LEN
+            || !Self::has_only_chars(&metadata.region_code, |c| {
+                c.is_ascii_uppercase() || c.is_ascii_digit()
+            })
+        {
+            panic!("Invalid metadata: region code");
+        }
+        if metadata.geohash.len() > MAX_GEOHASH_LEN
+            || !Self::has_only_chars(&metadata.geohash, Self::is_geohash_char)
+        {
+            panic!("Invalid metadata: geohash");
+        }
+    }
+
+    /// Internal helper to check that revenue shares split the whole proceeds
+    fn validate_revenue_shares(shares: &Vec<RevenueShare>) {
+        if shares.len() > MAX_BENEFICIARIES {
+            panic!("Invalid revenue shares: too many beneficiaries");
+        }
+        let mut total = 0u32;
+        for (i, share) in shares.iter().enumerate() {
+            if share.bps == 0 || share.bps > TOTAL_SHARE_BPS {
+                panic!("Invalid revenue shares: share out of range");
+            }
+            if shares
+                .iter()
+                .skip(i + 1)
+                .any(|other| other.beneficiary == share.beneficiary)
+            {
+                panic!("Invalid revenue shares: duplicate beneficiary");
+            }
+            total += share.bps;
+        }
+        if total != TOTAL_SHARE_BPS {
+            panic!("Invalid revenue shares: shares must sum to 10000 bps");
+        }
+    }
+
+    /// Internal helper to check compliance fields for sane bounds
+    fn validate_compliance(compliance: &ListingCompliance) {
+        if compliance.jurisdiction.len() < 2
+            || compliance.jurisdiction.len() > MAX_JURISDICTION_LEN
+            || !Self::has_only_chars(&compliance.jurisdiction, |c| {
+                c.is_ascii_uppercase() || c.is_ascii_digit() || c == b'-'
+            })
+        {
+            panic!("Invalid compliance: jurisdiction");
+        }
+        if compliance.license_id.is_empty()
+            || compliance.license_id.len() > MAX_LICENSE_ID_LEN
+            || !Self::has_only_chars(&compliance.license_id, |c| {
+                c.is_ascii_graphic() || c == b' '
+            })
+        {
+            panic!("Invalid compliance: license id");
+        }
+        if let Some(cap) = compliance.annual_night_cap {
+            if cap == 0 || cap > MAX_ANNUAL_NIGHT_CAP {
+                panic!("Invalid compliance: annual night cap out of range");
+            }
+        }
+    }
+
+    /// Internal helper returning the geohash under which a listing is
+    /// indexed, if any
+    fn searchable_geohash(metadata: Option<&ListingMetadata>) -> Option<String> {
+        metadata
+            .filter(|metadata| metadata.geohash.len() >= GEOHASH_MIN_PRECISION)
+            .map(|metadata| metadata.geohash.clone())
+    }
+
+    /// Internal helper returning the scopes of the location buckets of a
+    /// geohash
+    fn geo_scopes(env: &Env, geohash: &String) -> Vec<Val> {
+        let mut scopes = vec![env];
+        for len in GEOHASH_MIN_PRECISION..=geohash.len().min(GEOHASH_MAX_PRECISION) {
+            let prefix = Self::geohash_prefix(env, geohash, len);
+            scopes.push_back((GEO_INDEX, prefix).into_val(env));
+        }
+        scopes
+    }
+
+    /// Internal helper to list a listing in or unlist it from the location
+    /// buckets of a geohash
+    fn update_geo_index(env: &Env, id: &String, geohash: &String, listed: bool) {
+        for scope in Self::geo_scopes(env, geohash).iter() {
+            Self::set_index_slot(env, scope, id, listed);
+        }
+    }
+
+    /// Internal helper to list a listing in or unlist it from the index of
+    /// each of the given tags
+    fn update_tag_index(env: &Env, id: &String, tags: &Vec<Symbol>, listed: bool) {
+        for tag in tags.iter() {
+            Self::set_index_slot(env, (TAG_INDEX, tag).into_val(env), id, listed);
+        }
+    }
+
+    /// Internal helper to cut a geohash down to at most `len` characters
+    fn geohash_prefix(env: &Env, geohash: &String, len: u32) -> String {
+        let mut buf = [0u8; MAX_GEOHASH_LEN as usize];
+        let full_len = geohash.len() as usize;
+        geohash.copy_into_slice(&mut buf[..full_len]);
+        String::from_bytes(env, &buf[..full_len.min(len as usize)])
+    }
+
+    /// Internal helper to check every byte of a short string. Strings longer
+    /// than the buffer are rejected.
+    fn has_only_chars(value: &String, allowed: impl Fn(u8) -> bool) -> bool {
+        let mut buf = [0u8; MAX_ID_LEN as usize];
+        let len = value.len() as usize;
+        if len > buf.len() {
+            return false;
+        }
+        value.copy_into_slice(&mut buf[..len]);
+        buf[..len].iter().all(|c| allowed(*c))
+    }
+
+    /// Internal helper matching the geohash base32 alphabet
+    fn is_geohash_char(c: u8) -> bool {
+        (c.is_ascii_digit() || c.is_ascii_lowercase()) && !matches!(c, b'a' | b'i' | b'l' | b'o')
+    }
+
+    /// Internal helper to validate and cap a page size
+    fn validate_limit(limit: u32) -> u32 {
+        if limit == 0 {
+            panic!("Invalid limit: must be greater than zero");
+        }
+        limit.min(MAX_PAGE_SIZE)
+    }
+
+    /// Internal helper to append a listing id to a dense index, keeping its
+    /// position under (pos, id)
+    fn append_to_index(env: &Env, scope: Val, count_key: Val, pos: Symbol, id: &String) {
+        let count = Self::get_slot_count(env, count_key);
+        let shard_number = count / INDEX_SHARD_SIZE;
+        let shard_key = (scope, shard_number);
+
+        let mut shard = Self::get_index_shard(env, scope, shard_number);
+        shard.push_back(id.clone());
+        env.storage().persistent().set(&shard_key, &shard);
+
+        let pos_key = (pos, id.clone());
+        env.storage().persistent().set(&pos_key, &count);
+        env.storage().persistent().set(&count_key, &(count + 1));
+
+        Self::extend_persistent_ttl(env, &shard_key);
+        Self::extend_persistent_ttl(env, &pos_key);
+        Self::extend_persistent_ttl(env, &count_key);
+    }
+
+    /// Internal helper to remove a listing id from a dense index. The last id
+    /// is moved into the freed position so the index stays dense.
+    fn remove_from_index(env: &Env, scope: Val, count_key: Val, pos: Symbol, id: &String) {
+        let pos_key = (pos.clone(), id.clone());
+        let position: u32 = match env.storage().persistent().get(&pos_key) {
+            Some(position) => position,
+            None => return,
+        };
+        let last = Self::get_slot_count(env, count_key) - 1;
+
+        let last_shard_number = last / INDEX_SHARD_SIZE;
+        let last_shard_key = (scope, last_shard_number);
+        let mut last_shard = Self::get_index_shard(env, scope, last_shard_number);
+        let last_id = last_shard.pop_back().unwrap();
+
+        if position != last {
+            let shard_number = position / INDEX_SHARD_SIZE;
+            let shard_key = (scope, shard_number);
+            if shard_number == last_shard_number {
+                last_shard.set(position % INDEX_SHARD_SIZE, last_id.clone());
+            } else {
+                let mut shard = Self::get_index_shard(env, scope, shard_number);
+                shard.set(position % INDEX_SHARD_SIZE, last_id.clone());
+                env.storage().persistent().set(&shard_key, &shard);
+                Self::extend_persistent_ttl(env, &shard_key);
+            }
+
+            let last_pos_key = (pos, last_id);
+            env.storage().persistent().set(&last_pos_key, &position);
+            Self::extend_persistent_ttl(env, &last_pos_key);
+        }
+
+        if last_shard.is_empty() {
+            env.storage().persistent().remove(&last_shard_key);
+        } else {
+            env.storage().persistent().set(&last_shard_key, &last_shard);
+            Self::extend_persistent_ttl(env, &last_shard_key);
+        }
+        env.storage().persistent().remove(&pos_key);
+        env.storage().persistent().set(&count_key, &last);
+        Self::extend_persistent_ttl(env, &count_key);
+    }
+
+    /// Internal helper to add a listing id to the index of its owner
+    fn add_to_owner_index(env: &Env, owner: &Address, id: &String) {
+        let scope = (OWNER_INDEX, owner.clone()).into_val(env);
+        Self::append_to_index(env, scope, scope, OWNER_POS, id);
+    }
+
+    /// Internal helper to remove a listing id from the index of its owner
+    fn remove_from_owner_index(env: &Env, owner: &Address, id: &String) {
+        let scope = (OWNER_INDEX, owner.clone()).into_val(env);
+        Self::remove_from_index(env, scope, scope, OWNER_POS, id);
+    }
+
+    /// Internal helper to read a page of the listings in a dense index
+    fn get_index_page(env: &Env, scope: Val, count: u32, cursor: u32, limit: u32) -> ListingPage {
+        let limit = Self::validate_limit(limit);
+
+        let mut listings = vec![env];
+        let mut shard: Vec<String> = vec![env];
+        let mut shard_number = u32::MAX;
+        let mut i = cursor;
+        while i < count && listings.len() < limit {
+            if i / INDEX_SHARD_SIZE != shard_number {
+                shard_number = i / INDEX_SHARD_SIZE;
+                shard = Self::get_index_shard(env, scope, shard_number);
+            }
+
+            let id = shard.get(i % INDEX_SHARD_SIZE).unwrap();
+            if let Some(listing) = Self::get_listing(env.clone(), id) {
+                listings.push_back(listing);
+            }
+            i += 1;
+        }
+
+        let next_cursor = if i < count { Some(i) } else { None };
+
+        ListingPage {
+            listings,
+            next_cursor,
+        }
+    }
+
+    /// Internal helper to list a listing in or unlist it from a sparse index,
+    /// giving it the next slot if it has none yet
+    fn set_index_slot(env: &Env, scope: Val, id: &String, listed: bool) {
+        let pos_key = (scope, id.clone());
+        let position = match env.storage().persistent().get(&pos_key) {
+            Some(position) => position,
+            None => {
+                let count = Self::get_slot_count(env, scope);
+                env.storage().persistent().set(&pos_key, &count);
+                env.storage().persistent().set(&scope, &(count + 1));
+                Self::extend_persistent_ttl(env, &scope);
+                count
+            }
+        };
+        Self::extend_persistent_ttl(env, &pos_key);
+
+        let shard_number = position / INDEX_SHARD_SIZE;
+        let slot = position % INDEX_SHARD_SIZE;
+        let mut shard = Self::get_index_shard(env, scope, shard_number);
+        let value = if listed {
+            id.clone()
+        } else {
+            String::from_str(env, "")
+        };
+        if shard.get(slot).as_ref() == Some(&value) {
+            return;
+        }
+        if slot < shard.len() {
+            shard.set(slot, value);
+        } else {
+            shard.push_back(value);
+        }
+        let shard_key = (scope, shard_number);
+        env.storage().persistent().set(&shard_key, &shard);
+        Self::extend_persistent_ttl(env, &shard_key);
+    }
+
+    /// Internal helper to keep the slot of a listing in a sparse index alive
+    fn extend_index_slot(env: &Env, scope: Val, id: &String) {
+        let pos_key = (scope, id.clone());
+        if let Some(position) = env.storage().persistent().get::<_, u32>(&pos_key) {
+            Self::extend_persistent_ttl(env, &pos_key);
+            Self::extend_persistent_ttl(env, &(scope, position / INDEX_SHARD_SIZE));
+        }
+    }
+
+    /// Internal helper to read the number of slots of a sparse index
+    fn get_slot_count(env: &Env, scope: Val) -> u32 {
+        match env.storage().persistent().get(&scope) {
+            Some(count) => {
+                Self::extend_persistent_ttl(env, &scope);
+                count
+            }
+            None => 0,
+        }
+    }
+
+    /// Internal helper to read a page of the ids listed in a sparse index
+    /// that pass a filter. Empty slots are skipped.
+    fn get_sparse_page(
+        env: &Env,
+        scope: Val,
+        cursor: u32,
+        limit: u32,
+        filter: impl Fn(&String) -> bool,
+    ) -> ListingIdPage {
+        let count = Self::get_slot_count(env, scope);
+        let mut ids = vec![env];
+        let mut shard = vec![env];
+        let mut shard_number = u32::MAX;
+        let mut i = cursor;
+        while i < count && ids.len() < limit {
+            if i / INDEX_SHARD_SIZE != shard_number {
+                shard_number = i / INDEX_SHARD_SIZE;
+                shard = Self::get_index_shard(env, scope, shard_number);
+            }
+            let id = shard.get(i % INDEX_SHARD_SIZE).unwrap();
+            if !id.is_empty() && filter(&id) {
+                ids.push_back(id);
+            }
+            i += 1;
+        }
+
+        let next_cursor = if i < count { Some(i) } else { None };
+
+        ListingIdPage { ids, next_cursor }
+    }
+
+    /// Internal helper to read a shard of the listing index or of a sparse
+    /// index
+    fn get_index_shard(env: &Env, scope: Val, shard_number: u32) -> Vec<String> {
+        let key = (scope, shard_number);
+        match env.storage().persistent().get(&key) {
+            Some(shard) => {
+                Self::extend_persistent_ttl(env, &key);
+                shard
+            }
+            None => vec![env],
+        }
+    }
+
+    /// Internal helper to read the admin
+    fn get_admin(env: &Env) -> Address {
+        env.storage()
+            .instance()
+            .get(&ADMIN)
+            .unwrap_or_else(|| panic!("Contract not initialized"))
+    }
+
+    /// Internal helper to keep the contract instance alive
+    fn extend_instance_ttl(env: &Env) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+    }
+
+    /// Internal helper to keep a persistent entry alive
+    fn extend_persistent_ttl<K>(env: &Env, key: &K)
+    where
+        K: IntoVal<Env, Val>,
+    {
+        env.storage().persistent().extend_ttl(
+            key,
+            PERSISTENT_LIFETIME_THRESHOLD,
+            PERSISTENT_BUMP_AMOUNT,
+        );
+    }
+
+    /// Internal helper to keep a persistent entry alive if it exists
+    fn extend_persistent_ttl_if_present<K>(env: &Env, key: &K)
+    where
+        K: IntoVal<Env, Val>,
+    {
+        if env.storage().persistent().has(key) {
+            Self::extend_persistent_ttl(env, key);
+        }
     }
 }
 
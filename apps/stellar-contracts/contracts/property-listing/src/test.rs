@@ -2,18 +2,125 @@
 
 use super::*;
 
-use soroban_sdk::{testutils::Address as _, Address, Env, String};
+use soroban_sdk::{
+    testutils::{
+        storage::{Instance as _, Persistent as _},
+        Address as _, Events, Ledger,
+    },
+    vec, Address, BytesN, Env, String,
+};
 
+// Release builds of the contract at each schema version, used by the upgrade tests
+mod property_listing_v1 {
+    soroban_sdk::contractimport!(file = "fixtures/property_listing_v1.wasm");
+}
+
+mod property_listing_v2 {
+    soroban_sdk::contractimport!(file = "fixtures/property_listing_v2.wasm");
+}
+
+mod property_listing_v3 {
+    soroban_sdk::contractimport!(file = "fixtures/property_listing_v3.wasm");
+}
+
+mod property_listing_v4 {
+    soroban_sdk::contractimport!(file = "fixtures/property_listing_v4.wasm");
+}
+
+mod property_listing_v5 {
+    soroban_sdk::contractimport!(file = "fixtures/property_listing_v5.wasm");
+}
+
+mod property_listing_v6 {
+    soroban_sdk::contractimport!(file = "fixtures/property_listing_v6.wasm");
+}
+
+mod property_listing_v7 {
+    soroban_sdk::contractimport!(file = "fixtures/property_listing_v7.wasm");
+}
+
+// Rewrite a listing and its content versions in the layout used before
+// schema version 6, with hex encoded data hashes. Must run as the contract.
+fn store_legacy_listing(env: &Env, id: &String) {
+    let key = (LISTINGS, id.clone());
+    let listing: PropertyListing = env.storage().persistent().get(&key).unwrap();
+    env.storage().persistent().set(
+        &key,
+        &LegacyPropertyListing {
+            id: listing.id,
+            data_hash: to_hex(env, &listing.data_hash),
+            owner: listing.owner,
+            status: listing.status,
+        },
+    );
+
+    let count: u32 = env
+        .storage()
+        .persistent()
+        .get(&(LIST_VERSION_COUNT, id.clone()))
+        .unwrap_or(0);
+    for version in 1..=count {
+        let version_key = (LIST_VERSION, id.clone(), version);
+        let entry: ListingVersion = env.storage().persistent().get(&version_key).unwrap();
+        env.storage().persistent().set(
+            &version_key,
+            &LegacyListingVersion {
+                version: entry.version,
+                data_hash: to_hex(env, &entry.data_hash),
+                updated_by: entry.updated_by,
+                timestamp: entry.timestamp,
+            },
+        );
+    }
+}
+
+fn to_hex(env: &Env, hash: &BytesN<32>) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut hex = [0u8; 64];
+    for (i, byte) in hash.to_array().iter().enumerate() {
+        hex[2 * i] = DIGITS[(byte >> 4) as usize];
+        hex[2 * i + 1] = DIGITS[(byte & 0xf) as usize];
+    }
+    String::from_bytes(env, &hex)
+}
+
+// Register a token and mint `amount` to `holder`
+fn create_token(env: &Env, holder: &Address, amount: i128) -> token::Client<'static> {
+    let token_admin = Address::generate(env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(token_admin)
+        .address();
+    token::StellarAssetClient::new(env, &token_id).mint(holder, &amount);
+    token::Client::new(env, &token_id)
+}
+
+// Stand-in for the booking contract that reports active bookings per property
+#[contract]
+pub struct MockBookingContract;
+
+#[contractimpl]
+impl MockBookingContract {
+    pub fn set_active_bookings(env: Env, property_id: String, active: bool) {
+        env.storage().persistent().set(&property_id, &active);
+    }
+
+    pub fn has_active_bookings(env: Env, property_id: String) -> bool {
+        env.storage()
+            .persistent()
+            .get(&property_id)
+            .unwrap_or(false)
+    }
+}
 
 #[test]
 fn test_create_listing() {
     let env = Env::default();
-    let contract_id = env.register(PropertyListingContract, ());
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
     let client = PropertyListingContractClient::new(&env, &contract_id);
 
     let owner = Address::generate(&env);
     let id = String::from_str(&env, "PROP1");
-    let data_hash = String::from_str(&env, "hash123");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
 
     env.mock_all_auths();
 
@@ -26,15 +133,15 @@ fn test_create_listing() {
 }
 
 #[test]
-#[should_panic(expected = "Property listing already exists")]
+#[should_panic(expected = "Error(Contract, #2)")]
 fn test_create_duplicate_listing() {
     let env = Env::default();
-    let contract_id = env.register(PropertyListingContract, ());
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
     let client = PropertyListingContractClient::new(&env, &contract_id);
 
     let owner = Address::generate(&env);
     let id = String::from_str(&env, "PROP1");
-    let data_hash = String::from_str(&env, "hash123");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
 
     env.mock_all_auths();
 
@@ -48,13 +155,13 @@ fn test_create_duplicate_listing() {
 #[test]
 fn test_update_listing() {
     let env = Env::default();
-    let contract_id = env.register(PropertyListingContract, ());
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
     let client = PropertyListingContractClient::new(&env, &contract_id);
 
     let owner = Address::generate(&env);
     let id = String::from_str(&env, "PROP1");
-    let data_hash = String::from_str(&env, "hash123");
-    let new_hash = String::from_str(&env, "newhash456");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    let new_hash = BytesN::from_array(&env, &[3; 32]);
 
     env.mock_all_auths();
 
@@ -74,14 +181,14 @@ fn test_update_listing() {
 #[should_panic(expected = "Only the owner can update the listing")]
 fn test_update_listing_unauthorized() {
     let env = Env::default();
-    let contract_id = env.register(PropertyListingContract, ());
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
     let client = PropertyListingContractClient::new(&env, &contract_id);
 
     let owner = Address::generate(&env);
     let unauthorized = Address::generate(&env);
     let id = String::from_str(&env, "PROP1");
-    let data_hash = String::from_str(&env, "hash123");
-    let new_hash = String::from_str(&env, "newhash456");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    let new_hash = BytesN::from_array(&env, &[3; 32]);
 
     env.mock_all_auths();
 
@@ -95,12 +202,12 @@ fn test_update_listing_unauthorized() {
 #[test]
 fn test_update_status() {
     let env = Env::default();
-    let contract_id = env.register(PropertyListingContract, ());
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
     let client = PropertyListingContractClient::new(&env, &contract_id);
 
     let owner = Address::generate(&env);
     let id = String::from_str(&env, "PROP1");
-    let data_hash = String::from_str(&env, "hash123");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
 
     env.mock_all_auths();
 
@@ -108,25 +215,25 @@ fn test_update_status() {
     client.create_listing(&id, &data_hash, &owner);
 
     // Update status
-    let updated_listing = client.update_status(&id, &owner, &PropertyStatus::Booked);
+    let updated_listing = client.update_status(&id, &owner, &PropertyStatus::Maintenance);
     
     assert_eq!(updated_listing.id, id);
     assert_eq!(updated_listing.data_hash, data_hash);
     assert_eq!(updated_listing.owner, owner);
-    assert_eq!(updated_listing.status, PropertyStatus::Booked);
+    assert_eq!(updated_listing.status, PropertyStatus::Maintenance);
 }
 
 #[test]
 #[should_panic(expected = "Only the owner can update the status")]
 fn test_update_status_unauthorized() {
     let env = Env::default();
-    let contract_id = env.register(PropertyListingContract, ());
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
     let client = PropertyListingContractClient::new(&env, &contract_id);
 
     let owner = Address::generate(&env);
     let unauthorized = Address::generate(&env);
     let id = String::from_str(&env, "PROP1");
-    let data_hash = String::from_str(&env, "hash123");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
 
     env.mock_all_auths();
 
@@ -134,18 +241,18 @@ fn test_update_status_unauthorized() {
     client.create_listing(&id, &data_hash, &owner);
 
     // Try to update status with unauthorized user - should panic
-    client.update_status(&id, &unauthorized, &PropertyStatus::Booked);
+    client.update_status(&id, &unauthorized, &PropertyStatus::Maintenance);
 }
 
 #[test]
 fn test_get_listing() {
     let env = Env::default();
-    let contract_id = env.register(PropertyListingContract, ());
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
     let client = PropertyListingContractClient::new(&env, &contract_id);
 
     let owner = Address::generate(&env);
     let id = String::from_str(&env, "PROP1");
-    let data_hash = String::from_str(&env, "hash123");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
 
     env.mock_all_auths();
 
@@ -166,7 +273,7 @@ fn test_get_listing() {
 #[test]
 fn test_get_nonexistent_listing() {
     let env = Env::default();
-    let contract_id = env.register(PropertyListingContract, ());
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
     let client = PropertyListingContractClient::new(&env, &contract_id);
 
     let id = String::from_str(&env, "NONEXISTENT");
@@ -177,7 +284,7 @@ fn test_get_nonexistent_listing() {
 #[test]
 fn test_multiple_listings() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, PropertyListingContract);
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
     let client = PropertyListingContractClient::new(&env, &contract_id);
 
     let owner1 = Address::generate(&env);
@@ -186,8 +293,8 @@ fn test_multiple_listings() {
     let id1 = String::from_str(&env, "PROP1");
     let id2 = String::from_str(&env, "PROP2");
     
-    let hash1 = String::from_str(&env, "hash123");
-    let hash2 = String::from_str(&env, "hash456");
+    let hash1 = BytesN::from_array(&env, &[1; 32]);
+    let hash2 = BytesN::from_array(&env, &[2; 32]);
 
     env.mock_all_auths();
 
@@ -219,17 +326,18 @@ fn test_multiple_listings() {
 // =========================
 // Objective: Ensure the contract is not vulnerable to reentrancy attacks.
 #[test]
-#[should_panic(expected = "Property listing already exists")]
+#[should_panic(expected = "Error(Contract, #2)")]
 fn test_reentrancy_attack_prevention() {
     // En Soroban, la reentrada directa no es posible debido a la arquitectura del runtime.
     // Simulamos un escenario donde una función externa podría intentar modificar el estado.
     let env = Env::default();
-    let contract_id = env.register(PropertyListingContract, ());
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
     let client = PropertyListingContractClient::new(&env, &contract_id);
     
     let owner = Address::generate(&env);
-    let id = symbol_short!("PROP1");
-    let data_hash = symbol_short!("HASH1");
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[7; 32]);
     
     // Crear listing de forma atómica
     let listing = client.create_listing(&id, &data_hash, &owner);
@@ -248,21 +356,22 @@ fn test_reentrancy_attack_prevention() {
 #[test]
 fn test_integer_overflow_underflow() {
     let env = Env::default();
-    let contract_id = env.register(PropertyListingContract, ());
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
     let client = PropertyListingContractClient::new(&env, &contract_id);
     
     let owner = Address::generate(&env);
     
     // Test con símbolos extremos (aunque Symbol tiene límites internos)
-    let id = symbol_short!("PROP_MAX");
-    let data_hash = symbol_short!("HASH_MAX");
+    let id = String::from_str(&env, "PROP_MAX");
+    let data_hash = BytesN::from_array(&env, &[11; 32]);
     
     let listing = client.create_listing(&id, &data_hash, &owner);
     assert_eq!(listing.id, id);
     assert_eq!(listing.data_hash, data_hash);
     
     // Verificar que el contrato maneja correctamente los símbolos
-    let retrieved_listing = client.get_listing(&id);
+    let retrieved_listing = client.get_listing(&id).unwrap();
     assert_eq!(retrieved_listing.owner, owner);
 }
 
@@ -274,16 +383,17 @@ fn test_integer_overflow_underflow() {
 fn test_timestamp_manipulation_resistance() {
     // PropertyListing no usa timestamps directamente, pero verificamos que no hay dependencias implícitas
     let env = Env::default();
+    env.mock_all_auths();
     env.ledger().with_mut(|li| {
         li.timestamp = u64::MAX; // Timestamp extremo
     });
     
-    let contract_id = env.register(PropertyListingContract, ());
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
     let client = PropertyListingContractClient::new(&env, &contract_id);
     
     let owner = Address::generate(&env);
-    let id = symbol_short!("PROP1");
-    let data_hash = symbol_short!("HASH1");
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[7; 32]);
     
     // El contrato debe funcionar independientemente del timestamp
     let listing = client.create_listing(&id, &data_hash, &owner);
@@ -298,14 +408,15 @@ fn test_timestamp_manipulation_resistance() {
 #[should_panic(expected = "Only the owner can update the listing")]
 fn test_unauthorized_access_attempts() {
     let env = Env::default();
-    let contract_id = env.register(PropertyListingContract, ());
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
     let client = PropertyListingContractClient::new(&env, &contract_id);
     
     let owner = Address::generate(&env);
     let unauthorized = Address::generate(&env);
-    let id = symbol_short!("PROP1");
-    let data_hash = symbol_short!("HASH1");
-    let new_data_hash = symbol_short!("HASH2");
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[7; 32]);
+    let new_data_hash = BytesN::from_array(&env, &[8; 32]);
     
     // Crear listing con owner
     client.create_listing(&id, &data_hash, &owner);
@@ -321,18 +432,19 @@ fn test_unauthorized_access_attempts() {
 #[test]
 fn test_invalid_state_transition_attempts() {
     let env = Env::default();
-    let contract_id = env.register(PropertyListingContract, ());
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
     let client = PropertyListingContractClient::new(&env, &contract_id);
     
     let owner = Address::generate(&env);
-    let id = symbol_short!("PROP1");
-    let data_hash = symbol_short!("HASH1");
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[7; 32]);
     
     client.create_listing(&id, &data_hash, &owner);
     
     // Test: Cambiar a estado válido
-    let updated_listing = client.update_status(&id, &owner, &PropertyStatus::Booked);
-    assert_eq!(updated_listing.status, PropertyStatus::Booked);
+    let updated_listing = client.update_status(&id, &owner, &PropertyStatus::Inactive);
+    assert_eq!(updated_listing.status, PropertyStatus::Inactive);
     
     // Test: Cambiar a otro estado válido
     let final_listing = client.update_status(&id, &owner, &PropertyStatus::Maintenance);
@@ -351,14 +463,15 @@ fn test_invalid_state_transition_attempts() {
 #[should_panic(expected = "Only the owner can update the listing")]
 fn test_economic_attack_simulation() {
     let env = Env::default();
-    let contract_id = env.register(PropertyListingContract, ());
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
     let client = PropertyListingContractClient::new(&env, &contract_id);
     
     let owner = Address::generate(&env);
     let attacker = Address::generate(&env);
-    let id = symbol_short!("PROP1");
-    let data_hash = symbol_short!("HASH1");
-    let malicious_hash = symbol_short!("MALICIOUS");
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[7; 32]);
+    let malicious_hash = BytesN::from_array(&env, &[9; 32]);
     
     // Crear listing legítimo
     client.create_listing(&id, &data_hash, &owner);
@@ -374,7 +487,8 @@ fn test_economic_attack_simulation() {
 #[test]
 fn test_property_fuzzing() {
     let env = Env::default();
-    let contract_id = env.register(PropertyListingContract, ());
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
     let client = PropertyListingContractClient::new(&env, &contract_id);
     
     let mut success_count = 0;
@@ -385,45 +499,45 @@ fn test_property_fuzzing() {
         
         // Crear símbolos únicos para cada iteración usando letras del alfabeto
         let id = match i {
-            0 => symbol_short!("PROP_A"),
-            1 => symbol_short!("PROP_B"),
-            2 => symbol_short!("PROP_C"),
-            3 => symbol_short!("PROP_D"),
-            4 => symbol_short!("PROP_E"),
-            5 => symbol_short!("PROP_F"),
-            6 => symbol_short!("PROP_G"),
-            7 => symbol_short!("PROP_H"),
-            8 => symbol_short!("PROP_I"),
-            9 => symbol_short!("PROP_J"),
-            10 => symbol_short!("PROP_K"),
-            11 => symbol_short!("PROP_L"),
-            12 => symbol_short!("PROP_M"),
-            13 => symbol_short!("PROP_N"),
-            14 => symbol_short!("PROP_O"),
-            15 => symbol_short!("PROP_P"),
-            16 => symbol_short!("PROP_Q"),
-            17 => symbol_short!("PROP_R"),
-            18 => symbol_short!("PROP_S"),
-            19 => symbol_short!("PROP_T"),
-            20 => symbol_short!("PROP_U"),
-            21 => symbol_short!("PROP_V"),
-            22 => symbol_short!("PROP_W"),
-            23 => symbol_short!("PROP_X"),
-            24 => symbol_short!("PROP_Y"),
-            _ => symbol_short!("PROP_Z"),
+            0 => String::from_str(&env, "PROP_A"),
+            1 => String::from_str(&env, "PROP_B"),
+            2 => String::from_str(&env, "PROP_C"),
+            3 => String::from_str(&env, "PROP_D"),
+            4 => String::from_str(&env, "PROP_E"),
+            5 => String::from_str(&env, "PROP_F"),
+            6 => String::from_str(&env, "PROP_G"),
+            7 => String::from_str(&env, "PROP_H"),
+            8 => String::from_str(&env, "PROP_I"),
+            9 => String::from_str(&env, "PROP_J"),
+            10 => String::from_str(&env, "PROP_K"),
+            11 => String::from_str(&env, "PROP_L"),
+            12 => String::from_str(&env, "PROP_M"),
+            13 => String::from_str(&env, "PROP_N"),
+            14 => String::from_str(&env, "PROP_O"),
+            15 => String::from_str(&env, "PROP_P"),
+            16 => String::from_str(&env, "PROP_Q"),
+            17 => String::from_str(&env, "PROP_R"),
+            18 => String::from_str(&env, "PROP_S"),
+            19 => String::from_str(&env, "PROP_T"),
+            20 => String::from_str(&env, "PROP_U"),
+            21 => String::from_str(&env, "PROP_V"),
+            22 => String::from_str(&env, "PROP_W"),
+            23 => String::from_str(&env, "PROP_X"),
+            24 => String::from_str(&env, "PROP_Y"),
+            _ => String::from_str(&env, "PROP_Z"),
         };
         
         let data_hash = match i % 10 {
-            0 => symbol_short!("HASH_1"),
-            1 => symbol_short!("HASH_2"),
-            2 => symbol_short!("HASH_3"),
-            3 => symbol_short!("HASH_4"),
-            4 => symbol_short!("HASH_5"),
-            5 => symbol_short!("HASH_6"),
-            6 => symbol_short!("HASH_7"),
-            7 => symbol_short!("HASH_8"),
-            8 => symbol_short!("HASH_9"),
-            _ => symbol_short!("HASH_0"),
+            0 => BytesN::from_array(&env, &[13; 32]),
+            1 => BytesN::from_array(&env, &[14; 32]),
+            2 => BytesN::from_array(&env, &[15; 32]),
+            3 => BytesN::from_array(&env, &[16; 32]),
+            4 => BytesN::from_array(&env, &[17; 32]),
+            5 => BytesN::from_array(&env, &[18; 32]),
+            6 => BytesN::from_array(&env, &[19; 32]),
+            7 => BytesN::from_array(&env, &[20; 32]),
+            8 => BytesN::from_array(&env, &[21; 32]),
+            _ => BytesN::from_array(&env, &[12; 32]),
         };
         
         // Crear listing con combinación única
@@ -450,30 +564,31 @@ fn test_property_fuzzing() {
 #[test]
 fn test_cross_contract_interaction() {
     let env = Env::default();
+    env.mock_all_auths();
     
     // Simular el flujo completo: Property Listing -> Booking -> Review
-    let property_contract_id = env.register(PropertyListingContract, ());
+    let property_contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
     let property_client = PropertyListingContractClient::new(&env, &property_contract_id);
     
     let owner = Address::generate(&env);
-    let id = symbol_short!("PROP1");
-    let data_hash = symbol_short!("HASH1");
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[7; 32]);
     
     // Paso 1: Crear property listing
     let listing = property_client.create_listing(&id, &data_hash, &owner);
     assert_eq!(listing.status, PropertyStatus::Available);
     
-    // Paso 2: Simular booking (mock)
-    // En un escenario real, el contrato de booking actualizaría el estado de la propiedad
-    let updated_listing = property_client.update_status(&id, &owner, &PropertyStatus::Booked);
-    assert_eq!(updated_listing.status, PropertyStatus::Booked);
+    // Paso 2: Simular mantenimiento
+    // Solo el contrato de booking puede marcar la propiedad como Booked
+    let updated_listing = property_client.update_status(&id, &owner, &PropertyStatus::Maintenance);
+    assert_eq!(updated_listing.status, PropertyStatus::Maintenance);
     
     // Paso 3: Simular finalización y volver a disponible
     let final_listing = property_client.update_status(&id, &owner, &PropertyStatus::Available);
     assert_eq!(final_listing.status, PropertyStatus::Available);
     
     // Verificar que el estado final es consistente
-    let retrieved_listing = property_client.get_listing(&id);
+    let retrieved_listing = property_client.get_listing(&id).unwrap();
     assert_eq!(retrieved_listing.status, PropertyStatus::Available);
     assert_eq!(retrieved_listing.owner, owner);
 }
@@ -485,32 +600,31 @@ fn test_cross_contract_interaction() {
 #[test]
 fn test_gas_optimization_validation() {
     let env = Env::default();
-    let contract_id = env.register(PropertyListingContract, ());
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
     let client = PropertyListingContractClient::new(&env, &contract_id);
     
     let owner = Address::generate(&env);
-    let id = symbol_short!("PROP1");
-    let data_hash = symbol_short!("HASH1");
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[7; 32]);
     
     // Operación 1: Crear listing
     let listing = client.create_listing(&id, &data_hash, &owner);
     assert_eq!(listing.status, PropertyStatus::Available);
     
     // Operación 2: Actualizar listing
-    let new_data_hash = symbol_short!("HASH2");
+    let new_data_hash = BytesN::from_array(&env, &[8; 32]);
     let updated_listing = client.update_listing(&id, &new_data_hash, &owner);
     assert_eq!(updated_listing.data_hash, new_data_hash);
     
     // Operación 3: Cambiar estado
-    let status_listing = client.update_status(&id, &owner, &PropertyStatus::Booked);
-    assert_eq!(status_listing.status, PropertyStatus::Booked);
+    let status_listing = client.update_status(&id, &owner, &PropertyStatus::Maintenance);
+    assert_eq!(status_listing.status, PropertyStatus::Maintenance);
     
     // Operación 4: Obtener listing
-    let retrieved_listing = client.get_listing(&id);
+    let retrieved_listing = client.get_listing(&id).unwrap();
     assert_eq!(retrieved_listing.owner, owner);
     
-    // Nota: Soroban aún no expone métricas detalladas de gas en el entorno de test
-    assert!(true, "Validación de gas completada - monitorear en producción");
 }
 
 // =========================
@@ -520,23 +634,22 @@ fn test_gas_optimization_validation() {
 #[test]
 fn test_deployment_validation_networks() {
     let env = Env::default();
-    let contract_id = env.register(PropertyListingContract, ());
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
     let client = PropertyListingContractClient::new(&env, &contract_id);
     
     // Test básico de funcionalidad (simula despliegue en cualquier red)
     let owner = Address::generate(&env);
-    let id = symbol_short!("PROP1");
-    let data_hash = symbol_short!("HASH1");
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[7; 32]);
     
     let listing = client.create_listing(&id, &data_hash, &owner);
     assert_eq!(listing.status, PropertyStatus::Available);
     
     // Verificar que las operaciones básicas funcionan
-    let retrieved_listing = client.get_listing(&id);
+    let retrieved_listing = client.get_listing(&id).unwrap();
     assert_eq!(retrieved_listing.owner, owner);
     
-    // Nota: Para tests reales de despliegue, se necesitan scripts de CI/CD
-    assert!(true, "Validación de despliegue completada - usar CI/CD para tests reales");
 }
 
 // =========================
@@ -545,29 +658,4375 @@ fn test_deployment_validation_networks() {
 #[test]
 fn test_edge_cases() {
     let env = Env::default();
-    let contract_id = env.register(PropertyListingContract, ());
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
     let client = PropertyListingContractClient::new(&env, &contract_id);
     
     let owner = Address::generate(&env);
     
     // Test: Símbolos con caracteres especiales (dentro del límite de 9 caracteres)
-    let id = symbol_short!("PROP_SP");
-    let data_hash = symbol_short!("HASH_SP");
+    let id = String::from_str(&env, "PROP_SP");
+    let data_hash = BytesN::from_array(&env, &[10; 32]);
     
     let listing = client.create_listing(&id, &data_hash, &owner);
     assert_eq!(listing.id, id);
     
     // Test: Múltiples listings del mismo owner
-    let id2 = symbol_short!("PROP2");
-    let data_hash2 = symbol_short!("HASH2");
+    let id2 = String::from_str(&env, "PROP2");
+    let data_hash2 = BytesN::from_array(&env, &[8; 32]);
     
     let listing2 = client.create_listing(&id2, &data_hash2, &owner);
     assert_eq!(listing2.owner, owner);
     assert_ne!(listing2.id, listing.id);
     
     // Test: Cambios de estado múltiples
-    let _ = client.update_status(&id, &owner, &PropertyStatus::Booked);
+    let _ = client.update_status(&id, &owner, &PropertyStatus::Inactive);
     let _ = client.update_status(&id, &owner, &PropertyStatus::Maintenance);
     let final_listing = client.update_status(&id, &owner, &PropertyStatus::Available);
     assert_eq!(final_listing.status, PropertyStatus::Available);
 }
+
+// =========================
+// Storage TTL Management
+// =========================
+#[test]
+fn test_listing_ttl_extended_on_write() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+
+    client.create_listing(&id, &data_hash, &owner);
+
+    let key = (LISTINGS, id.clone());
+    env.as_contract(&contract_id, || {
+        assert_eq!(
+            env.storage().persistent().get_ttl(&key),
+            PERSISTENT_BUMP_AMOUNT
+        );
+        assert_eq!(env.storage().instance().get_ttl(), INSTANCE_BUMP_AMOUNT);
+    });
+}
+
+#[test]
+fn test_listing_ttl_extended_on_read() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+
+    client.create_listing(&id, &data_hash, &owner);
+
+    // Advance the ledger until the listing drops below the bump threshold
+    let elapsed = PERSISTENT_BUMP_AMOUNT - PERSISTENT_LIFETIME_THRESHOLD + 1;
+    env.ledger().with_mut(|li| {
+        li.sequence_number += elapsed;
+    });
+
+    let key = (LISTINGS, id.clone());
+    env.as_contract(&contract_id, || {
+        assert_eq!(
+            env.storage().persistent().get_ttl(&key),
+            PERSISTENT_LIFETIME_THRESHOLD - 1
+        );
+    });
+
+    // Reading the listing bumps it back to the full amount
+    assert!(client.get_listing(&id).is_some());
+    env.as_contract(&contract_id, || {
+        assert_eq!(
+            env.storage().persistent().get_ttl(&key),
+            PERSISTENT_BUMP_AMOUNT
+        );
+    });
+}
+
+#[test]
+fn test_bump_ttl_by_admin() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(PropertyListingContract, (&admin,));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id1 = String::from_str(&env, "PROP1");
+    let id2 = String::from_str(&env, "PROP2");
+    let missing = String::from_str(&env, "MISSING");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+
+    client.create_listing(&id1, &data_hash, &owner);
+    client.create_listing(&id2, &data_hash, &owner);
+
+    // Entries added to a listing over its lifetime
+    let tag = symbol_short!("beach");
+    client.add_tag(&tag);
+    client.set_listing_tags(&id1, &owner, &vec![&env, tag]);
+    client.update_listing(&id1, &BytesN::from_array(&env, &[2; 32]), &owner);
+    client.update_status(&id1, &owner, &PropertyStatus::Maintenance);
+    client.grant_role(
+        &id1,
+        &Address::generate(&env),
+        &ListingRole::Manager,
+        &PERM_UPDATE_DATA,
+    );
+    client.set_compliance(
+        &id1,
+        &owner,
+        &ListingCompliance {
+            jurisdiction: String::from_str(&env, "ES-B-BCN"),
+            license_id: String::from_str(&env, "HUTB-012345"),
+            annual_night_cap: Some(90),
+        },
+    );
+    client.set_revenue_shares(
+        &id1,
+        &vec![
+            &env,
+            RevenueShare {
+                beneficiary: owner.clone(),
+                bps: TOTAL_SHARE_BPS,
+            },
+        ],
+    );
+    client.tokenize_listing(&id1);
+
+    env.ledger().with_mut(|li| {
+        li.sequence_number += 8 * DAY_IN_LEDGERS;
+    });
+
+    // Missing listings are skipped
+    client.bump_ttl(&vec![&env, id1.clone(), id2.clone(), missing]);
+
+    env.as_contract(&contract_id, || {
+        assert_eq!(
+            env.storage().persistent().get_ttl(&(LISTINGS, id1.clone())),
+            PERSISTENT_BUMP_AMOUNT
+        );
+        assert_eq!(
+            env.storage().persistent().get_ttl(&(LISTINGS, id2.clone())),
+            PERSISTENT_BUMP_AMOUNT
+        );
+        for prefix in [
+            LIST_POS,
+            LIST_VERSION_COUNT,
+            STATUS_HISTORY,
+            DELEGATES,
+            COMPLIANCE,
+            LISTING_TAGS,
+            REVENUE_SHARES,
+            TOKENIZED,
+        ] {
+            assert_eq!(
+                env.storage().persistent().get_ttl(&(prefix, id1.clone())),
+                PERSISTENT_BUMP_AMOUNT
+            );
+        }
+        for version in 1..=2u32 {
+            assert_eq!(
+                env.storage()
+                    .persistent()
+                    .get_ttl(&(LIST_VERSION, id1.clone(), version)),
+                PERSISTENT_BUMP_AMOUNT
+            );
+        }
+        let owner_scope = (OWNER_INDEX, owner.clone());
+        assert_eq!(
+            env.storage()
+                .persistent()
+                .get_ttl(&(OWNER_POS, id1.clone())),
+            PERSISTENT_BUMP_AMOUNT
+        );
+        assert_eq!(
+            env.storage().persistent().get_ttl(&owner_scope),
+            PERSISTENT_BUMP_AMOUNT
+        );
+        assert_eq!(
+            env.storage().persistent().get_ttl(&(owner_scope, 0u32)),
+            PERSISTENT_BUMP_AMOUNT
+        );
+        assert_eq!(env.storage().instance().get_ttl(), INSTANCE_BUMP_AMOUNT);
+    });
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_bump_ttl_requires_admin_auth() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(PropertyListingContract, (&admin,));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    // No auth mocked for the admin
+    client.bump_ttl(&vec![&env]);
+}
+
+// =========================
+// Upgrades and Schema Versioning
+// =========================
+#[test]
+fn test_version_after_deploy() {
+    let env = Env::default();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    assert_eq!(client.version(), SCHEMA_VERSION);
+}
+
+#[test]
+fn test_upgrade_keeps_state() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    // Deploy the version 1 release and create a listing with it
+    let contract_id = env.register(property_listing_v1::WASM, ());
+    let old_client = property_listing_v1::Client::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = String::from_str(&env, "hash123");
+
+    old_client.initialize(&admin);
+    old_client.create_listing(&id, &data_hash, &owner);
+    assert_eq!(old_client.version(), 1);
+
+    // Upgrade to the version 2 release and migrate the stored data
+    let new_wasm_hash = env
+        .deployer()
+        .upload_contract_wasm(property_listing_v2::WASM);
+    old_client.upgrade(&new_wasm_hash);
+
+    let client = property_listing_v2::Client::new(&env, &contract_id);
+    assert_eq!(client.version(), 1);
+    assert_eq!(client.migrate(), 2);
+    assert_eq!(client.version(), 2);
+
+    let listing = client.get_listing(&id).unwrap();
+    assert_eq!(listing.id, id);
+    assert_eq!(listing.data_hash, data_hash);
+    assert_eq!(listing.owner, owner);
+    assert_eq!(
+        listing.status,
+        property_listing_v2::PropertyStatus::Available
+    );
+
+    // Listings created before the index existed are backfilled by the admin
+    assert_eq!(client.listing_count(), 0);
+    assert_eq!(client.index_listings(&vec![&env, id.clone()]), 1);
+    assert_eq!(client.listing_count(), 1);
+    let page = client.get_all_listings(&0, &10);
+    assert_eq!(page.listings.get(0).unwrap().id, id);
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_upgrade_requires_admin_auth() {
+    let env = Env::default();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    // No auth mocked for the admin
+    client.upgrade(&BytesN::from_array(&env, &[0u8; 32]));
+}
+
+#[test]
+fn test_migrate_legacy_storage() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    // Simulate a deployment from before schema versioning
+    env.as_contract(&contract_id, || {
+        env.storage().instance().remove(&VERSION);
+    });
+    assert_eq!(client.version(), 0);
+
+    assert_eq!(client.migrate(&100), SCHEMA_VERSION);
+    assert_eq!(client.version(), SCHEMA_VERSION);
+
+    // Migrating again is a no-op
+    assert_eq!(client.migrate(&100), SCHEMA_VERSION);
+}
+
+#[test]
+#[should_panic(expected = "Stored schema is newer than the contract code")]
+fn test_migrate_newer_schema() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    env.as_contract(&contract_id, || {
+        env.storage()
+            .instance()
+            .set(&VERSION, &(SCHEMA_VERSION + 1));
+    });
+
+    client.migrate(&100);
+}
+
+// =========================
+// Emergency Pause
+// =========================
+#[test]
+#[should_panic(expected = "Error(Contract, #9)")]
+fn test_pause_blocks_create_listing() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(PropertyListingContract, (&admin,));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    client.pause(&admin);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+}
+
+#[test]
+fn test_pause_blocks_updates_but_not_views() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(PropertyListingContract, (&admin,));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+
+    client.create_listing(&id, &data_hash, &owner);
+    client.pause(&admin);
+    assert!(client.is_paused());
+
+    let new_hash = BytesN::from_array(&env, &[3; 32]);
+    assert!(client.try_update_listing(&id, &new_hash, &owner).is_err());
+    assert!(client
+        .try_update_status(&id, &owner, &PropertyStatus::Maintenance)
+        .is_err());
+
+    // Views keep working
+    let listing = client.get_listing(&id).unwrap();
+    assert_eq!(listing.data_hash, data_hash);
+    assert_eq!(client.version(), SCHEMA_VERSION);
+}
+
+#[test]
+fn test_guardian_pause_and_admin_unpause() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(PropertyListingContract, (&admin,));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let guardian = Address::generate(&env);
+    client.set_guardian(&guardian);
+
+    client.pause(&guardian);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("paused"),).into_val(&env),
+                guardian.into_val(&env)
+            )
+        ]
+    );
+    assert!(client.is_paused());
+
+    client.unpause();
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("unpaused"),).into_val(&env),
+                admin.into_val(&env)
+            )
+        ]
+    );
+    assert!(!client.is_paused());
+
+    // Mutations work again
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized: only the admin or guardian can pause")]
+fn test_pause_unauthorized() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let stranger = Address::generate(&env);
+    client.pause(&stranger);
+}
+
+// =========================
+// Listing Index and Pagination
+// =========================
+#[test]
+fn test_get_all_listings_empty() {
+    let env = Env::default();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let page = client.get_all_listings(&0, &10);
+    assert_eq!(page.listings.len(), 0);
+    assert_eq!(page.next_cursor, None);
+    assert_eq!(client.listing_count(), 0);
+}
+
+#[test]
+fn test_get_all_listings_pagination() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    let ids = [
+        String::from_str(&env, "PROP1"),
+        String::from_str(&env, "PROP2"),
+        String::from_str(&env, "PROP3"),
+        String::from_str(&env, "PROP4"),
+        String::from_str(&env, "PROP5"),
+    ];
+    for id in ids.iter() {
+        client.create_listing(id, &data_hash, &owner);
+    }
+    assert_eq!(client.listing_count(), 5);
+
+    let page1 = client.get_all_listings(&0, &2);
+    assert_eq!(page1.listings.len(), 2);
+    assert_eq!(page1.listings.get(0).unwrap().id, ids[0]);
+    assert_eq!(page1.listings.get(1).unwrap().id, ids[1]);
+    assert_eq!(page1.next_cursor, Some(2));
+
+    let page2 = client.get_all_listings(&page1.next_cursor.unwrap(), &2);
+    assert_eq!(page2.listings.get(0).unwrap().id, ids[2]);
+    assert_eq!(page2.listings.get(1).unwrap().id, ids[3]);
+    assert_eq!(page2.next_cursor, Some(4));
+
+    let page3 = client.get_all_listings(&page2.next_cursor.unwrap(), &2);
+    assert_eq!(page3.listings.len(), 1);
+    assert_eq!(page3.listings.get(0).unwrap().id, ids[4]);
+    assert_eq!(page3.next_cursor, None);
+
+    // Large limits are capped
+    let all = client.get_all_listings(&0, &(MAX_PAGE_SIZE + 10));
+    assert_eq!(all.listings.len(), 5);
+}
+
+#[test]
+fn test_listing_index_sharded() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    for i in 0..INDEX_SHARD_SIZE + 1 {
+        let mut raw = *b"PROP000";
+        raw[4] += (i / 100) as u8;
+        raw[5] += (i / 10 % 10) as u8;
+        raw[6] += (i % 10) as u8;
+        let id = String::from_bytes(&env, &raw);
+        client.create_listing(&id, &data_hash, &owner);
+    }
+    assert_eq!(client.listing_count(), INDEX_SHARD_SIZE + 1);
+
+    env.as_contract(&contract_id, || {
+        let shard0: Vec<String> = env.storage().persistent().get(&(LIST_INDEX, 0u32)).unwrap();
+        let shard1: Vec<String> = env.storage().persistent().get(&(LIST_INDEX, 1u32)).unwrap();
+        assert_eq!(shard0.len(), INDEX_SHARD_SIZE);
+        assert_eq!(shard1.len(), 1);
+    });
+
+    // Pages can span shards
+    let page = client.get_all_listings(&(INDEX_SHARD_SIZE - 1), &10);
+    assert_eq!(page.listings.len(), 2);
+    assert_eq!(
+        page.listings.get(0).unwrap().id,
+        String::from_str(&env, "PROP099")
+    );
+    assert_eq!(
+        page.listings.get(1).unwrap().id,
+        String::from_str(&env, "PROP100")
+    );
+    assert_eq!(page.next_cursor, None);
+}
+
+#[test]
+#[should_panic(expected = "Invalid limit: must be greater than zero")]
+fn test_get_all_listings_zero_limit() {
+    let env = Env::default();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    client.get_all_listings(&0, &0);
+}
+
+#[test]
+fn test_index_listings_backfill() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id1 = String::from_str(&env, "PROP1");
+    let id2 = String::from_str(&env, "PROP2");
+    let missing = String::from_str(&env, "MISSING");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id1, &data_hash, &owner);
+    client.create_listing(&id2, &data_hash, &owner);
+
+    // Roll storage back to the version 1 layout, which had no index
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().remove(&(LIST_INDEX, 0u32));
+        env.storage().persistent().remove(&(LIST_POS, id1.clone()));
+        env.storage().persistent().remove(&(LIST_POS, id2.clone()));
+        env.storage().persistent().remove(&LIST_COUNT);
+        env.storage().instance().set(&VERSION, &1u32);
+    });
+    assert_eq!(client.migrate(&100), SCHEMA_VERSION);
+    assert_eq!(client.listing_count(), 0);
+
+    // Missing and duplicate ids are skipped
+    let added = client.index_listings(&vec![&env, id2.clone(), missing, id1.clone(), id2.clone()]);
+    assert_eq!(added, 2);
+    assert_eq!(client.listing_count(), 2);
+
+    let page = client.get_all_listings(&0, &10);
+    assert_eq!(page.listings.get(0).unwrap().id, id2);
+    assert_eq!(page.listings.get(1).unwrap().id, id1);
+
+    // Indexed listings are not added twice
+    assert_eq!(client.index_listings(&vec![&env, id1]), 0);
+    assert_eq!(client.listing_count(), 2);
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_index_listings_requires_admin_auth() {
+    let env = Env::default();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    // No auth mocked for the admin
+    client.index_listings(&vec![&env]);
+}
+
+// =========================
+// Owner Index
+// =========================
+#[test]
+fn test_get_listings_by_owner() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let host = Address::generate(&env);
+    let other_host = Address::generate(&env);
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    let id1 = String::from_str(&env, "PROP1");
+    let id2 = String::from_str(&env, "PROP2");
+    let id3 = String::from_str(&env, "PROP3");
+    let id4 = String::from_str(&env, "PROP4");
+
+    client.create_listing(&id1, &data_hash, &host);
+    client.create_listing(&id2, &data_hash, &other_host);
+    client.create_listing(&id3, &data_hash, &host);
+    client.create_listing(&id4, &data_hash, &host);
+
+    let page1 = client.get_listings_by_owner(&host, &0, &2);
+    assert_eq!(page1.listings.len(), 2);
+    assert_eq!(page1.listings.get(0).unwrap().id, id1);
+    assert_eq!(page1.listings.get(1).unwrap().id, id3);
+    assert_eq!(page1.next_cursor, Some(2));
+
+    let page2 = client.get_listings_by_owner(&host, &page1.next_cursor.unwrap(), &2);
+    assert_eq!(page2.listings.len(), 1);
+    assert_eq!(page2.listings.get(0).unwrap().id, id4);
+    assert_eq!(page2.next_cursor, None);
+
+    let other_page = client.get_listings_by_owner(&other_host, &0, &10);
+    assert_eq!(other_page.listings.len(), 1);
+    assert_eq!(other_page.listings.get(0).unwrap().id, id2);
+
+    let empty = client.get_listings_by_owner(&Address::generate(&env), &0, &10);
+    assert_eq!(empty.listings.len(), 0);
+    assert_eq!(empty.next_cursor, None);
+}
+
+#[test]
+fn test_owner_index_spans_shards() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    // Fill the first shard of the host's index with placeholder ids
+    let host = Address::generate(&env);
+    let scope = (OWNER_INDEX, host.clone());
+    env.as_contract(&contract_id, || {
+        let mut shard = vec![&env];
+        for _ in 0..INDEX_SHARD_SIZE {
+            shard.push_back(String::from_str(&env, "OTHER"));
+        }
+        env.storage()
+            .persistent()
+            .set(&(scope.clone(), 0u32), &shard);
+        env.storage().persistent().set(&scope, &INDEX_SHARD_SIZE);
+    });
+
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    let id1 = String::from_str(&env, "PROP1");
+    let id2 = String::from_str(&env, "PROP2");
+    client.create_listing(&id1, &data_hash, &host);
+    client.create_listing(&id2, &data_hash, &host);
+
+    // Transferring a listing moves the last listing of the owner into its
+    // place
+    let new_owner = Address::generate(&env);
+    client.propose_transfer(&id1, &new_owner);
+    client.accept_transfer(&id1);
+    let page = client.get_listings_by_owner(&host, &INDEX_SHARD_SIZE, &10);
+    assert_eq!(page.listings.len(), 1);
+    assert_eq!(page.listings.get(0).unwrap().id, id2);
+    assert_eq!(page.next_cursor, None);
+    assert_eq!(
+        client
+            .get_listings_by_owner(&new_owner, &0, &10)
+            .listings
+            .get(0)
+            .unwrap()
+            .id,
+        id1
+    );
+
+    // Emptied shards are removed
+    client.archive_listing(&id2);
+    env.as_contract(&contract_id, || {
+        assert!(!env.storage().persistent().has(&(scope.clone(), 1u32)));
+        let count: u32 = env.storage().persistent().get(&scope).unwrap();
+        assert_eq!(count, INDEX_SHARD_SIZE);
+    });
+}
+
+#[test]
+#[should_panic(expected = "Invalid limit: must be greater than zero")]
+fn test_get_listings_by_owner_zero_limit() {
+    let env = Env::default();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    client.get_listings_by_owner(&Address::generate(&env), &0, &0);
+}
+
+#[test]
+fn test_migrate_backfills_owner_index() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let host = Address::generate(&env);
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    let id1 = String::from_str(&env, "PROP1");
+    let id2 = String::from_str(&env, "PROP2");
+    client.create_listing(&id1, &data_hash, &host);
+    client.create_listing(&id2, &data_hash, &host);
+
+    // Roll storage back to the version 2 layout, which had no owner index
+    env.as_contract(&contract_id, || {
+        store_legacy_listing(&env, &id1);
+        store_legacy_listing(&env, &id2);
+        let scope = (OWNER_INDEX, host.clone());
+        env.storage().persistent().remove(&(scope.clone(), 0u32));
+        env.storage().persistent().remove(&scope);
+        env.storage().persistent().remove(&(OWNER_POS, id1.clone()));
+        env.storage().persistent().remove(&(OWNER_POS, id2.clone()));
+        env.storage().instance().set(&VERSION, &2u32);
+    });
+    assert_eq!(
+        client.get_listings_by_owner(&host, &0, &10).listings.len(),
+        0
+    );
+
+    // Each call converts at most `limit` listings, and the version is only
+    // bumped once a step has gone through every listing
+    assert_eq!(client.migrate(&1), 2);
+    assert_eq!(client.version(), 2);
+
+    assert_eq!(client.migrate(&100), SCHEMA_VERSION);
+
+    let page = client.get_listings_by_owner(&host, &0, &10);
+    assert_eq!(page.listings.len(), 2);
+    assert_eq!(page.listings.get(0).unwrap().id, id1);
+    assert_eq!(page.listings.get(1).unwrap().id, id2);
+}
+
+#[test]
+fn test_upgrade_backfills_owner_index() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    // Deploy the version 2 release and create listings with it
+    let contract_id = env.register(property_listing_v2::WASM, ());
+    let old_client = property_listing_v2::Client::new(&env, &contract_id);
+    old_client.initialize(&Address::generate(&env));
+
+    let host = Address::generate(&env);
+    let data_hash = String::from_str(&env, "hash123");
+    let id1 = String::from_str(&env, "PROP1");
+    let id2 = String::from_str(&env, "PROP2");
+    old_client.create_listing(&id1, &data_hash, &host);
+    old_client.create_listing(&id2, &data_hash, &Address::generate(&env));
+
+    // Upgrade to the version 3 release and migrate the stored data
+    let new_wasm_hash = env
+        .deployer()
+        .upload_contract_wasm(property_listing_v3::WASM);
+    old_client.upgrade(&new_wasm_hash);
+
+    let client = property_listing_v3::Client::new(&env, &contract_id);
+    assert_eq!(client.migrate(), 3);
+
+    let page = client.get_listings_by_owner(&host, &0, &10);
+    assert_eq!(page.listings.len(), 1);
+    assert_eq!(page.listings.get(0).unwrap().id, id1);
+    assert_eq!(client.listing_count(), 2);
+}
+
+// =========================
+// Ownership Transfer
+// =========================
+#[test]
+fn test_transfer_ownership() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let new_owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    client.propose_transfer(&id, &new_owner);
+    assert_eq!(env.auths()[0].0, owner);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("xfer_prop"), id.clone(), owner.clone()).into_val(&env),
+                new_owner.into_val(&env)
+            )
+        ]
+    );
+    assert_eq!(client.get_pending_transfer(&id), Some(new_owner.clone()));
+
+    // Nothing changes until the new owner accepts
+    assert_eq!(client.get_listing(&id).unwrap().owner, owner);
+
+    let listing = client.accept_transfer(&id);
+    assert_eq!(env.auths()[0].0, new_owner);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("xfer_done"), id.clone(), new_owner.clone()).into_val(&env),
+                owner.into_val(&env)
+            )
+        ]
+    );
+    assert_eq!(listing.owner, new_owner);
+    assert_eq!(client.get_listing(&id).unwrap().owner, new_owner);
+    assert_eq!(client.get_pending_transfer(&id), None);
+
+    // The owner index follows the listing
+    assert_eq!(
+        client.get_listings_by_owner(&owner, &0, &10).listings.len(),
+        0
+    );
+    let page = client.get_listings_by_owner(&new_owner, &0, &10);
+    assert_eq!(page.listings.get(0).unwrap().id, id);
+
+    // Only the new owner can update the listing now
+    let new_hash = BytesN::from_array(&env, &[2; 32]);
+    client.update_listing(&id, &new_hash, &new_owner);
+}
+
+#[test]
+fn test_cancel_transfer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let new_owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    client.propose_transfer(&id, &new_owner);
+    client.cancel_transfer(&id);
+    assert_eq!(env.auths()[0].0, owner);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("xfer_cncl"), id.clone(), owner.clone()).into_val(&env),
+                new_owner.into_val(&env)
+            )
+        ]
+    );
+    assert_eq!(client.get_pending_transfer(&id), None);
+}
+
+#[test]
+#[should_panic(expected = "No pending transfer")]
+fn test_accept_cancelled_transfer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let new_owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    client.propose_transfer(&id, &new_owner);
+    client.cancel_transfer(&id);
+    client.accept_transfer(&id);
+}
+
+#[test]
+#[should_panic(expected = "No pending transfer")]
+fn test_cancel_transfer_without_proposal() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    client.cancel_transfer(&id);
+}
+
+#[test]
+#[should_panic(expected = "Cannot transfer to the current owner")]
+fn test_propose_transfer_to_current_owner() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    client.propose_transfer(&id, &owner);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_propose_transfer_missing_listing() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let id = String::from_str(&env, "MISSING");
+    client.propose_transfer(&id, &Address::generate(&env));
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_propose_transfer_requires_owner_auth() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    // Stop mocking auths so the owner signature is missing
+    env.set_auths(&[]);
+    client.propose_transfer(&id, &Address::generate(&env));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #9)")]
+fn test_pause_blocks_transfer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(PropertyListingContract, (&admin,));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+    client.propose_transfer(&id, &Address::generate(&env));
+
+    client.pause(&admin);
+    client.accept_transfer(&id);
+}
+
+// =========================
+// Delegated Roles
+// =========================
+#[test]
+fn test_delegate_updates_listing() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let manager = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    let permissions = PERM_UPDATE_DATA | PERM_UPDATE_STATUS;
+    client.grant_role(&id, &manager, &ListingRole::Manager, &permissions);
+    assert_eq!(env.auths()[0].0, owner);
+
+    let delegate = client.get_delegates(&id).get(manager.clone()).unwrap();
+    assert_eq!(delegate.role, ListingRole::Manager);
+    assert_eq!(delegate.permissions, permissions);
+
+    let new_hash = BytesN::from_array(&env, &[2; 32]);
+    let updated = client.update_listing(&id, &new_hash, &manager);
+    assert_eq!(env.auths()[0].0, manager);
+    assert_eq!(updated.data_hash, new_hash);
+    assert_eq!(updated.owner, owner);
+
+    let updated = client.update_status(&id, &manager, &PropertyStatus::Maintenance);
+    assert_eq!(updated.status, PropertyStatus::Maintenance);
+}
+
+#[test]
+fn test_has_permission() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let cleaner = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let missing = String::from_str(&env, "MISSING");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+    client.grant_role(
+        &id,
+        &cleaner,
+        &ListingRole::CalendarOnly,
+        &PERM_CONFIRM_BOOKINGS,
+    );
+
+    // Owners hold every permission
+    assert!(client.has_permission(&id, &owner, &PERM_ALL));
+
+    assert!(client.has_permission(&id, &cleaner, &PERM_CONFIRM_BOOKINGS));
+    assert!(!client.has_permission(&id, &cleaner, &PERM_UPDATE_STATUS));
+    assert!(!client.has_permission(&id, &cleaner, &(PERM_CONFIRM_BOOKINGS | PERM_UPDATE_STATUS)));
+    assert!(!client.has_permission(&id, &stranger, &PERM_CONFIRM_BOOKINGS));
+    assert!(!client.has_permission(&missing, &owner, &PERM_UPDATE_DATA));
+}
+
+#[test]
+#[should_panic(expected = "Only the owner can update the listing")]
+fn test_delegate_without_permission() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let cleaner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+    client.grant_role(&id, &cleaner, &ListingRole::CalendarOnly, &PERM_CALENDAR);
+
+    let new_hash = BytesN::from_array(&env, &[2; 32]);
+    client.update_listing(&id, &new_hash, &cleaner);
+}
+
+#[test]
+#[should_panic(expected = "Permissions not allowed for role")]
+fn test_calendar_only_role_limits_permissions() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    client.grant_role(
+        &id,
+        &Address::generate(&env),
+        &ListingRole::CalendarOnly,
+        &PERM_UPDATE_DATA,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Invalid permissions")]
+fn test_grant_role_invalid_permissions() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    client.grant_role(
+        &id,
+        &Address::generate(&env),
+        &ListingRole::CoHost,
+        &(PERM_ALL + 1),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Too many delegates")]
+fn test_grant_role_too_many_delegates() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    for _ in 0..MAX_DELEGATES + 1 {
+        client.grant_role(
+            &id,
+            &Address::generate(&env),
+            &ListingRole::CoHost,
+            &PERM_ALL,
+        );
+    }
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_grant_role_requires_owner_auth() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    // Stop mocking auths so the owner signature is missing
+    env.set_auths(&[]);
+    client.grant_role(
+        &id,
+        &Address::generate(&env),
+        &ListingRole::CoHost,
+        &PERM_ALL,
+    );
+}
+
+#[test]
+fn test_revoke_role() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let co_host = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+    client.grant_role(&id, &co_host, &ListingRole::CoHost, &PERM_ALL);
+    assert!(client.has_permission(&id, &co_host, &PERM_UPDATE_DATA));
+
+    client.revoke_role(&id, &co_host);
+    assert!(!client.has_permission(&id, &co_host, &PERM_UPDATE_DATA));
+    assert_eq!(client.get_delegates(&id).len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Delegate not found")]
+fn test_revoke_missing_delegate() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    client.revoke_role(&id, &Address::generate(&env));
+}
+
+#[test]
+fn test_transfer_clears_delegates() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let new_owner = Address::generate(&env);
+    let co_host = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+    client.grant_role(&id, &co_host, &ListingRole::CoHost, &PERM_ALL);
+
+    client.propose_transfer(&id, &new_owner);
+    client.accept_transfer(&id);
+
+    assert_eq!(client.get_delegates(&id).len(), 0);
+    assert!(!client.has_permission(&id, &co_host, &PERM_UPDATE_DATA));
+}
+
+// =========================
+// Status State Machine
+// =========================
+#[test]
+fn test_status_history() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1703980800;
+    });
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let manager = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+    client.grant_role(&id, &manager, &ListingRole::Manager, &PERM_UPDATE_STATUS);
+    assert_eq!(client.get_status_history(&id).len(), 0);
+
+    client.update_status(&id, &owner, &PropertyStatus::Maintenance);
+    env.ledger().with_mut(|li| {
+        li.timestamp += 3600;
+    });
+    client.update_status(&id, &manager, &PropertyStatus::Available);
+
+    let history = client.get_status_history(&id);
+    assert_eq!(history.len(), 2);
+    assert_eq!(
+        history.get(0).unwrap(),
+        StatusChange {
+            from: PropertyStatus::Available,
+            to: PropertyStatus::Maintenance,
+            changed_by: owner,
+            timestamp: 1703980800,
+        }
+    );
+    assert_eq!(
+        history.get(1).unwrap(),
+        StatusChange {
+            from: PropertyStatus::Maintenance,
+            to: PropertyStatus::Available,
+            changed_by: manager,
+            timestamp: 1703980800 + 3600,
+        }
+    );
+}
+
+#[test]
+fn test_status_history_is_capped() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    // Toggle between Available and Maintenance past the cap
+    for i in 0..MAX_STATUS_HISTORY + 1 {
+        let status = if i % 2 == 0 {
+            PropertyStatus::Maintenance
+        } else {
+            PropertyStatus::Available
+        };
+        client.update_status(&id, &owner, &status);
+    }
+
+    let history = client.get_status_history(&id);
+    assert_eq!(history.len(), MAX_STATUS_HISTORY);
+    // The first change was dropped
+    assert_eq!(history.get(0).unwrap().from, PropertyStatus::Maintenance);
+    assert_eq!(
+        history.get(MAX_STATUS_HISTORY - 1).unwrap().to,
+        PropertyStatus::Maintenance
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #1)")]
+fn test_owner_cannot_set_booked() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    client.update_status(&id, &owner, &PropertyStatus::Booked);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #1)")]
+fn test_same_status_transition() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    client.update_status(&id, &owner, &PropertyStatus::Available);
+}
+
+#[test]
+fn test_invalid_transition_leaves_status() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+    client.update_status(&id, &owner, &PropertyStatus::Inactive);
+
+    let result = client.try_update_status(&id, &owner, &PropertyStatus::Booked);
+    assert_eq!(
+        result,
+        Err(Ok(soroban_sdk::Error::from(
+            PropertyListingError::InvalidTransition
+        )))
+    );
+
+    assert_eq!(
+        client.get_listing(&id).unwrap().status,
+        PropertyStatus::Inactive
+    );
+    assert_eq!(client.get_status_history(&id).len(), 1);
+}
+
+// =========================
+// Booking Contract Occupancy
+// =========================
+#[test]
+fn test_booking_contract_sets_occupancy() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let booking_contract = Address::generate(&env);
+    client.set_booking_contract(&booking_contract);
+    assert_eq!(
+        client.get_booking_contract(),
+        Some(booking_contract.clone())
+    );
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    let listing = client.set_occupancy(&id, &true);
+    assert_eq!(env.auths()[0].0, booking_contract);
+    assert_eq!(listing.status, PropertyStatus::Booked);
+
+    // The owner cannot override while a guest is in the house
+    let result = client.try_update_status(&id, &owner, &PropertyStatus::Available);
+    assert_eq!(
+        result,
+        Err(Ok(soroban_sdk::Error::from(
+            PropertyListingError::InvalidTransition
+        )))
+    );
+
+    let listing = client.set_occupancy(&id, &false);
+    assert_eq!(listing.status, PropertyStatus::Available);
+
+    let history = client.get_status_history(&id);
+    assert_eq!(history.len(), 2);
+    assert_eq!(history.get(0).unwrap().to, PropertyStatus::Booked);
+    assert_eq!(history.get(0).unwrap().changed_by, booking_contract);
+    assert_eq!(history.get(1).unwrap().to, PropertyStatus::Available);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #1)")]
+fn test_set_occupancy_from_maintenance() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    client.set_booking_contract(&Address::generate(&env));
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+    client.update_status(&id, &owner, &PropertyStatus::Maintenance);
+
+    client.set_occupancy(&id, &true);
+}
+
+#[test]
+#[should_panic(expected = "Booking contract not set")]
+fn test_set_occupancy_without_booking_contract() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    client.set_occupancy(&id, &true);
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_set_occupancy_requires_booking_contract_auth() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    client.set_booking_contract(&Address::generate(&env));
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    // Stop mocking auths so the booking contract authorization is missing
+    env.set_auths(&[]);
+    client.set_occupancy(&id, &true);
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_set_booking_contract_requires_admin_auth() {
+    let env = Env::default();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    // No auth mocked for the admin
+    client.set_booking_contract(&Address::generate(&env));
+}
+
+// =========================
+// Listing Metadata
+// =========================
+#[test]
+fn test_set_metadata() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let manager = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+    assert_eq!(client.get_metadata(&id), None);
+    assert_eq!(client.get_max_guests(&id), None);
+
+    let metadata = ListingMetadata {
+        property_type: PropertyType::Apartment,
+        bedrooms: 2,
+        bathrooms: 1,
+        max_guests: 4,
+        amenities: AMENITY_WIFI | AMENITY_KITCHEN,
+        country_code: String::from_str(&env, "AR"),
+        region_code: String::from_str(&env, "C"),
+        geohash: String::from_str(&env, "69y7pkxf"),
+    };
+    client.set_metadata(&id, &owner, &metadata);
+    assert_eq!(client.get_metadata(&id), Some(metadata.clone()));
+    assert_eq!(client.get_max_guests(&id), Some(4));
+
+    // Delegates allowed to update the listing data can change it too
+    client.grant_role(&id, &manager, &ListingRole::Manager, &PERM_UPDATE_DATA);
+    let updated = ListingMetadata {
+        max_guests: 6,
+        amenities: AMENITY_ALL,
+        ..metadata
+    };
+    client.set_metadata(&id, &manager, &updated);
+    assert_eq!(env.auths()[0].0, manager);
+    assert_eq!(client.get_max_guests(&id), Some(6));
+}
+
+#[test]
+#[should_panic(expected = "Only the owner can update the listing")]
+fn test_set_metadata_unauthorized() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    let metadata = ListingMetadata {
+        property_type: PropertyType::House,
+        bedrooms: 3,
+        bathrooms: 2,
+        max_guests: 6,
+        amenities: AMENITY_PARKING,
+        country_code: String::from_str(&env, "AR"),
+        region_code: String::from_str(&env, "B"),
+        geohash: String::from_str(&env, "69y7"),
+    };
+    client.set_metadata(&id, &Address::generate(&env), &metadata);
+}
+
+#[test]
+#[should_panic(expected = "Invalid metadata: max guests out of range")]
+fn test_set_metadata_invalid_max_guests() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    let metadata = ListingMetadata {
+        property_type: PropertyType::Room,
+        bedrooms: 1,
+        bathrooms: 1,
+        max_guests: 0,
+        amenities: 0,
+        country_code: String::from_str(&env, "AR"),
+        region_code: String::from_str(&env, "B"),
+        geohash: String::from_str(&env, "69y7"),
+    };
+    client.set_metadata(&id, &owner, &metadata);
+}
+
+#[test]
+#[should_panic(expected = "Invalid metadata: country code")]
+fn test_set_metadata_invalid_country_code() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    let metadata = ListingMetadata {
+        property_type: PropertyType::Room,
+        bedrooms: 1,
+        bathrooms: 1,
+        max_guests: 2,
+        amenities: 0,
+        country_code: String::from_str(&env, "ar"),
+        region_code: String::from_str(&env, "B"),
+        geohash: String::from_str(&env, "69y7"),
+    };
+    client.set_metadata(&id, &owner, &metadata);
+}
+
+#[test]
+#[should_panic(expected = "Invalid metadata: geohash")]
+fn test_set_metadata_invalid_geohash() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    // "a" is not part of the geohash alphabet
+    let metadata = ListingMetadata {
+        property_type: PropertyType::Room,
+        bedrooms: 1,
+        bathrooms: 1,
+        max_guests: 2,
+        amenities: 0,
+        country_code: String::from_str(&env, "AR"),
+        region_code: String::from_str(&env, "B"),
+        geohash: String::from_str(&env, "69ya"),
+    };
+    client.set_metadata(&id, &owner, &metadata);
+}
+
+#[test]
+#[should_panic(expected = "Invalid metadata: unknown amenities")]
+fn test_set_metadata_unknown_amenities() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    let metadata = ListingMetadata {
+        property_type: PropertyType::Room,
+        bedrooms: 1,
+        bathrooms: 1,
+        max_guests: 2,
+        amenities: AMENITY_ALL + 1,
+        country_code: String::from_str(&env, "AR"),
+        region_code: String::from_str(&env, "B"),
+        geohash: String::from_str(&env, "69y7"),
+    };
+    client.set_metadata(&id, &owner, &metadata);
+}
+
+// =========================
+// Location Search
+// =========================
+#[test]
+fn test_search_by_geohash() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    let id1 = String::from_str(&env, "PROP1");
+    let id2 = String::from_str(&env, "PROP2");
+    let id3 = String::from_str(&env, "PROP3");
+    let id4 = String::from_str(&env, "PROP4");
+    client.create_listing(&id1, &data_hash, &owner);
+    client.create_listing(&id2, &data_hash, &owner);
+    client.create_listing(&id3, &data_hash, &owner);
+    client.create_listing(&id4, &data_hash, &owner);
+
+    let metadata = ListingMetadata {
+        property_type: PropertyType::Apartment,
+        bedrooms: 1,
+        bathrooms: 1,
+        max_guests: 2,
+        amenities: 0,
+        country_code: String::from_str(&env, "AR"),
+        region_code: String::from_str(&env, "C"),
+        geohash: String::from_str(&env, "69y7pkxf"),
+    };
+    client.set_metadata(&id1, &owner, &metadata);
+    client.set_metadata(
+        &id2,
+        &owner,
+        &ListingMetadata {
+            geohash: String::from_str(&env, "69y7pz00"),
+            ..metadata.clone()
+        },
+    );
+    client.set_metadata(
+        &id3,
+        &owner,
+        &ListingMetadata {
+            geohash: String::from_str(&env, "69yb1234"),
+            ..metadata.clone()
+        },
+    );
+    // PROP4 has no metadata and is not searchable
+
+    let page = client.search_by_geohash(&String::from_str(&env, "69y"), &0, &10);
+    assert_eq!(page.ids, vec![&env, id1.clone(), id2.clone(), id3.clone()]);
+    assert_eq!(page.next_cursor, None);
+
+    let page = client.search_by_geohash(&String::from_str(&env, "69y7p"), &0, &10);
+    assert_eq!(page.ids, vec![&env, id1.clone(), id2.clone()]);
+
+    // Prefixes longer than the bucket length are filtered
+    let page = client.search_by_geohash(&String::from_str(&env, "69y7pk"), &0, &10);
+    assert_eq!(page.ids, vec![&env, id1.clone()]);
+
+    let page = client.search_by_geohash(&String::from_str(&env, "6gkz"), &0, &10);
+    assert_eq!(page.ids.len(), 0);
+
+    // Pagination
+    let page1 = client.search_by_geohash(&String::from_str(&env, "69y"), &0, &2);
+    assert_eq!(page1.ids, vec![&env, id1.clone(), id2.clone()]);
+    assert_eq!(page1.next_cursor, Some(2));
+    let page2 = client.search_by_geohash(
+        &String::from_str(&env, "69y"),
+        &page1.next_cursor.unwrap(),
+        &2,
+    );
+    assert_eq!(page2.ids, vec![&env, id3]);
+    assert_eq!(page2.next_cursor, None);
+}
+
+#[test]
+fn test_geo_bucket_spans_shards() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+
+    // Fill the first shard of the widest bucket with emptied slots
+    let scope = (GEO_INDEX, String::from_str(&env, "69y"));
+    env.as_contract(&contract_id, || {
+        let mut shard = vec![&env];
+        for _ in 0..INDEX_SHARD_SIZE {
+            shard.push_back(String::from_str(&env, ""));
+        }
+        env.storage()
+            .persistent()
+            .set(&(scope.clone(), 0u32), &shard);
+        env.storage().persistent().set(&scope, &INDEX_SHARD_SIZE);
+    });
+
+    client.set_metadata(
+        &id,
+        &owner,
+        &ListingMetadata {
+            property_type: PropertyType::Apartment,
+            bedrooms: 1,
+            bathrooms: 1,
+            max_guests: 2,
+            amenities: 0,
+            country_code: String::from_str(&env, "AR"),
+            region_code: String::from_str(&env, "C"),
+            geohash: String::from_str(&env, "69y7pkxf"),
+        },
+    );
+    let area = String::from_str(&env, "69y");
+    let page = client.search_by_geohash(&area, &0, &10);
+    assert_eq!(page.ids, vec![&env, id.clone()]);
+    assert_eq!(page.next_cursor, None);
+
+    // A listing going back to its slot is never refused
+    client.update_status(&id, &owner, &PropertyStatus::Inactive);
+    assert_eq!(client.search_by_geohash(&area, &0, &10).ids.len(), 0);
+    client.update_status(&id, &owner, &PropertyStatus::Available);
+    assert_eq!(client.search_by_geohash(&area, &0, &10).ids, vec![&env, id]);
+    env.as_contract(&contract_id, || {
+        let count: u32 = env.storage().persistent().get(&scope).unwrap();
+        assert_eq!(count, INDEX_SHARD_SIZE + 1);
+    });
+}
+
+#[test]
+fn test_search_by_geohash_follows_updates() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    let metadata = ListingMetadata {
+        property_type: PropertyType::House,
+        bedrooms: 3,
+        bathrooms: 2,
+        max_guests: 6,
+        amenities: 0,
+        country_code: String::from_str(&env, "AR"),
+        region_code: String::from_str(&env, "C"),
+        geohash: String::from_str(&env, "69y7pkxf"),
+    };
+    client.set_metadata(&id, &owner, &metadata);
+
+    let old_area = String::from_str(&env, "69y7p");
+    let new_area = String::from_str(&env, "6gkzw");
+    assert_eq!(client.search_by_geohash(&old_area, &0, &10).ids.len(), 1);
+
+    // Inactive listings drop out of the index and come back when reactivated
+    client.update_status(&id, &owner, &PropertyStatus::Inactive);
+    assert_eq!(client.search_by_geohash(&old_area, &0, &10).ids.len(), 0);
+    client.update_status(&id, &owner, &PropertyStatus::Maintenance);
+    assert_eq!(client.search_by_geohash(&old_area, &0, &10).ids.len(), 1);
+
+    // Moving the listing moves it between buckets
+    client.set_metadata(
+        &id,
+        &owner,
+        &ListingMetadata {
+            geohash: String::from_str(&env, "6gkzwgjz"),
+            ..metadata.clone()
+        },
+    );
+    assert_eq!(client.search_by_geohash(&old_area, &0, &10).ids.len(), 0);
+    assert_eq!(
+        client.search_by_geohash(&new_area, &0, &10).ids,
+        vec![&env, id.clone()]
+    );
+
+    // Moving back reuses the old slots
+    client.set_metadata(&id, &owner, &metadata);
+    assert_eq!(
+        client.search_by_geohash(&old_area, &0, &10).ids,
+        vec![&env, id]
+    );
+    env.as_contract(&contract_id, || {
+        let count: u32 = env
+            .storage()
+            .persistent()
+            .get(&(GEO_INDEX, old_area))
+            .unwrap();
+        assert_eq!(count, 1);
+    });
+}
+
+#[test]
+#[should_panic(expected = "Invalid geohash prefix")]
+fn test_search_by_geohash_short_prefix() {
+    let env = Env::default();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    client.search_by_geohash(&String::from_str(&env, "69"), &0, &10);
+}
+
+#[test]
+fn test_migrate_builds_geohash_index() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+    client.set_metadata(
+        &id,
+        &owner,
+        &ListingMetadata {
+            property_type: PropertyType::Cabin,
+            bedrooms: 1,
+            bathrooms: 1,
+            max_guests: 2,
+            amenities: 0,
+            country_code: String::from_str(&env, "AR"),
+            region_code: String::from_str(&env, "Q"),
+            geohash: String::from_str(&env, "6ft1"),
+        },
+    );
+
+    // Roll storage back to the version 3 layout, which had no location index
+    env.as_contract(&contract_id, || {
+        store_legacy_listing(&env, &id);
+        for prefix in ["6ft", "6ft1"] {
+            let scope = (GEO_INDEX, String::from_str(&env, prefix));
+            env.storage().persistent().remove(&(scope.clone(), 0u32));
+            env.storage()
+                .persistent()
+                .remove(&(scope.clone(), id.clone()));
+            env.storage().persistent().remove(&scope);
+        }
+        env.storage().instance().set(&VERSION, &3u32);
+    });
+    let area = String::from_str(&env, "6ft");
+    assert_eq!(client.search_by_geohash(&area, &0, &10).ids.len(), 0);
+
+    assert_eq!(client.migrate(&100), SCHEMA_VERSION);
+    assert_eq!(client.search_by_geohash(&area, &0, &10).ids, vec![&env, id]);
+}
+
+#[test]
+fn test_upgrade_to_location_index() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    // Deploy the version 3 release and create a listing with it
+    let contract_id = env.register(property_listing_v3::WASM, ());
+    let old_client = property_listing_v3::Client::new(&env, &contract_id);
+    old_client.initialize(&Address::generate(&env));
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = String::from_str(&env, "hash123");
+    old_client.create_listing(&id, &data_hash, &owner);
+
+    // Upgrade to the version 4 release and migrate the stored data
+    let new_wasm_hash = env
+        .deployer()
+        .upload_contract_wasm(property_listing_v4::WASM);
+    old_client.upgrade(&new_wasm_hash);
+
+    let client = property_listing_v4::Client::new(&env, &contract_id);
+    assert_eq!(client.migrate(), 4);
+    assert_eq!(client.listing_count(), 1);
+
+    client.set_metadata(
+        &id,
+        &owner,
+        &property_listing_v4::ListingMetadata {
+            property_type: property_listing_v4::PropertyType::Villa,
+            bedrooms: 4,
+            bathrooms: 3,
+            max_guests: 8,
+            amenities: AMENITY_POOL,
+            country_code: String::from_str(&env, "AR"),
+            region_code: String::from_str(&env, "U"),
+            geohash: String::from_str(&env, "4wd8"),
+        },
+    );
+    let page = client.search_by_geohash(&String::from_str(&env, "4wd"), &0, &10);
+    assert_eq!(page.ids, vec![&env, id]);
+}
+
+// =========================
+// Listing Version History
+// =========================
+#[test]
+fn test_listing_history_records_updates() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1000;
+    });
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let manager = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[4; 32]), &owner);
+    client.grant_role(&id, &manager, &ListingRole::Manager, &PERM_UPDATE_DATA);
+    assert_eq!(client.get_listing_version(&id), 1);
+
+    env.ledger().with_mut(|li| {
+        li.timestamp = 2000;
+    });
+    client.update_listing(&id, &BytesN::from_array(&env, &[5; 32]), &manager);
+
+    let page = client.get_listing_history(&id, &0, &10);
+    assert_eq!(page.next_cursor, None);
+    assert_eq!(
+        page.versions,
+        vec![
+            &env,
+            ListingVersion {
+                version: 1,
+                data_hash: BytesN::from_array(&env, &[4; 32]),
+                updated_by: owner,
+                timestamp: 1000,
+            },
+            ListingVersion {
+                version: 2,
+                data_hash: BytesN::from_array(&env, &[5; 32]),
+                updated_by: manager,
+                timestamp: 2000,
+            },
+        ]
+    );
+    assert_eq!(client.get_listing_version(&id), 2);
+}
+
+#[test]
+fn test_listing_history_pagination() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[4; 32]), &owner);
+    client.update_listing(&id, &BytesN::from_array(&env, &[5; 32]), &owner);
+    client.update_listing(&id, &BytesN::from_array(&env, &[6; 32]), &owner);
+
+    let first = client.get_listing_history(&id, &0, &2);
+    assert_eq!(first.versions.len(), 2);
+    assert_eq!(first.versions.get(0).unwrap().version, 1);
+    assert_eq!(first.next_cursor, Some(2));
+
+    let second = client.get_listing_history(&id, &2, &2);
+    assert_eq!(second.versions.len(), 1);
+    assert_eq!(
+        second.versions.get(0).unwrap().data_hash,
+        BytesN::from_array(&env, &[6; 32])
+    );
+    assert_eq!(second.next_cursor, None);
+
+    // Unknown listings have no history
+    let unknown = String::from_str(&env, "PROP2");
+    assert_eq!(
+        client.get_listing_history(&unknown, &0, &10).versions.len(),
+        0
+    );
+    assert_eq!(client.get_listing_version(&unknown), 0);
+}
+
+#[test]
+#[should_panic(expected = "Invalid limit")]
+fn test_listing_history_zero_limit() {
+    let env = Env::default();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    client.get_listing_history(&String::from_str(&env, "PROP1"), &0, &0);
+}
+
+#[test]
+fn test_migrate_seeds_listing_history() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[4; 32]), &owner);
+    client.update_listing(&id, &BytesN::from_array(&env, &[5; 32]), &owner);
+
+    // Roll storage back to the version 4 layout, which had no history
+    env.as_contract(&contract_id, || {
+        store_legacy_listing(&env, &id);
+        for version in 1u32..=2 {
+            env.storage()
+                .persistent()
+                .remove(&(LIST_VERSION, id.clone(), version));
+        }
+        env.storage()
+            .persistent()
+            .remove(&(LIST_VERSION_COUNT, id.clone()));
+        env.storage().instance().set(&VERSION, &4u32);
+    });
+    assert_eq!(client.get_listing_version(&id), 0);
+
+    // The current content becomes version 1
+    assert_eq!(client.migrate(&100), SCHEMA_VERSION);
+    let page = client.get_listing_history(&id, &0, &10);
+    assert_eq!(page.versions.len(), 1);
+    let version = page.versions.get(0).unwrap();
+    assert_eq!(version.version, 1);
+    assert_eq!(version.data_hash, BytesN::from_array(&env, &[5; 32]));
+    assert_eq!(version.updated_by, owner);
+}
+
+#[test]
+fn test_upgrade_to_listing_history() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    // Deploy the version 4 release and create a listing with it
+    let contract_id = env.register(property_listing_v4::WASM, ());
+    let old_client = property_listing_v4::Client::new(&env, &contract_id);
+    old_client.initialize(&Address::generate(&env));
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    old_client.create_listing(&id, &String::from_str(&env, "hash1"), &owner);
+
+    // Upgrade to the version 5 release and migrate the stored data
+    let new_wasm_hash = env
+        .deployer()
+        .upload_contract_wasm(property_listing_v5::WASM);
+    old_client.upgrade(&new_wasm_hash);
+
+    let client = property_listing_v5::Client::new(&env, &contract_id);
+    assert_eq!(client.migrate(), 5);
+    assert_eq!(client.get_listing_version(&id), 1);
+
+    client.update_listing(&id, &String::from_str(&env, "hash2"), &owner);
+    let page = client.get_listing_history(&id, &0, &10);
+    assert_eq!(page.versions.len(), 2);
+    assert_eq!(
+        page.versions.get(1).unwrap().data_hash,
+        String::from_str(&env, "hash2")
+    );
+}
+
+// =========================
+// Listing Removal
+// =========================
+#[test]
+fn test_archive_listing() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1000;
+    });
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    let id1 = String::from_str(&env, "PROP1");
+    let id2 = String::from_str(&env, "PROP2");
+    let id3 = String::from_str(&env, "PROP3");
+    client.create_listing(&id1, &data_hash, &owner);
+    client.create_listing(&id2, &data_hash, &owner);
+    client.create_listing(&id3, &data_hash, &owner);
+    client.set_metadata(
+        &id1,
+        &owner,
+        &ListingMetadata {
+            property_type: PropertyType::House,
+            bedrooms: 2,
+            bathrooms: 1,
+            max_guests: 4,
+            amenities: 0,
+            country_code: String::from_str(&env, "AR"),
+            region_code: String::from_str(&env, "C"),
+            geohash: String::from_str(&env, "69y7pkxf"),
+        },
+    );
+    let area = String::from_str(&env, "69y");
+    assert_eq!(client.search_by_geohash(&area, &0, &10).ids.len(), 1);
+
+    let listing = client.archive_listing(&id1);
+    assert_eq!(env.auths()[0].0, owner);
+    assert_eq!(listing.status, PropertyStatus::Inactive);
+
+    // Still readable, but off every index
+    assert_eq!(client.get_listing(&id1), Some(listing));
+    assert_eq!(client.listing_count(), 2);
+    let page = client.get_all_listings(&0, &10);
+    assert_eq!(page.listings.get(0).unwrap().id, id3);
+    assert_eq!(page.listings.get(1).unwrap().id, id2);
+    let owned = client.get_listings_by_owner(&owner, &0, &10);
+    assert_eq!(owned.listings.len(), 2);
+    assert_eq!(client.search_by_geohash(&area, &0, &10).ids.len(), 0);
+    assert_eq!(client.index_listings(&vec![&env, id1.clone()]), 0);
+
+    assert_eq!(
+        client.get_tombstone(&id1),
+        Some(ListingTombstone {
+            owner: owner.clone(),
+            deleted: false,
+            timestamp: 1000,
+        })
+    );
+    assert_eq!(client.get_tombstone(&id2), None);
+    assert_eq!(
+        client.get_status_history(&id1).get(0).unwrap().to,
+        PropertyStatus::Inactive
+    );
+    assert!(!client.has_permission(&id1, &owner, &PERM_UPDATE_DATA));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #5)")]
+fn test_archived_listing_is_read_only() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+    client.archive_listing(&id);
+
+    client.update_listing(&id, &BytesN::from_array(&env, &[2; 32]), &owner);
+}
+
+#[test]
+fn test_delete_listing() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let delegate = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+    client.grant_role(&id, &delegate, &ListingRole::Manager, &PERM_UPDATE_DATA);
+
+    client.delete_listing(&id);
+    assert_eq!(env.auths()[0].0, owner);
+
+    assert_eq!(client.get_listing(&id), None);
+    assert_eq!(client.get_delegates(&id).len(), 0);
+    assert_eq!(client.listing_count(), 0);
+    assert_eq!(
+        client.get_listings_by_owner(&owner, &0, &10).listings.len(),
+        0
+    );
+    assert!(client.get_tombstone(&id).unwrap().deleted);
+
+    // The content history stays available for existing bookings
+    assert_eq!(client.get_listing_version(&id), 1);
+}
+
+#[test]
+fn test_delete_archived_listing() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1000;
+    });
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+    client.archive_listing(&id);
+
+    env.ledger().with_mut(|li| {
+        li.timestamp = 2000;
+    });
+    client.delete_listing(&id);
+
+    assert_eq!(client.get_listing(&id), None);
+    assert_eq!(
+        client.get_tombstone(&id),
+        Some(ListingTombstone {
+            owner,
+            deleted: true,
+            timestamp: 1000,
+        })
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #5)")]
+fn test_removed_id_cannot_be_reused() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &Address::generate(&env));
+    client.delete_listing(&id);
+
+    client.create_listing(&id, &data_hash, &Address::generate(&env));
+}
+
+#[test]
+fn test_remove_listing_across_shards() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    for i in 0..INDEX_SHARD_SIZE + 1 {
+        let mut raw = *b"PROP000";
+        raw[4] += (i / 100) as u8;
+        raw[5] += (i / 10 % 10) as u8;
+        raw[6] += (i % 10) as u8;
+        client.create_listing(&String::from_bytes(&env, &raw), &data_hash, &owner);
+    }
+
+    // The last listing moves into the freed slot and its shard is dropped
+    client.delete_listing(&String::from_str(&env, "PROP000"));
+    assert_eq!(client.listing_count(), INDEX_SHARD_SIZE);
+    let page = client.get_all_listings(&0, &1);
+    assert_eq!(
+        page.listings.get(0).unwrap().id,
+        String::from_str(&env, "PROP100")
+    );
+    env.as_contract(&contract_id, || {
+        assert!(!env.storage().persistent().has(&(LIST_INDEX, 1u32)));
+        let position: u32 = env
+            .storage()
+            .persistent()
+            .get(&(LIST_POS, String::from_str(&env, "PROP100")))
+            .unwrap();
+        assert_eq!(position, 0);
+    });
+
+    // Removing the last entry only shrinks the index
+    client.archive_listing(&String::from_str(&env, "PROP099"));
+    assert_eq!(client.listing_count(), INDEX_SHARD_SIZE - 1);
+    let page = client.get_all_listings(&(INDEX_SHARD_SIZE - 2), &10);
+    assert_eq!(
+        page.listings.get(0).unwrap().id,
+        String::from_str(&env, "PROP098")
+    );
+    assert_eq!(page.next_cursor, None);
+}
+
+#[test]
+#[should_panic(expected = "Property listing has active bookings")]
+fn test_remove_listing_with_active_bookings() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let booking_id = env.register(MockBookingContract, ());
+    let booking = MockBookingContractClient::new(&env, &booking_id);
+    client.set_booking_contract(&booking_id);
+
+    let id = String::from_str(&env, "PROP1");
+    let other = String::from_str(&env, "PROP2");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    let owner = Address::generate(&env);
+    client.create_listing(&id, &data_hash, &owner);
+    client.create_listing(&other, &data_hash, &owner);
+    booking.set_active_bookings(&id, &true);
+
+    // Listings without upcoming stays can still be removed
+    client.archive_listing(&other);
+
+    client.delete_listing(&id);
+}
+
+#[test]
+fn test_remove_booked_listing() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let booking_id = env.register(MockBookingContract, ());
+    client.set_booking_contract(&booking_id);
+
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(
+        &id,
+        &BytesN::from_array(&env, &[1; 32]),
+        &Address::generate(&env),
+    );
+    client.set_occupancy(&id, &true);
+
+    assert_eq!(
+        client.try_archive_listing(&id),
+        Err(Ok(soroban_sdk::Error::from(
+            PropertyListingError::InvalidTransition
+        )))
+    );
+    assert_eq!(client.get_tombstone(&id), None);
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_archive_listing_requires_owner_auth() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(
+        &id,
+        &BytesN::from_array(&env, &[1; 32]),
+        &Address::generate(&env),
+    );
+
+    env.set_auths(&[]);
+    client.archive_listing(&id);
+}
+
+// =========================
+// Listing Ids and Data Hashes
+// =========================
+#[test]
+fn test_create_listing_validates_id() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    let invalid_id = Err(Ok(soroban_sdk::Error::from(
+        PropertyListingError::InvalidId,
+    )));
+    let too_long = String::from_bytes(&env, &[b'a'; MAX_ID_LEN as usize + 1]);
+    for id in [
+        String::from_str(&env, ""),
+        String::from_str(&env, "PROP 1"),
+        String::from_str(&env, "PROP/1"),
+        too_long,
+    ] {
+        assert_eq!(
+            client.try_create_listing(&id, &data_hash, &owner),
+            invalid_id
+        );
+    }
+
+    // Backend ids are UUIDs
+    let uuid = String::from_str(&env, "3f2b8c1e-9d4a-4e7b-8f6c-2a1d5e9b7c30");
+    client.create_listing(&uuid, &data_hash, &owner);
+    let longest = String::from_bytes(&env, &[b'a'; MAX_ID_LEN as usize]);
+    client.create_listing(&longest, &data_hash, &owner);
+    assert_eq!(client.listing_count(), 2);
+}
+
+#[test]
+fn test_listing_errors() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+
+    assert_eq!(
+        client.try_create_listing(&id, &data_hash, &owner),
+        Err(Ok(soroban_sdk::Error::from(
+            PropertyListingError::ListingAlreadyExists
+        )))
+    );
+    assert_eq!(
+        client.try_update_listing(&String::from_str(&env, "MISSING"), &data_hash, &owner),
+        Err(Ok(soroban_sdk::Error::from(
+            PropertyListingError::ListingNotFound
+        )))
+    );
+}
+
+#[test]
+fn test_migrate_converts_data_hashes() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id1 = String::from_str(&env, "PROP1");
+    let id2 = String::from_str(&env, "PROP2");
+    let first_hash = BytesN::from_array(&env, &[1; 32]);
+    let second_hash = BytesN::from_array(&env, &[2; 32]);
+    client.create_listing(&id1, &first_hash, &owner);
+    client.update_listing(&id1, &second_hash, &owner);
+    client.create_listing(&id2, &first_hash, &owner);
+
+    // Roll storage back to the version 5 layout. The backend wrote hex
+    // digests, but any string was accepted.
+    let free_form = String::from_str(&env, "hash123");
+    env.as_contract(&contract_id, || {
+        store_legacy_listing(&env, &id1);
+        store_legacy_listing(&env, &id2);
+        let key = (LISTINGS, id2.clone());
+        let mut legacy: LegacyPropertyListing = env.storage().persistent().get(&key).unwrap();
+        legacy.data_hash = free_form.clone();
+        env.storage().persistent().set(&key, &legacy);
+        env.storage().instance().set(&VERSION, &5u32);
+    });
+
+    assert_eq!(client.migrate(&100), SCHEMA_VERSION);
+
+    // Hex digests keep their value
+    assert_eq!(client.get_listing(&id1).unwrap().data_hash, second_hash);
+    let history = client.get_listing_history(&id1, &0, &10);
+    assert_eq!(history.versions.get(0).unwrap().data_hash, first_hash);
+    assert_eq!(history.versions.get(1).unwrap().data_hash, second_hash);
+
+    // Other strings are hashed
+    let expected: BytesN<32> = env.crypto().sha256(&free_form.to_xdr(&env)).into();
+    assert_eq!(client.get_listing(&id2).unwrap().data_hash, expected);
+}
+
+#[test]
+fn test_index_listings_converts_legacy_listing() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    // A listing from before the listing index, never backfilled
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[7; 32]);
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(
+            &(LISTINGS, id.clone()),
+            &LegacyPropertyListing {
+                id: id.clone(),
+                data_hash: to_hex(&env, &data_hash),
+                owner: owner.clone(),
+                status: PropertyStatus::Available,
+            },
+        );
+    });
+
+    assert_eq!(client.index_listings(&vec![&env, id.clone()]), 1);
+
+    let listing = client.get_listing(&id).unwrap();
+    assert_eq!(listing.data_hash, data_hash);
+    assert_eq!(listing.owner, owner);
+    assert_eq!(client.listing_count(), 1);
+    let history = client.get_listing_history(&id, &0, &10);
+    assert_eq!(history.versions.get(0).unwrap().data_hash, data_hash);
+}
+
+#[test]
+fn test_upgrade_to_byte_hashes() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    // Deploy the version 5 release and create a listing with a hex digest
+    let contract_id = env.register(property_listing_v5::WASM, ());
+    let old_client = property_listing_v5::Client::new(&env, &contract_id);
+    old_client.initialize(&Address::generate(&env));
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[0xab; 32]);
+    old_client.create_listing(&id, &to_hex(&env, &data_hash), &owner);
+
+    // Upgrade to the version 6 release and migrate the stored data
+    let new_wasm_hash = env
+        .deployer()
+        .upload_contract_wasm(property_listing_v6::WASM);
+    old_client.upgrade(&new_wasm_hash);
+
+    let client = property_listing_v6::Client::new(&env, &contract_id);
+    assert_eq!(client.migrate(&100), 6);
+    assert_eq!(client.get_listing(&id).unwrap().data_hash, data_hash);
+
+    let new_hash = BytesN::from_array(&env, &[0xcd; 32]);
+    client.update_listing(&id, &new_hash, &owner);
+    let history = client.get_listing_history(&id, &0, &10);
+    assert_eq!(history.versions.get(0).unwrap().data_hash, data_hash);
+    assert_eq!(history.versions.get(1).unwrap().data_hash, new_hash);
+}
+
+#[test]
+fn test_upgrade_converts_removed_listings() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    // Archive and delete listings on the version 5 release
+    let contract_id = env.register(property_listing_v5::WASM, ());
+    let old_client = property_listing_v5::Client::new(&env, &contract_id);
+    old_client.initialize(&Address::generate(&env));
+
+    let owner = Address::generate(&env);
+    let archived = String::from_str(&env, "PROP1");
+    let deleted = String::from_str(&env, "PROP2");
+    let data_hash = BytesN::from_array(&env, &[0xab; 32]);
+    let new_hash = BytesN::from_array(&env, &[0xcd; 32]);
+    old_client.create_listing(&archived, &to_hex(&env, &data_hash), &owner);
+    old_client.update_listing(&archived, &to_hex(&env, &new_hash), &owner);
+    old_client.create_listing(&deleted, &to_hex(&env, &data_hash), &owner);
+    old_client.archive_listing(&archived);
+    old_client.delete_listing(&deleted);
+
+    let new_wasm_hash = env
+        .deployer()
+        .upload_contract_wasm(property_listing_v6::WASM);
+    old_client.upgrade(&new_wasm_hash);
+
+    // Removed listings are converted when passed to the backfill, without
+    // joining the indexes
+    let client = property_listing_v6::Client::new(&env, &contract_id);
+    assert_eq!(client.migrate(&100), 6);
+    assert_eq!(
+        client.index_listings(&vec![&env, archived.clone(), deleted.clone()]),
+        0
+    );
+    assert_eq!(client.listing_count(), 0);
+
+    let listing = client.get_listing(&archived).unwrap();
+    assert_eq!(listing.data_hash, new_hash);
+    assert_eq!(
+        listing.status,
+        property_listing_v6::PropertyStatus::Inactive
+    );
+    let history = client.get_listing_history(&archived, &0, &10);
+    assert_eq!(history.versions.len(), 2);
+    assert_eq!(history.versions.get(0).unwrap().data_hash, data_hash);
+    assert_eq!(history.versions.get(1).unwrap().data_hash, new_hash);
+    let history = client.get_listing_history(&deleted, &0, &10);
+    assert_eq!(history.versions.get(0).unwrap().data_hash, data_hash);
+
+    // Converting again leaves the records as they are
+    client.index_listings(&vec![&env, archived.clone(), deleted.clone()]);
+    assert_eq!(client.get_listing(&archived).unwrap().data_hash, new_hash);
+
+    client.delete_listing(&archived);
+    assert_eq!(client.get_listing(&archived), None);
+}
+
+#[test]
+fn test_deploy_release_build() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    // The constructor of the release build sets the admin and the version
+    let admin = Address::generate(&env);
+    let contract_id = env.register(property_listing_v7::WASM, (admin.clone(),));
+    let client = property_listing_v7::Client::new(&env, &contract_id);
+    assert_eq!(client.version(), SCHEMA_VERSION);
+    assert_eq!(client.migrate(&10), SCHEMA_VERSION);
+
+    client.pause(&admin);
+    assert!(client.is_paused());
+}
+
+#[test]
+fn test_upgrade_to_sharded_indexes() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    // Deploy the version 6 release and create tagged, located listings with it
+    let contract_id = env.register(property_listing_v6::WASM, (Address::generate(&env),));
+    let old_client = property_listing_v6::Client::new(&env, &contract_id);
+    let beach = symbol_short!("beach");
+    old_client.add_tag(&beach);
+
+    let owner = Address::generate(&env);
+    let ids = [
+        String::from_str(&env, "PROP1"),
+        String::from_str(&env, "PROP2"),
+        String::from_str(&env, "PROP3"),
+    ];
+    for id in ids.iter() {
+        old_client.create_listing(id, &BytesN::from_array(&env, &[1; 32]), &owner);
+        old_client.set_metadata(
+            id,
+            &owner,
+            &property_listing_v6::ListingMetadata {
+                property_type: property_listing_v6::PropertyType::Villa,
+                bedrooms: 4,
+                bathrooms: 3,
+                max_guests: 8,
+                amenities: AMENITY_POOL,
+                country_code: String::from_str(&env, "AR"),
+                region_code: String::from_str(&env, "U"),
+                geohash: String::from_str(&env, "4wd8"),
+            },
+        );
+        old_client.set_listing_tags(id, &owner, &vec![&env, beach.clone()]);
+    }
+
+    // Upgrade to the current release and migrate in batches
+    let new_wasm_hash = env
+        .deployer()
+        .upload_contract_wasm(property_listing_v7::WASM);
+    old_client.upgrade(&new_wasm_hash);
+
+    let client = property_listing_v7::Client::new(&env, &contract_id);
+    assert_eq!(client.migrate(&2), 6);
+    assert_eq!(client.migrate(&2), SCHEMA_VERSION);
+
+    let page = client.get_listings_by_owner(&owner, &0, &10);
+    assert_eq!(page.listings.len(), 3);
+    let area = String::from_str(&env, "4wd");
+    assert_eq!(client.search_by_geohash(&area, &0, &10).ids.len(), 3);
+    assert_eq!(
+        client.get_listings_by_tag(&beach, &0, &10).listings.len(),
+        3
+    );
+
+    // The rebuilt indexes follow later updates
+    client.update_status(
+        &ids[0],
+        &owner,
+        &property_listing_v7::PropertyStatus::Inactive,
+    );
+    assert_eq!(client.search_by_geohash(&area, &0, &10).ids.len(), 2);
+    assert_eq!(
+        client.get_listings_by_tag(&beach, &0, &10).listings.len(),
+        2
+    );
+}
+
+// =========================
+// Listing Events
+// =========================
+#[test]
+fn test_listing_events() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let manager = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    let data_hash = BytesN::from_array(&env, &[1; 32]);
+    client.create_listing(&id, &data_hash, &owner);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (
+                    Symbol::new(&env, "property_created"),
+                    id.clone(),
+                    owner.clone()
+                )
+                    .into_val(&env),
+                data_hash.into_val(&env)
+            )
+        ]
+    );
+
+    // Updates by delegates are published under the owner
+    client.grant_role(&id, &manager, &ListingRole::Manager, &PERM_ALL);
+    let new_hash = BytesN::from_array(&env, &[2; 32]);
+    client.update_listing(&id, &new_hash, &manager);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (
+                    Symbol::new(&env, "property_updated"),
+                    id.clone(),
+                    owner.clone()
+                )
+                    .into_val(&env),
+                new_hash.into_val(&env)
+            )
+        ]
+    );
+
+    client.update_status(&id, &manager, &PropertyStatus::Maintenance);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (
+                    Symbol::new(&env, "property_status"),
+                    id.clone(),
+                    owner.clone()
+                )
+                    .into_val(&env),
+                PropertyStatus::Maintenance.into_val(&env)
+            )
+        ]
+    );
+}
+
+#[test]
+fn test_occupancy_events() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    client.set_booking_contract(&Address::generate(&env));
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+
+    client.set_occupancy(&id, &true);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (Symbol::new(&env, "property_status"), id, owner).into_val(&env),
+                PropertyStatus::Booked.into_val(&env)
+            )
+        ]
+    );
+}
+
+#[test]
+fn test_removal_events() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+
+    client.archive_listing(&id);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (
+                    Symbol::new(&env, "property_archived"),
+                    id.clone(),
+                    owner.clone()
+                )
+                    .into_val(&env),
+                PropertyStatus::Inactive.into_val(&env)
+            )
+        ]
+    );
+
+    client.delete_listing(&id);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (Symbol::new(&env, "property_deleted"), id, owner).into_val(&env),
+                ().into_val(&env)
+            )
+        ]
+    );
+}
+
+// =========================
+// Listing Attestations
+// =========================
+#[test]
+fn test_attest_listing() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1000;
+    });
+    let admin = Address::generate(&env);
+    let contract_id = env.register(PropertyListingContract, (&admin,));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let verifier = Address::generate(&env);
+    client.add_verifier(&verifier);
+    assert!(client.is_verifier(&verifier));
+
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(
+        &id,
+        &BytesN::from_array(&env, &[1; 32]),
+        &Address::generate(&env),
+    );
+    assert_eq!(client.get_attestations(&id).len(), 0);
+
+    let evidence_hash = BytesN::from_array(&env, &[2; 32]);
+    let attestation = client.attest(
+        &id,
+        &verifier,
+        &AttestationKind::Address,
+        &evidence_hash,
+        &5000,
+    );
+    assert_eq!(env.auths()[0].0, verifier);
+    assert_eq!(
+        attestation,
+        Attestation {
+            kind: AttestationKind::Address,
+            verifier: verifier.clone(),
+            evidence_hash,
+            issued_at: 1000,
+            expires_at: 5000,
+            revoked: false,
+        }
+    );
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("attested"), id.clone(), verifier.clone()).into_val(&env),
+                AttestationKind::Address.into_val(&env)
+            )
+        ]
+    );
+
+    client.attest(
+        &id,
+        &verifier,
+        &AttestationKind::Photos,
+        &BytesN::from_array(&env, &[3; 32]),
+        &5000,
+    );
+
+    // Attesting again replaces the attestation of the same kind
+    client.attest(
+        &id,
+        &verifier,
+        &AttestationKind::Address,
+        &BytesN::from_array(&env, &[4; 32]),
+        &9000,
+    );
+    let attestations = client.get_attestations(&id);
+    assert_eq!(attestations.len(), 2);
+    assert_eq!(attestations.get(0).unwrap().kind, AttestationKind::Address);
+    assert_eq!(attestations.get(0).unwrap().expires_at, 9000);
+    assert_eq!(attestations.get(1).unwrap().kind, AttestationKind::Photos);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized: only registered verifiers can attest")]
+fn test_attest_requires_verifier() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(PropertyListingContract, (&admin,));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let verifier = Address::generate(&env);
+    client.add_verifier(&verifier);
+    client.remove_verifier(&verifier);
+    assert!(!client.is_verifier(&verifier));
+
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(
+        &id,
+        &BytesN::from_array(&env, &[1; 32]),
+        &Address::generate(&env),
+    );
+    client.attest(
+        &id,
+        &verifier,
+        &AttestationKind::License,
+        &BytesN::from_array(&env, &[2; 32]),
+        &5000,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Invalid expiry: must be in the future")]
+fn test_attest_rejects_past_expiry() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1000;
+    });
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let verifier = Address::generate(&env);
+    client.add_verifier(&verifier);
+
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(
+        &id,
+        &BytesN::from_array(&env, &[1; 32]),
+        &Address::generate(&env),
+    );
+    client.attest(
+        &id,
+        &verifier,
+        &AttestationKind::Address,
+        &BytesN::from_array(&env, &[2; 32]),
+        &1000,
+    );
+}
+
+#[test]
+fn test_revoke_attestation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(PropertyListingContract, (&admin,));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let verifier = Address::generate(&env);
+    client.add_verifier(&verifier);
+
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(
+        &id,
+        &BytesN::from_array(&env, &[1; 32]),
+        &Address::generate(&env),
+    );
+    for kind in [AttestationKind::Address, AttestationKind::Photos] {
+        client.attest(
+            &id,
+            &verifier,
+            &kind,
+            &BytesN::from_array(&env, &[2; 32]),
+            &5000,
+        );
+    }
+
+    client.revoke_attestation(&id, &AttestationKind::Address, &verifier);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("att_revok"), id.clone(), verifier.clone()).into_val(&env),
+                AttestationKind::Address.into_val(&env)
+            )
+        ]
+    );
+
+    // The admin can revoke attestations of any verifier
+    client.revoke_attestation(&id, &AttestationKind::Photos, &admin);
+
+    let attestations = client.get_attestations(&id);
+    assert_eq!(attestations.len(), 2);
+    assert!(attestations.iter().all(|attestation| attestation.revoked));
+
+    let result = client.try_revoke_attestation(&id, &AttestationKind::Photos, &admin);
+    assert!(result.is_err());
+    let result = client.try_revoke_attestation(&id, &AttestationKind::License, &admin);
+    assert!(result.is_err());
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized: only the verifier or admin can revoke")]
+fn test_revoke_attestation_unauthorized() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let verifier = Address::generate(&env);
+    client.add_verifier(&verifier);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+    client.attest(
+        &id,
+        &verifier,
+        &AttestationKind::Address,
+        &BytesN::from_array(&env, &[2; 32]),
+        &5000,
+    );
+
+    // Owners cannot withdraw attestations about their own listing
+    client.revoke_attestation(&id, &AttestationKind::Address, &owner);
+}
+
+#[test]
+fn test_delete_listing_clears_attestations() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let verifier = Address::generate(&env);
+    client.add_verifier(&verifier);
+
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(
+        &id,
+        &BytesN::from_array(&env, &[1; 32]),
+        &Address::generate(&env),
+    );
+    client.attest(
+        &id,
+        &verifier,
+        &AttestationKind::Address,
+        &BytesN::from_array(&env, &[2; 32]),
+        &5000,
+    );
+
+    // Archived listings keep their attestations; deleted ones lose them
+    client.archive_listing(&id);
+    assert_eq!(client.get_attestations(&id).len(), 1);
+    client.delete_listing(&id);
+    assert_eq!(client.get_attestations(&id).len(), 0);
+}
+
+// =========================
+// Listing Compliance
+// =========================
+#[test]
+fn test_set_compliance() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let manager = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+    assert_eq!(client.get_compliance(&id), None);
+    assert_eq!(client.get_annual_night_cap(&id), None);
+
+    let compliance = ListingCompliance {
+        jurisdiction: String::from_str(&env, "ES-B-BCN"),
+        license_id: String::from_str(&env, "HUTB-012345"),
+        annual_night_cap: Some(90),
+    };
+    client.set_compliance(&id, &owner, &compliance);
+    assert_eq!(env.auths()[0].0, owner);
+    assert_eq!(client.get_compliance(&id), Some(compliance));
+    assert_eq!(client.get_annual_night_cap(&id), Some(90));
+
+    // Delegates allowed to update the listing data can update it too
+    client.grant_role(&id, &manager, &ListingRole::Manager, &PERM_UPDATE_DATA);
+    let compliance = ListingCompliance {
+        jurisdiction: String::from_str(&env, "US-NY"),
+        license_id: String::from_str(&env, "STR 2024/001"),
+        annual_night_cap: None,
+    };
+    client.set_compliance(&id, &manager, &compliance);
+    assert_eq!(client.get_annual_night_cap(&id), None);
+
+    client.delete_listing(&id);
+    assert_eq!(client.get_compliance(&id), None);
+}
+
+#[test]
+fn test_set_nightly_rate() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let manager = Address::generate(&env);
+    let editor = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+    assert_eq!(client.get_nightly_rate(&id), None);
+
+    client.set_nightly_rate(&id, &owner, &100);
+    assert_eq!(env.auths()[0].0, owner);
+    assert_eq!(client.get_nightly_rate(&id), Some(100));
+
+    // Pricing is granted separately from the listing data
+    client.grant_role(&id, &manager, &ListingRole::Manager, &PERM_UPDATE_PRICING);
+    client.grant_role(&id, &editor, &ListingRole::Manager, &PERM_UPDATE_DATA);
+    client.set_nightly_rate(&id, &manager, &120);
+    assert!(client.try_set_nightly_rate(&id, &editor, &80).is_err());
+    assert!(client.try_set_nightly_rate(&id, &owner, &0).is_err());
+    assert_eq!(client.get_nightly_rate(&id), Some(120));
+
+    client.delete_listing(&id);
+    assert_eq!(client.get_nightly_rate(&id), None);
+}
+
+#[test]
+#[should_panic(expected = "Only the owner can update the listing")]
+fn test_set_compliance_unauthorized() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+
+    client.set_compliance(
+        &id,
+        &Address::generate(&env),
+        &ListingCompliance {
+            jurisdiction: String::from_str(&env, "US-NY"),
+            license_id: String::from_str(&env, "STR-1"),
+            annual_night_cap: None,
+        },
+    );
+}
+
+#[test]
+fn test_set_compliance_validation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+
+    let valid = ListingCompliance {
+        jurisdiction: String::from_str(&env, "US-NY"),
+        license_id: String::from_str(&env, "STR-1"),
+        annual_night_cap: Some(120),
+    };
+    let invalid = [
+        ListingCompliance {
+            jurisdiction: String::from_str(&env, "U"),
+            ..valid.clone()
+        },
+        ListingCompliance {
+            jurisdiction: String::from_str(&env, "us-ny"),
+            ..valid.clone()
+        },
+        ListingCompliance {
+            jurisdiction: String::from_str(&env, "US-NY-NYC-MANH"),
+            ..valid.clone()
+        },
+        ListingCompliance {
+            license_id: String::from_str(&env, ""),
+            ..valid.clone()
+        },
+        ListingCompliance {
+            license_id: String::from_str(&env, "STR\n1"),
+            ..valid.clone()
+        },
+        ListingCompliance {
+            annual_night_cap: Some(0),
+            ..valid.clone()
+        },
+        ListingCompliance {
+            annual_night_cap: Some(367),
+            ..valid.clone()
+        },
+    ];
+    for compliance in invalid.iter() {
+        assert!(client.try_set_compliance(&id, &owner, compliance).is_err());
+    }
+    assert_eq!(client.get_compliance(&id), None);
+
+    client.set_compliance(&id, &owner, &valid);
+    assert_eq!(client.get_compliance(&id), Some(valid));
+}
+
+// =========================
+// Listing Bonds
+// =========================
+#[test]
+fn test_create_listing_locks_bond() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let token = create_token(&env, &owner, 1000);
+
+    // Without a bond config listings are created without a bond
+    let unbonded = String::from_str(&env, "PROP0");
+    client.create_listing(&unbonded, &BytesN::from_array(&env, &[1; 32]), &owner);
+    assert_eq!(client.get_bond(&unbonded), None);
+
+    client.set_bond_config(&token.address, &100, &3600);
+    assert_eq!(
+        client.get_bond_config(),
+        Some(BondConfig {
+            token: token.address.clone(),
+            amount: 100,
+            cooldown: 3600,
+        })
+    );
+
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+    assert_eq!(token.balance(&owner), 900);
+    assert_eq!(token.balance(&contract_id), 100);
+    assert_eq!(
+        client.get_bond(&id),
+        Some(ListingBond {
+            token: token.address.clone(),
+            amount: 100,
+            cooldown: 3600,
+            unlock_at: None,
+        })
+    );
+
+    // Changing the config does not affect existing bonds
+    client.set_bond_config(&token.address, &0, &0);
+    assert_eq!(client.get_bond_config(), None);
+    assert_eq!(client.get_bond(&id).unwrap().amount, 100);
+}
+
+#[test]
+fn test_withdraw_bond_after_cooldown() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1000;
+    });
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let token = create_token(&env, &owner, 1000);
+    client.set_bond_config(&token.address, &100, &3600);
+
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+    client.archive_listing(&id);
+    assert_eq!(client.get_bond(&id).unwrap().unlock_at, Some(4600));
+
+    // Deleting the listing keeps the running cooldown
+    env.ledger().with_mut(|li| {
+        li.timestamp = 2000;
+    });
+    client.delete_listing(&id);
+    assert_eq!(client.get_bond(&id).unwrap().unlock_at, Some(4600));
+    assert!(client.try_withdraw_bond(&id).is_err());
+
+    env.ledger().with_mut(|li| {
+        li.timestamp = 4600;
+    });
+    assert_eq!(client.withdraw_bond(&id), 100);
+    assert_eq!(env.auths()[0].0, owner);
+    assert_eq!(
+        vec![&env, env.events().all().last().unwrap()],
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("bond_rel"), id.clone(), owner.clone()).into_val(&env),
+                100i128.into_val(&env)
+            )
+        ]
+    );
+    assert_eq!(token.balance(&owner), 1000);
+    assert_eq!(client.get_bond(&id), None);
+    assert!(client.try_withdraw_bond(&id).is_err());
+}
+
+#[test]
+#[should_panic(expected = "Property listing is not archived")]
+fn test_withdraw_bond_of_active_listing() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let token = create_token(&env, &owner, 1000);
+    client.set_bond_config(&token.address, &100, &0);
+
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+    client.withdraw_bond(&id);
+}
+
+#[test]
+fn test_slash_bond() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(PropertyListingContract, (&admin,));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let arbitrator = Address::generate(&env);
+    client.set_arbitrator(&arbitrator);
+
+    let owner = Address::generate(&env);
+    let token = create_token(&env, &owner, 1000);
+    client.set_bond_config(&token.address, &100, &3600);
+
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+
+    let guest = Address::generate(&env);
+    client.slash_bond(&id, &arbitrator, &guest, &40);
+    assert_eq!(env.auths()[0].0, arbitrator);
+    assert_eq!(
+        vec![&env, env.events().all().last().unwrap()],
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("bond_slsh"), id.clone(), guest.clone()).into_val(&env),
+                40i128.into_val(&env)
+            )
+        ]
+    );
+    assert_eq!(token.balance(&guest), 40);
+    assert_eq!(client.get_bond(&id).unwrap().amount, 60);
+
+    // Owners cannot slash their own bond, nor more than what is left
+    assert!(client.try_slash_bond(&id, &owner, &owner, &10).is_err());
+    assert!(client.try_slash_bond(&id, &admin, &guest, &61).is_err());
+    assert!(client.try_slash_bond(&id, &admin, &guest, &0).is_err());
+
+    // The rest goes to the treasury, which leaves nothing to withdraw
+    let treasury = Address::generate(&env);
+    client.archive_listing(&id);
+    client.slash_bond(&id, &admin, &treasury, &60);
+    assert_eq!(token.balance(&treasury), 60);
+    assert_eq!(token.balance(&contract_id), 0);
+    assert_eq!(client.get_bond(&id), None);
+}
+
+// =========================
+// Revenue Shares
+// =========================
+#[test]
+fn test_set_revenue_shares() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let co_owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    assert_eq!(client.get_revenue_shares(&id).len(), 0);
+
+    // Listings without shares pay everything to the owner
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+    let owner_only = vec![
+        &env,
+        RevenueShare {
+            beneficiary: owner.clone(),
+            bps: TOTAL_SHARE_BPS,
+        },
+    ];
+    assert_eq!(client.get_revenue_shares(&id), owner_only);
+
+    let shares = vec![
+        &env,
+        RevenueShare {
+            beneficiary: owner.clone(),
+            bps: 7500,
+        },
+        RevenueShare {
+            beneficiary: co_owner.clone(),
+            bps: 2500,
+        },
+    ];
+    client.set_revenue_shares(&id, &shares);
+    assert_eq!(env.auths()[0].0, owner);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("rev_share"), id.clone(), owner.clone()).into_val(&env),
+                shares.into_val(&env)
+            )
+        ]
+    );
+    assert_eq!(client.get_revenue_shares(&id), shares);
+
+    client.set_revenue_shares(&id, &vec![&env]);
+    assert_eq!(client.get_revenue_shares(&id), owner_only);
+
+    client.set_revenue_shares(&id, &shares);
+    client.delete_listing(&id);
+    assert_eq!(client.get_revenue_shares(&id).len(), 0);
+}
+
+#[test]
+fn test_accept_transfer_moves_revenue_share() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let co_owner = Address::generate(&env);
+    let new_owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+    client.set_revenue_shares(
+        &id,
+        &vec![
+            &env,
+            RevenueShare {
+                beneficiary: owner.clone(),
+                bps: 6000,
+            },
+            RevenueShare {
+                beneficiary: co_owner.clone(),
+                bps: 4000,
+            },
+        ],
+    );
+
+    client.propose_transfer(&id, &new_owner);
+    client.accept_transfer(&id);
+
+    // The new owner receives the previous owner's part of the payouts
+    assert_eq!(
+        client.get_revenue_shares(&id),
+        vec![
+            &env,
+            RevenueShare {
+                beneficiary: new_owner,
+                bps: 6000,
+            },
+            RevenueShare {
+                beneficiary: co_owner,
+                bps: 4000,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_set_revenue_shares_validation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let co_owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+
+    let share = |beneficiary: &Address, bps: u32| RevenueShare {
+        beneficiary: beneficiary.clone(),
+        bps,
+    };
+    let mut too_many = vec![&env];
+    for _ in 0..MAX_BENEFICIARIES + 1 {
+        too_many.push_back(share(&Address::generate(&env), 1));
+    }
+    let invalid = [
+        vec![&env, share(&owner, 5000), share(&co_owner, 4999)],
+        vec![&env, share(&owner, 10000), share(&co_owner, 0)],
+        vec![&env, share(&owner, 5000), share(&owner, 5000)],
+        vec![&env, share(&owner, 10001)],
+        too_many,
+    ];
+    for shares in invalid.iter() {
+        assert!(client.try_set_revenue_shares(&id, shares).is_err());
+    }
+    assert_eq!(client.get_revenue_shares(&id).len(), 1);
+}
+
+// =========================
+// Listing Tokens
+// =========================
+#[test]
+fn test_tokenize_listing() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+    client.create_listing(
+        &String::from_str(&env, "PROP2"),
+        &BytesN::from_array(&env, &[2; 32]),
+        &owner,
+    );
+    client.propose_transfer(&id, &Address::generate(&env));
+    assert!(!client.is_tokenized(&id));
+    assert!(client.try_owner_of(&id).is_err());
+
+    client.tokenize_listing(&id);
+    assert_eq!(env.auths()[0].0, owner);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("mint"), id.clone(), owner.clone()).into_val(&env),
+                ().into_val(&env)
+            )
+        ]
+    );
+    assert!(client.is_tokenized(&id));
+    assert_eq!(client.owner_of(&id), owner);
+    assert_eq!(client.balance(&owner), 1);
+
+    // Tokenized listings only change hands as tokens
+    assert_eq!(client.get_pending_transfer(&id), None);
+    assert!(client
+        .try_propose_transfer(&id, &Address::generate(&env))
+        .is_err());
+    assert!(client.try_tokenize_listing(&id).is_err());
+
+    // Deleting the listing burns its token
+    client.delete_listing(&id);
+    assert!(!client.is_tokenized(&id));
+    assert_eq!(client.balance(&owner), 0);
+}
+
+#[test]
+fn test_transfer_listing_token() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let co_owner = Address::generate(&env);
+    let manager = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+    client.grant_role(&id, &manager, &ListingRole::Manager, &PERM_ALL);
+    client.set_revenue_shares(
+        &id,
+        &vec![
+            &env,
+            RevenueShare {
+                beneficiary: owner.clone(),
+                bps: 7000,
+            },
+            RevenueShare {
+                beneficiary: co_owner.clone(),
+                bps: 3000,
+            },
+        ],
+    );
+    client.tokenize_listing(&id);
+
+    client.transfer(&owner, &buyer, &id);
+    assert_eq!(env.auths()[0].0, owner);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (
+                    symbol_short!("transfer"),
+                    id.clone(),
+                    owner.clone(),
+                    buyer.clone()
+                )
+                    .into_val(&env),
+                ().into_val(&env)
+            )
+        ]
+    );
+
+    // The token holder controls the listing and receives the owner's share
+    assert_eq!(client.owner_of(&id), buyer);
+    assert_eq!(client.get_listing(&id).unwrap().owner, buyer);
+    assert!(client.has_permission(&id, &buyer, &PERM_ALL));
+    assert!(!client.has_permission(&id, &owner, &PERM_UPDATE_DATA));
+    assert!(!client.has_permission(&id, &manager, &PERM_UPDATE_DATA));
+    assert_eq!(client.balance(&owner), 0);
+    assert_eq!(client.balance(&buyer), 1);
+    assert_eq!(
+        client.get_revenue_shares(&id),
+        vec![
+            &env,
+            RevenueShare {
+                beneficiary: buyer.clone(),
+                bps: 7000,
+            },
+            RevenueShare {
+                beneficiary: co_owner.clone(),
+                bps: 3000,
+            },
+        ]
+    );
+
+    client.update_listing(&id, &BytesN::from_array(&env, &[2; 32]), &buyer);
+    assert_eq!(env.auths()[0].0, buyer);
+
+    // A co-owner buying the token merges both shares
+    client.transfer(&buyer, &co_owner, &id);
+    assert_eq!(
+        client.get_revenue_shares(&id),
+        vec![
+            &env,
+            RevenueShare {
+                beneficiary: co_owner,
+                bps: TOTAL_SHARE_BPS,
+            },
+        ]
+    );
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized: only the token owner can transfer")]
+fn test_transfer_listing_token_unauthorized() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let attacker = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+    client.tokenize_listing(&id);
+
+    client.transfer(&attacker, &attacker, &id);
+}
+
+#[test]
+fn test_approve_and_transfer_from() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.sequence_number = 100;
+    });
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let marketplace = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+    client.tokenize_listing(&id);
+    assert_eq!(client.get_approved(&id), None);
+    assert!(client
+        .try_transfer_from(&marketplace, &owner, &buyer, &id)
+        .is_err());
+
+    client.approve(&id, &marketplace, &200);
+    assert_eq!(env.auths()[0].0, owner);
+    assert_eq!(client.get_approved(&id), Some(marketplace.clone()));
+
+    // Approvals expire after their last ledger
+    env.ledger().with_mut(|li| {
+        li.sequence_number = 201;
+    });
+    assert_eq!(client.get_approved(&id), None);
+    assert!(client
+        .try_transfer_from(&marketplace, &owner, &buyer, &id)
+        .is_err());
+
+    client.approve(&id, &marketplace, &300);
+    client.transfer_from(&marketplace, &owner, &buyer, &id);
+    assert_eq!(env.auths()[0].0, marketplace);
+    assert_eq!(client.owner_of(&id), buyer);
+
+    // The approval does not survive the transfer
+    assert_eq!(client.get_approved(&id), None);
+
+    // Approving with a past ledger revokes the approval
+    client.approve(&id, &marketplace, &300);
+    client.approve(&id, &marketplace, &0);
+    assert_eq!(client.get_approved(&id), None);
+}
+
+// =========================
+// Batch Operations
+// =========================
+// Listings PROP0..PROPn to create in a batch
+fn new_listings(env: &Env, count: u32) -> Vec<NewListing> {
+    let ids = [
+        "PROP0", "PROP1", "PROP2", "PROP3", "PROP4", "PROP5", "PROP6", "PROP7", "PROP8",
+    ];
+    let mut listings = vec![env];
+    for (i, id) in ids.iter().take(count as usize).enumerate() {
+        listings.push_back(NewListing {
+            id: String::from_str(env, id),
+            data_hash: BytesN::from_array(env, &[i as u8; 32]),
+        });
+    }
+    listings
+}
+
+#[test]
+fn test_create_listings_batch() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let listings = new_listings(&env, MAX_BATCH_SIZE);
+    assert_eq!(client.create_listings_batch(&owner, &listings).len(), 0);
+
+    // The owner signs once for the whole batch
+    assert_eq!(env.auths().len(), 1);
+    assert_eq!(env.auths()[0].0, owner);
+    assert_eq!(env.events().all().len(), MAX_BATCH_SIZE);
+
+    assert_eq!(client.listing_count(), MAX_BATCH_SIZE);
+    for item in listings.iter() {
+        let listing = client.get_listing(&item.id).unwrap();
+        assert_eq!(listing.owner, owner);
+        assert_eq!(listing.data_hash, item.data_hash);
+        assert_eq!(client.get_listing_version(&item.id), 1);
+    }
+    assert_eq!(
+        client
+            .get_listings_by_owner(&owner, &0, &MAX_PAGE_SIZE)
+            .listings
+            .len(),
+        MAX_BATCH_SIZE
+    );
+}
+
+#[test]
+fn test_create_listings_batch_fits_write_limit() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    // Bonds add the most writes per listing
+    let owner = Address::generate(&env);
+    let token = create_token(&env, &owner, 1000);
+    client.set_bond_config(&token.address, &10, &0);
+
+    client.create_listings_batch(&owner, &new_listings(&env, MAX_BATCH_SIZE));
+    assert!(env.cost_estimate().resources().write_entries <= 50);
+}
+
+#[test]
+fn test_create_listings_batch_reports_errors() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let existing = String::from_str(&env, "PROP1");
+    let deleted = String::from_str(&env, "PROP3");
+    client.create_listing(&existing, &BytesN::from_array(&env, &[1; 32]), &owner);
+    client.create_listing(&deleted, &BytesN::from_array(&env, &[3; 32]), &owner);
+    client.delete_listing(&deleted);
+
+    let mut listings = new_listings(&env, 5);
+    listings.set(
+        2,
+        NewListing {
+            id: String::from_str(&env, "PROP 2"),
+            data_hash: BytesN::from_array(&env, &[2; 32]),
+        },
+    );
+    listings.push_back(listings.get(0).unwrap());
+
+    assert_eq!(
+        client.create_listings_batch(&owner, &listings),
+        vec![
+            &env,
+            BatchItemError {
+                index: 1,
+                error: PropertyListingError::ListingAlreadyExists as u32,
+            },
+            BatchItemError {
+                index: 2,
+                error: PropertyListingError::InvalidId as u32,
+            },
+            BatchItemError {
+                index: 3,
+                error: PropertyListingError::ListingRemoved as u32,
+            },
+            BatchItemError {
+                index: 5,
+                error: PropertyListingError::DuplicateItem as u32,
+            },
+        ]
+    );
+
+    // Nothing is created when any item is rejected
+    assert_eq!(client.listing_count(), 1);
+    assert_eq!(client.get_listing(&String::from_str(&env, "PROP0")), None);
+    assert_eq!(client.get_listing(&String::from_str(&env, "PROP4")), None);
+}
+
+#[test]
+fn test_batch_size_limit() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let result = client.try_create_listings_batch(&owner, &new_listings(&env, MAX_BATCH_SIZE + 1));
+    assert_eq!(
+        result,
+        Err(Ok(soroban_sdk::Error::from(
+            PropertyListingError::BatchTooLarge
+        )))
+    );
+
+    let mut updates = vec![&env];
+    for _ in 0..MAX_BATCH_SIZE + 1 {
+        updates.push_back(StatusUpdate {
+            id: String::from_str(&env, "PROP0"),
+            status: PropertyStatus::Maintenance,
+        });
+    }
+    let result = client.try_update_status_batch(&owner, &updates);
+    assert_eq!(
+        result,
+        Err(Ok(soroban_sdk::Error::from(
+            PropertyListingError::BatchTooLarge
+        )))
+    );
+}
+
+#[test]
+fn test_update_status_batch() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let manager = Address::generate(&env);
+    let listings = new_listings(&env, 3);
+    client.create_listings_batch(&owner, &listings);
+    for item in listings.iter() {
+        client.grant_role(
+            &item.id,
+            &manager,
+            &ListingRole::Manager,
+            &PERM_UPDATE_STATUS,
+        );
+    }
+
+    let updates = vec![
+        &env,
+        StatusUpdate {
+            id: listings.get(0).unwrap().id,
+            status: PropertyStatus::Maintenance,
+        },
+        StatusUpdate {
+            id: listings.get(1).unwrap().id,
+            status: PropertyStatus::Inactive,
+        },
+    ];
+    assert_eq!(client.update_status_batch(&manager, &updates).len(), 0);
+    assert_eq!(env.auths().len(), 1);
+    assert_eq!(env.auths()[0].0, manager);
+    for update in updates.iter() {
+        assert_eq!(
+            client.get_listing(&update.id).unwrap().status,
+            update.status
+        );
+        assert_eq!(client.get_status_history(&update.id).len(), 1);
+    }
+}
+
+#[test]
+fn test_update_status_batch_reports_errors() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let listings = new_listings(&env, 4);
+    client.create_listings_batch(&owner, &listings);
+    client.archive_listing(&listings.get(3).unwrap().id);
+    client.create_listing(
+        &String::from_str(&env, "OTHER"),
+        &BytesN::from_array(&env, &[9; 32]),
+        &Address::generate(&env),
+    );
+
+    let update = |id: &str, status: PropertyStatus| StatusUpdate {
+        id: String::from_str(&env, id),
+        status,
+    };
+    let updates = vec![
+        &env,
+        update("PROP0", PropertyStatus::Maintenance),
+        update("MISSING", PropertyStatus::Maintenance),
+        update("PROP1", PropertyStatus::Booked),
+        update("OTHER", PropertyStatus::Maintenance),
+        update("PROP3", PropertyStatus::Available),
+        update("PROP0", PropertyStatus::Inactive),
+        update("PROP2", PropertyStatus::Inactive),
+    ];
+    assert_eq!(
+        client.update_status_batch(&owner, &updates),
+        vec![
+            &env,
+            BatchItemError {
+                index: 1,
+                error: PropertyListingError::ListingNotFound as u32,
+            },
+            BatchItemError {
+                index: 2,
+                error: PropertyListingError::InvalidTransition as u32,
+            },
+            BatchItemError {
+                index: 3,
+                error: PropertyListingError::Unauthorized as u32,
+            },
+            BatchItemError {
+                index: 4,
+                error: PropertyListingError::ListingRemoved as u32,
+            },
+            BatchItemError {
+                index: 5,
+                error: PropertyListingError::DuplicateItem as u32,
+            },
+        ]
+    );
+
+    // No status changes when any item is rejected
+    for id in ["PROP0", "PROP2"] {
+        let id = String::from_str(&env, id);
+        assert_eq!(
+            client.get_listing(&id).unwrap().status,
+            PropertyStatus::Available
+        );
+        assert_eq!(client.get_status_history(&id).len(), 0);
+    }
+}
+
+#[test]
+fn test_update_status_batch_fits_write_limit() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    // Reactivating a listing writes its slot in every location bucket and
+    // tag index, so give each listing its own area and tags
+    let owner = Address::generate(&env);
+    let listings = new_listings(&env, 4);
+    client.create_listings_batch(&owner, &listings);
+    let geohashes = ["69y7pkxf", "6gkzwgjz", "u4pruydq", "dr5regw3"];
+    for (i, item) in listings.iter().enumerate() {
+        let mut tags = vec![&env];
+        for j in 0..MAX_TAGS_PER_LISTING {
+            let name = [b'a' + i as u8, b'0' + j as u8];
+            let tag = Symbol::new(&env, core::str::from_utf8(&name).unwrap());
+            client.add_tag(&tag);
+            tags.push_back(tag);
+        }
+        client.set_listing_tags(&item.id, &owner, &tags);
+        client.set_metadata(
+            &item.id,
+            &owner,
+            &ListingMetadata {
+                property_type: PropertyType::Apartment,
+                bedrooms: 1,
+                bathrooms: 1,
+                max_guests: 2,
+                amenities: 0,
+                country_code: String::from_str(&env, "AR"),
+                region_code: String::from_str(&env, "C"),
+                geohash: String::from_str(&env, geohashes[i]),
+            },
+        );
+        client.update_status(&item.id, &owner, &PropertyStatus::Inactive);
+    }
+
+    let mut updates = vec![&env];
+    for item in listings.iter() {
+        updates.push_back(StatusUpdate {
+            id: item.id,
+            status: PropertyStatus::Available,
+        });
+    }
+    let result = client.try_update_status_batch(&owner, &updates);
+    assert_eq!(
+        result,
+        Err(Ok(soroban_sdk::Error::from(
+            PropertyListingError::BatchTooLarge
+        )))
+    );
+
+    updates.pop_back();
+    assert_eq!(client.update_status_batch(&owner, &updates).len(), 0);
+    assert!(env.cost_estimate().resources().write_entries <= 50);
+    assert_eq!(
+        client
+            .get_listings_by_tag(&symbol_short!("a0"), &0, &10)
+            .listings
+            .len(),
+        1
+    );
+}
+
+// =========================
+// Listing Tags
+// =========================
+#[test]
+fn test_tag_vocabulary() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(PropertyListingContract, (&admin,));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    assert_eq!(client.get_tag_vocabulary().len(), 0);
+
+    client.add_tag(&symbol_short!("beach"));
+    assert_eq!(env.auths()[0].0, admin);
+    client.add_tag(&symbol_short!("pets"));
+    client.add_tag(&symbol_short!("wifi"));
+    assert_eq!(
+        client.get_tag_vocabulary(),
+        vec![
+            &env,
+            symbol_short!("beach"),
+            symbol_short!("pets"),
+            symbol_short!("wifi")
+        ]
+    );
+
+    client.remove_tag(&symbol_short!("pets"));
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(
+        client.get_tag_vocabulary(),
+        vec![&env, symbol_short!("beach"), symbol_short!("wifi")]
+    );
+}
+
+#[test]
+#[should_panic(expected = "Tag already exists")]
+fn test_add_duplicate_tag() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    client.add_tag(&symbol_short!("beach"));
+    client.add_tag(&symbol_short!("beach"));
+}
+
+#[test]
+#[should_panic(expected = "Tag not found")]
+fn test_remove_unknown_tag() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    client.remove_tag(&symbol_short!("beach"));
+}
+
+#[test]
+fn test_set_listing_tags() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+    client.add_tag(&symbol_short!("beach"));
+    client.add_tag(&symbol_short!("pets"));
+    client.add_tag(&symbol_short!("wifi"));
+
+    let owner = Address::generate(&env);
+    let manager = Address::generate(&env);
+    let id1 = String::from_str(&env, "PROP1");
+    let id2 = String::from_str(&env, "PROP2");
+    client.create_listing(&id1, &BytesN::from_array(&env, &[1; 32]), &owner);
+    client.create_listing(&id2, &BytesN::from_array(&env, &[2; 32]), &owner);
+    assert_eq!(client.get_listing_tags(&id1).len(), 0);
+
+    let tags = vec![&env, symbol_short!("beach"), symbol_short!("pets")];
+    client.set_listing_tags(&id1, &owner, &tags);
+    assert_eq!(env.auths()[0].0, owner);
+    assert_eq!(client.get_listing_tags(&id1), tags);
+
+    // Delegates allowed to update the listing data can set tags too
+    client.grant_role(&id2, &manager, &ListingRole::Manager, &PERM_UPDATE_DATA);
+    client.set_listing_tags(&id2, &manager, &vec![&env, symbol_short!("beach")]);
+
+    let page = client.get_listings_by_tag(&symbol_short!("beach"), &0, &10);
+    assert_eq!(page.listings.len(), 2);
+    assert_eq!(page.listings.get(0).unwrap().id, id1);
+    assert_eq!(page.listings.get(1).unwrap().id, id2);
+    assert_eq!(page.next_cursor, None);
+
+    // Replacing the tags moves the listing between indexes
+    client.set_listing_tags(&id1, &owner, &vec![&env, symbol_short!("wifi")]);
+    let page = client.get_listings_by_tag(&symbol_short!("beach"), &0, &10);
+    assert_eq!(page.listings.len(), 1);
+    assert_eq!(page.listings.get(0).unwrap().id, id2);
+    assert_eq!(
+        client
+            .get_listings_by_tag(&symbol_short!("pets"), &0, &10)
+            .listings
+            .len(),
+        0
+    );
+    assert_eq!(
+        client
+            .get_listings_by_tag(&symbol_short!("wifi"), &0, &10)
+            .listings
+            .get(0)
+            .unwrap()
+            .id,
+        id1
+    );
+
+    // Removing a tag from the vocabulary keeps it on existing listings
+    client.remove_tag(&symbol_short!("wifi"));
+    assert_eq!(
+        client.get_listing_tags(&id1),
+        vec![&env, symbol_short!("wifi")]
+    );
+}
+
+#[test]
+fn test_tag_index_spans_shards() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+    let beach = symbol_short!("beach");
+    client.add_tag(&beach);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+
+    // Fill the first shard of the index with emptied slots
+    let scope = (TAG_INDEX, beach.clone());
+    env.as_contract(&contract_id, || {
+        let mut shard = vec![&env];
+        for _ in 0..INDEX_SHARD_SIZE {
+            shard.push_back(String::from_str(&env, ""));
+        }
+        env.storage()
+            .persistent()
+            .set(&(scope.clone(), 0u32), &shard);
+        env.storage().persistent().set(&scope, &INDEX_SHARD_SIZE);
+    });
+
+    client.set_listing_tags(&id, &owner, &vec![&env, beach.clone()]);
+    let page = client.get_listings_by_tag(&beach, &0, &10);
+    assert_eq!(page.listings.len(), 1);
+    assert_eq!(page.listings.get(0).unwrap().id, id);
+    assert_eq!(page.next_cursor, None);
+
+    // A listing going back to its slot is never refused
+    client.update_status(&id, &owner, &PropertyStatus::Inactive);
+    assert_eq!(
+        client.get_listings_by_tag(&beach, &0, &10).listings.len(),
+        0
+    );
+    client.update_status(&id, &owner, &PropertyStatus::Available);
+    assert_eq!(
+        client.get_listings_by_tag(&beach, &0, &10).listings.len(),
+        1
+    );
+    env.as_contract(&contract_id, || {
+        let count: u32 = env.storage().persistent().get(&scope).unwrap();
+        assert_eq!(count, INDEX_SHARD_SIZE + 1);
+    });
+}
+
+#[test]
+fn test_migrate_builds_tag_index() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+    let beach = symbol_short!("beach");
+    client.add_tag(&beach);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+    client.set_listing_tags(&id, &owner, &vec![&env, beach.clone()]);
+
+    // Roll storage back to the version 6 layout, which kept the index of a
+    // tag in a single entry
+    env.as_contract(&contract_id, || {
+        let scope = (TAG_INDEX, beach.clone());
+        env.storage().persistent().remove(&(scope.clone(), 0u32));
+        env.storage()
+            .persistent()
+            .remove(&(scope.clone(), id.clone()));
+        env.storage().persistent().remove(&scope);
+        env.storage().instance().set(&VERSION, &6u32);
+    });
+    assert_eq!(
+        client.get_listings_by_tag(&beach, &0, &10).listings.len(),
+        0
+    );
+
+    assert_eq!(client.migrate(&100), SCHEMA_VERSION);
+    let page = client.get_listings_by_tag(&beach, &0, &10);
+    assert_eq!(page.listings.len(), 1);
+    assert_eq!(page.listings.get(0).unwrap().id, id);
+}
+
+#[test]
+#[should_panic(expected = "Only the owner can update the listing")]
+fn test_set_listing_tags_unauthorized() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+    client.add_tag(&symbol_short!("beach"));
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+
+    client.set_listing_tags(
+        &id,
+        &Address::generate(&env),
+        &vec![&env, symbol_short!("beach")],
+    );
+}
+
+#[test]
+fn test_set_listing_tags_validation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let id = String::from_str(&env, "PROP1");
+    client.create_listing(&id, &BytesN::from_array(&env, &[1; 32]), &owner);
+
+    let mut tags = vec![&env];
+    let names = [
+        "tag0", "tag1", "tag2", "tag3", "tag4", "tag5", "tag6", "tag7", "tag8", "tag9", "tag10",
+    ];
+    for name in names.iter().take(MAX_TAGS_PER_LISTING as usize + 1) {
+        let tag = Symbol::new(&env, name);
+        client.add_tag(&tag);
+        tags.push_back(tag);
+    }
+    assert!(client.try_set_listing_tags(&id, &owner, &tags).is_err());
+
+    let beach = symbol_short!("beach");
+    assert!(client
+        .try_set_listing_tags(&id, &owner, &vec![&env, beach.clone()])
+        .is_err());
+
+    client.add_tag(&beach);
+    assert!(client
+        .try_set_listing_tags(&id, &owner, &vec![&env, beach.clone(), beach.clone()])
+        .is_err());
+
+    assert_eq!(client.get_listing_tags(&id).len(), 0);
+}
+
+#[test]
+fn test_listings_by_tag_excludes_inactive() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+    let beach = symbol_short!("beach");
+    client.add_tag(&beach);
+
+    let owner = Address::generate(&env);
+    let ids = ["PROP1", "PROP2", "PROP3"].map(|id| String::from_str(&env, id));
+    for (i, id) in ids.iter().enumerate() {
+        client.create_listing(id, &BytesN::from_array(&env, &[i as u8; 32]), &owner);
+        client.set_listing_tags(id, &owner, &vec![&env, beach.clone()]);
+    }
+
+    // Inactive listings leave the index and rejoin it when reactivated
+    client.update_status(&ids[0], &owner, &PropertyStatus::Inactive);
+    let page = client.get_listings_by_tag(&beach, &0, &10);
+    assert_eq!(page.listings.len(), 2);
+    assert_eq!(page.listings.get(0).unwrap().id, ids[1]);
+    assert_eq!(page.listings.get(1).unwrap().id, ids[2]);
+
+    // Tags set while inactive are indexed once the listing is reactivated
+    client.set_listing_tags(&ids[0], &owner, &vec![&env, beach.clone()]);
+    assert_eq!(
+        client.get_listings_by_tag(&beach, &0, &10).listings.len(),
+        2
+    );
+    client.update_status(&ids[0], &owner, &PropertyStatus::Maintenance);
+    assert_eq!(
+        client.get_listings_by_tag(&beach, &0, &10).listings.len(),
+        3
+    );
+
+    // Archived and deleted listings leave the index
+    client.archive_listing(&ids[1]);
+    client.delete_listing(&ids[2]);
+    let page = client.get_listings_by_tag(&beach, &0, &10);
+    assert_eq!(page.listings.len(), 1);
+    assert_eq!(page.listings.get(0).unwrap().id, ids[0]);
+    assert_eq!(client.get_listing_tags(&ids[2]).len(), 0);
+}
+
+#[test]
+fn test_listings_by_tag_pagination() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PropertyListingContract, (Address::generate(&env),));
+    let client = PropertyListingContractClient::new(&env, &contract_id);
+    let beach = symbol_short!("beach");
+    client.add_tag(&beach);
+
+    let owner = Address::generate(&env);
+    let listings = new_listings(&env, 5);
+    client.create_listings_batch(&owner, &listings);
+    for item in listings.iter() {
+        client.set_listing_tags(&item.id, &owner, &vec![&env, beach.clone()]);
+    }
+
+    let page = client.get_listings_by_tag(&beach, &0, &2);
+    assert_eq!(page.listings.len(), 2);
+    assert_eq!(page.next_cursor, Some(2));
+    let page = client.get_listings_by_tag(&beach, &4, &2);
+    assert_eq!(page.listings.len(), 1);
+    assert_eq!(
+        page.listings.get(0).unwrap().id,
+        listings.get(4).unwrap().id
+    );
+    assert_eq!(page.next_cursor, None);
+}
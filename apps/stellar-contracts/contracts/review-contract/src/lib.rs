@@ -1,6 +1,7 @@
 #![no_std]
 use soroban_sdk::{
-    contract, contracterror, contractimpl, contracttype, symbol_short, Env, Map, String, Symbol, Vec,
+    contract, contracterror, contractimpl, contracttype, symbol_short, Address, BytesN, Env, Map,
+    String, Symbol, Vec,
 };
 
 #[contracttype]
@@ -32,8 +33,28 @@ pub enum StorageKey {
     Review(Symbol),
     ReputationScoresMap(Symbol),
     ReviewMap(Symbol),
+    Admin,
+    Version,
+    Guardian,
+    Paused,
 }
 
+/// Version of the storage layout written by this code. Bump it together with
+/// a new arm in `migrate` whenever the layout of stored data changes.
+pub const SCHEMA_VERSION: u32 = 1;
+
+// Storage TTL policy. Ledgers close roughly every 5 seconds, so one day is
+// about 17280 ledgers. Entries are bumped back to the full bump amount
+// whenever they are read or written and their remaining TTL has dropped
+// below the threshold.
+pub const DAY_IN_LEDGERS: u32 = 17_280;
+/// Instance storage (admin and contract config) is kept alive for 30 days.
+pub const INSTANCE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+/// Persistent entries (reviews and reputation scores) are kept alive for 90 days.
+pub const PERSISTENT_BUMP_AMOUNT: u32 = 90 * DAY_IN_LEDGERS;
+pub const PERSISTENT_LIFETIME_THRESHOLD: u32 = PERSISTENT_BUMP_AMOUNT - 7 * DAY_IN_LEDGERS;
+
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 #[repr(u32)]
@@ -42,6 +63,10 @@ pub enum ReviewError {
     DuplicateReview = 2,
     UnauthorizedReviewer = 3,
     InvalidInput = 4,
+    NotInitialized = 6,
+    UnsupportedSchemaVersion = 7,
+    ContractPaused = 8,
+    Unauthorized = 9,
 }
 
 #[contract]
@@ -49,6 +74,14 @@ pub struct ReviewContract;
 
 #[contractimpl]
 impl ReviewContract {
+    pub fn __constructor(env: &Env, admin: Address) {
+        env.storage().instance().set(&StorageKey::Admin, &admin);
+        env.storage()
+            .instance()
+            .set(&StorageKey::Version, &SCHEMA_VERSION);
+        Self::extend_instance_ttl(env);
+    }
+
     pub fn submit_review(
         env: &Env,
         booking_id: Symbol,
@@ -57,10 +90,11 @@ impl ReviewContract {
         rating: u32,
         comment: String,
     ) -> Result<Symbol, ReviewError> {
+        Self::require_not_paused(env)?;
         // Validate input
-        (if rating < 1 || rating > 5 {
+        if !(1..=5).contains(&rating) {
             return Err(ReviewError::InvalidRating);
-        });
+        }
         // Validate comment length (e.g., max 500 characters)
         if comment.len() > 500 {
             return Err(ReviewError::InvalidInput);
@@ -92,20 +126,166 @@ impl ReviewContract {
         };
         reviews.push_back(review);
         env.storage().persistent().set(&map_key, &reviews);
+        Self::extend_persistent_ttl(env, &map_key);
+        Self::extend_instance_ttl(env);
         Ok(booking_id)
     }
 
     pub fn get_reviews_for_user(env: &Env, user_did: Symbol) -> Vec<Review> {
         let key = StorageKey::ReviewMap(user_did);
-        env.storage()
-            .persistent()
-            .get(&key)
-            .unwrap_or_else(|| Vec::new(env))
+        match env.storage().persistent().get(&key) {
+            Some(reviews) => {
+                Self::extend_persistent_ttl(env, &key);
+                reviews
+            }
+            None => Vec::new(env),
+        }
     }
 
     pub fn get_reputation(env: &Env, user_did: Symbol) -> u32 {
         let key = StorageKey::ReputationScoresMap(user_did);
-        env.storage().persistent().get(&key).unwrap_or(0)
+        match env.storage().persistent().get(&key) {
+            Some(score) => {
+                Self::extend_persistent_ttl(env, &key);
+                score
+            }
+            None => 0,
+        }
+    }
+
+    /// Extends the TTL of the contract instance and of the review and
+    /// reputation entries of the given users. Admin only.
+    pub fn bump_ttl(env: &Env, user_dids: Vec<Symbol>) -> Result<(), ReviewError> {
+        Self::require_admin(env)?;
+
+        Self::extend_instance_ttl(env);
+        for user_did in user_dids.iter() {
+            let review_key = StorageKey::ReviewMap(user_did.clone());
+            if env.storage().persistent().has(&review_key) {
+                Self::extend_persistent_ttl(env, &review_key);
+            }
+            let reputation_key = StorageKey::ReputationScoresMap(user_did);
+            if env.storage().persistent().has(&reputation_key) {
+                Self::extend_persistent_ttl(env, &reputation_key);
+            }
+        }
+        Ok(())
+    }
+
+    /// Upgrades the contract code. Storage is kept as is, so `migrate` must be
+    /// called afterwards if the new code expects a newer schema version.
+    pub fn upgrade(env: &Env, new_wasm_hash: BytesN<32>) -> Result<(), ReviewError> {
+        Self::require_admin(env)?;
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+        Ok(())
+    }
+
+    pub fn version(env: &Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&StorageKey::Version)
+            .unwrap_or(0)
+    }
+
+    /// Migrates stored data to the schema version of the current code and
+    /// returns the resulting version. Admin only.
+    pub fn migrate(env: &Env) -> Result<u32, ReviewError> {
+        Self::require_admin(env)?;
+
+        let mut version = Self::version(env);
+        if version > SCHEMA_VERSION {
+            return Err(ReviewError::UnsupportedSchemaVersion);
+        }
+        while version < SCHEMA_VERSION {
+            match version {
+                // Contracts deployed before schema versioning was introduced
+                // already use the version 1 layout.
+                0 => {}
+                _ => return Err(ReviewError::UnsupportedSchemaVersion),
+            }
+            version += 1;
+        }
+
+        env.storage().instance().set(&StorageKey::Version, &version);
+        Self::extend_instance_ttl(env);
+        Ok(version)
+    }
+
+    /// Sets the guardian allowed to pause the contract. Admin only.
+    pub fn set_guardian(env: &Env, guardian: Address) -> Result<(), ReviewError> {
+        Self::require_admin(env)?;
+        env.storage()
+            .instance()
+            .set(&StorageKey::Guardian, &guardian);
+        Self::extend_instance_ttl(env);
+        Ok(())
+    }
+
+    /// Pauses review submission. Callable by the admin or the guardian.
+    pub fn pause(env: &Env, caller: Address) -> Result<(), ReviewError> {
+        caller.require_auth();
+
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&StorageKey::Admin)
+            .ok_or(ReviewError::NotInitialized)?;
+        let guardian: Option<Address> = env.storage().instance().get(&StorageKey::Guardian);
+        if caller != admin && Some(caller.clone()) != guardian {
+            return Err(ReviewError::Unauthorized);
+        }
+
+        env.storage().instance().set(&StorageKey::Paused, &true);
+        Self::extend_instance_ttl(env);
+        env.events().publish((symbol_short!("paused"),), caller);
+        Ok(())
+    }
+
+    /// Resumes review submission. Admin only.
+    pub fn unpause(env: &Env) -> Result<(), ReviewError> {
+        let admin = Self::require_admin(env)?;
+        env.storage().instance().set(&StorageKey::Paused, &false);
+        Self::extend_instance_ttl(env);
+        env.events().publish((symbol_short!("unpaused"),), admin);
+        Ok(())
+    }
+
+    pub fn is_paused(env: &Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&StorageKey::Paused)
+            .unwrap_or(false)
+    }
+
+    fn require_not_paused(env: &Env) -> Result<(), ReviewError> {
+        if Self::is_paused(env) {
+            return Err(ReviewError::ContractPaused);
+        }
+        Ok(())
+    }
+
+    fn require_admin(env: &Env) -> Result<Address, ReviewError> {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&StorageKey::Admin)
+            .ok_or(ReviewError::NotInitialized)?;
+        admin.require_auth();
+        Ok(admin)
+    }
+
+    fn extend_instance_ttl(env: &Env) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+    }
+
+    fn extend_persistent_ttl(env: &Env, key: &StorageKey) {
+        env.storage().persistent().extend_ttl(
+            key,
+            PERSISTENT_LIFETIME_THRESHOLD,
+            PERSISTENT_BUMP_AMOUNT,
+        );
     }
 }
 
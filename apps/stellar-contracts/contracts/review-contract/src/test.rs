@@ -1,13 +1,19 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::{Env, String, Symbol};
-use soroban_sdk::testutils::Ledger;
+use soroban_sdk::testutils::storage::{Instance as _, Persistent as _};
+use soroban_sdk::testutils::{Address as _, Events, Ledger};
+use soroban_sdk::{vec, Address, BytesN, Env, IntoVal, String, Symbol};
+
+// Release build of the contract at schema version 1, used as the upgrade target
+mod review_contract_v1 {
+    soroban_sdk::contractimport!(file = "fixtures/review_contract_v1.wasm");
+}
 
 #[test]
 fn test_submit_review_success() {
     let env = Env::default();
-    let contract_id = env.register(ReviewContract, ());
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
     let client = ReviewContractClient::new(&env, &contract_id);
 
     let booking_id = Symbol::new(&env, "booking1");
@@ -27,7 +33,7 @@ fn test_submit_review_success() {
 #[should_panic(expected = "Error(Contract, #1)")]
 fn test_submit_review_invalid_rating_zero() {
     let env = Env::default();
-    let contract_id = env.register(ReviewContract, ());
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
     let client = ReviewContractClient::new(&env, &contract_id);
 
     let booking_id = Symbol::new(&env, "booking1");
@@ -42,7 +48,7 @@ fn test_submit_review_invalid_rating_zero() {
 #[should_panic(expected = "Error(Contract, #1)")]
 fn test_submit_review_invalid_rating_six() {
     let env = Env::default();
-    let contract_id = env.register(ReviewContract, ());
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
     let client = ReviewContractClient::new(&env, &contract_id);
 
     let booking_id = Symbol::new(&env, "booking1");
@@ -56,7 +62,7 @@ fn test_submit_review_invalid_rating_six() {
 #[test]
 fn test_get_reviews_for_user() {
     let env = Env::default();
-    let contract_id = env.register(ReviewContract, ());
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
     let client = ReviewContractClient::new(&env, &contract_id);
 
     let target_did = Symbol::new(&env, "target1");
@@ -79,7 +85,7 @@ fn test_get_reviews_for_user() {
 #[test]
 fn test_get_reviews_for_user_empty() {
     let env = Env::default();
-    let contract_id = env.register(ReviewContract, ());
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
     let client = ReviewContractClient::new(&env, &contract_id);
 
     let user_did = Symbol::new(&env, "user_with_no_reviews");
@@ -90,7 +96,7 @@ fn test_get_reviews_for_user_empty() {
 #[test]
 fn test_get_reputation_default() {
     let env = Env::default();
-    let contract_id = env.register(ReviewContract, ());
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
     let client = ReviewContractClient::new(&env, &contract_id);
 
     let user_did = Symbol::new(&env, "user_with_no_reputation");
@@ -101,7 +107,7 @@ fn test_get_reputation_default() {
 #[test]
 fn test_multiple_users_isolated() {
     let env = Env::default();
-    let contract_id = env.register(ReviewContract, ());
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
     let client = ReviewContractClient::new(&env, &contract_id);
 
     let user1 = Symbol::new(&env, "user1");
@@ -126,7 +132,7 @@ fn test_multiple_users_isolated() {
 #[test]
 fn test_review_rating_values() {
     let env = Env::default();
-    let contract_id = env.register(ReviewContract, ());
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
     let client = ReviewContractClient::new(&env, &contract_id);
 
     let target_did = Symbol::new(&env, "target");
@@ -153,7 +159,7 @@ fn test_review_rating_values() {
 #[should_panic(expected = "Error(Contract, #2)")]
 fn test_duplicate_review_prevention() {
     let env = Env::default();
-    let contract_id = env.register(ReviewContract, ());
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
     let client = ReviewContractClient::new(&env, &contract_id);
 
     let booking_id = Symbol::new(&env, "booking1");
@@ -181,7 +187,7 @@ fn test_reentrancy_attack_prevention() {
     // In Soroban, direct reentrancy is not possible due to the runtime architecture.
     // We simulate a scenario where an external function could try to modify the state.
     let env = Env::default();
-    let contract_id = env.register(ReviewContract, ());
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
     let client = ReviewContractClient::new(&env, &contract_id);
     
     let booking_id = Symbol::new(&env, "booking1");
@@ -205,7 +211,7 @@ fn test_reentrancy_attack_prevention() {
 #[should_panic(expected = "Error(Contract, #1)")]
 fn test_integer_overflow_underflow() {
     let env = Env::default();
-    let contract_id = env.register(ReviewContract, ());
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
     let client = ReviewContractClient::new(&env, &contract_id);
     let booking_id = Symbol::new(&env, "booking1");
     let reviewer_did = Symbol::new(&env, "reviewer1");
@@ -227,7 +233,7 @@ fn test_timestamp_manipulation_resistance() {
         li.timestamp = u64::MAX; // Extreme timestamp
     });
     
-    let contract_id = env.register(ReviewContract, ());
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
     let client = ReviewContractClient::new(&env, &contract_id);
     
     let booking_id = Symbol::new(&env, "booking1");
@@ -252,7 +258,7 @@ fn test_timestamp_manipulation_resistance() {
 #[should_panic(expected = "Error(Contract, #4)")]
 fn test_unauthorized_access_attempts() {
     let env = Env::default();
-    let contract_id = env.register(ReviewContract, ());
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
     let client = ReviewContractClient::new(&env, &contract_id);
     let booking_id = Symbol::new(&env, "booking1");
     let empty_did = Symbol::new(&env, "");
@@ -269,7 +275,7 @@ fn test_unauthorized_access_attempts() {
 #[should_panic(expected = "Error(Contract, #4)")]
 fn test_invalid_state_transition_attempts() {
     let env = Env::default();
-    let contract_id = env.register(ReviewContract, ());
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
     let client = ReviewContractClient::new(&env, &contract_id);
     let booking_id = Symbol::new(&env, "booking1");
     let reviewer_did = Symbol::new(&env, "reviewer1");
@@ -286,7 +292,7 @@ fn test_invalid_state_transition_attempts() {
 #[should_panic(expected = "Error(Contract, #2)")]
 fn test_economic_attack_simulation() {
     let env = Env::default();
-    let contract_id = env.register(ReviewContract, ());
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
     let client = ReviewContractClient::new(&env, &contract_id);
     let booking_id = Symbol::new(&env, "booking1");
     let reviewer_did = Symbol::new(&env, "reviewer1");
@@ -304,7 +310,7 @@ fn test_economic_attack_simulation() {
 #[test]
 fn test_property_fuzzing() {
     let env = Env::default();
-    let contract_id = env.register(ReviewContract, ());
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
     let client = ReviewContractClient::new(&env, &contract_id);
     let mut success_count = 0;
     for i in 0u32..26 {
@@ -398,7 +404,7 @@ fn test_property_fuzzing() {
         let result = client.submit_review(&booking_id, &reviewer_did, &target_did, &rating, &comment);
         assert_eq!(result, booking_id);
         let reviews = client.get_reviews_for_user(&target_did);
-        assert!(reviews.len() > 0);
+        assert!(!reviews.is_empty());
         success_count += 1;
     }
     assert!(success_count > 0, "There must be valid cases in fuzzing");
@@ -415,7 +421,7 @@ fn test_cross_contract_interaction() {
     let env = Env::default();
     
     // Simulate complete flow: Property Listing -> Booking -> Review
-    let review_contract_id = env.register(ReviewContract, ());
+    let review_contract_id = env.register(ReviewContract, (Address::generate(&env),));
     let review_client = ReviewContractClient::new(&env, &review_contract_id);
     
     let booking_id = Symbol::new(&env, "booking1");
@@ -453,7 +459,7 @@ fn test_cross_contract_interaction() {
 #[test]
 fn test_gas_optimization_validation() {
     let env = Env::default();
-    let contract_id = env.register(ReviewContract, ());
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
     let client = ReviewContractClient::new(&env, &contract_id);
     
     let booking_id = Symbol::new(&env, "booking1");
@@ -481,8 +487,6 @@ fn test_gas_optimization_validation() {
     let reviews = client.get_reviews_for_user(&target_did);
     assert_eq!(reviews.len(), 2);
     
-    // Note: Soroban doesn't expose detailed gas metrics in test environment yet
-    assert!(true, "Gas validation completed - monitor in production");
 }
 
 // =========================
@@ -492,7 +496,7 @@ fn test_gas_optimization_validation() {
 #[test]
 fn test_deployment_validation_networks() {
     let env = Env::default();
-    let contract_id = env.register(ReviewContract, ());
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
     let client = ReviewContractClient::new(&env, &contract_id);
     
     // Basic functionality test (simulates deployment on any network)
@@ -511,8 +515,6 @@ fn test_deployment_validation_networks() {
     let _reputation = client.get_reputation(&target_did);
     // reputation is u32, always >= 0, so no need to check
     
-    // Note: For real deployment tests, CI/CD scripts are needed
-    assert!(true, "Deployment validation completed - use CI/CD for real tests");
 }
 
 // =========================
@@ -521,7 +523,7 @@ fn test_deployment_validation_networks() {
 #[test]
 fn test_edge_cases() {
     let env = Env::default();
-    let contract_id = env.register(ReviewContract, ());
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
     let client = ReviewContractClient::new(&env, &contract_id);
     
     // Test: Empty comment
@@ -554,3 +556,294 @@ fn test_edge_cases() {
     let reputation = client.get_reputation(&empty_user);
     assert_eq!(reputation, 0);
 }
+
+// =========================
+// Storage TTL Management
+// =========================
+#[test]
+fn test_review_ttl_extended_on_write() {
+    let env = Env::default();
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
+    let client = ReviewContractClient::new(&env, &contract_id);
+
+    let booking_id = Symbol::new(&env, "booking1");
+    let reviewer_did = Symbol::new(&env, "reviewer1");
+    let target_did = Symbol::new(&env, "target1");
+    let comment = String::from_str(&env, "Great experience!");
+
+    client.submit_review(&booking_id, &reviewer_did, &target_did, &5, &comment);
+
+    env.as_contract(&contract_id, || {
+        let key = StorageKey::ReviewMap(target_did.clone());
+        assert_eq!(
+            env.storage().persistent().get_ttl(&key),
+            PERSISTENT_BUMP_AMOUNT
+        );
+        assert_eq!(env.storage().instance().get_ttl(), INSTANCE_BUMP_AMOUNT);
+    });
+}
+
+#[test]
+fn test_review_ttl_extended_on_read() {
+    let env = Env::default();
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
+    let client = ReviewContractClient::new(&env, &contract_id);
+
+    let booking_id = Symbol::new(&env, "booking1");
+    let reviewer_did = Symbol::new(&env, "reviewer1");
+    let target_did = Symbol::new(&env, "target1");
+    let comment = String::from_str(&env, "Great experience!");
+
+    client.submit_review(&booking_id, &reviewer_did, &target_did, &5, &comment);
+
+    // Advance the ledger until the reviews drop below the bump threshold
+    let elapsed = PERSISTENT_BUMP_AMOUNT - PERSISTENT_LIFETIME_THRESHOLD + 1;
+    env.ledger().with_mut(|li| {
+        li.sequence_number += elapsed;
+    });
+
+    let key = StorageKey::ReviewMap(target_did.clone());
+    env.as_contract(&contract_id, || {
+        assert_eq!(
+            env.storage().persistent().get_ttl(&key),
+            PERSISTENT_LIFETIME_THRESHOLD - 1
+        );
+    });
+
+    let reviews = client.get_reviews_for_user(&target_did);
+    assert_eq!(reviews.len(), 1);
+
+    env.as_contract(&contract_id, || {
+        assert_eq!(
+            env.storage().persistent().get_ttl(&key),
+            PERSISTENT_BUMP_AMOUNT
+        );
+    });
+}
+
+#[test]
+fn test_bump_ttl_by_admin() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(ReviewContract, (&admin,));
+    let client = ReviewContractClient::new(&env, &contract_id);
+
+    let booking_id = Symbol::new(&env, "booking1");
+    let reviewer_did = Symbol::new(&env, "reviewer1");
+    let target_did = Symbol::new(&env, "target1");
+    let unknown_did = Symbol::new(&env, "unknown");
+    let comment = String::from_str(&env, "Great experience!");
+
+    client.submit_review(&booking_id, &reviewer_did, &target_did, &5, &comment);
+
+    env.ledger().with_mut(|li| {
+        li.sequence_number += 8 * DAY_IN_LEDGERS;
+    });
+
+    // Users without reviews are skipped
+    client.bump_ttl(&vec![&env, target_did.clone(), unknown_did]);
+
+    env.as_contract(&contract_id, || {
+        let key = StorageKey::ReviewMap(target_did.clone());
+        assert_eq!(
+            env.storage().persistent().get_ttl(&key),
+            PERSISTENT_BUMP_AMOUNT
+        );
+        assert_eq!(env.storage().instance().get_ttl(), INSTANCE_BUMP_AMOUNT);
+    });
+}
+
+// =========================
+// Upgrades and Schema Versioning
+// =========================
+#[test]
+fn test_version_after_deploy() {
+    let env = Env::default();
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
+    let client = ReviewContractClient::new(&env, &contract_id);
+
+    assert_eq!(client.version(), SCHEMA_VERSION);
+}
+
+#[test]
+fn test_upgrade_keeps_state() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
+    let client = ReviewContractClient::new(&env, &contract_id);
+
+    let booking_id = Symbol::new(&env, "booking1");
+    let reviewer_did = Symbol::new(&env, "reviewer1");
+    let target_did = Symbol::new(&env, "target1");
+    let comment = String::from_str(&env, "Great experience!");
+    client.submit_review(&booking_id, &reviewer_did, &target_did, &5, &comment);
+
+    let new_wasm_hash = env
+        .deployer()
+        .upload_contract_wasm(review_contract_v1::WASM);
+    client.upgrade(&new_wasm_hash);
+
+    // Calls now run the uploaded wasm against the existing storage
+    let upgraded = review_contract_v1::Client::new(&env, &contract_id);
+    assert_eq!(upgraded.version(), 1);
+
+    let reviews = upgraded.get_reviews_for_user(&target_did);
+    assert_eq!(reviews.len(), 1);
+    assert_eq!(reviews.get(0).unwrap().rating, 5);
+    assert_eq!(reviews.get(0).unwrap().comment, comment);
+
+    // Duplicate detection still sees the reviews written before the upgrade
+    let result = upgraded.try_submit_review(&booking_id, &reviewer_did, &target_did, &4, &comment);
+    assert!(result.is_err());
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_upgrade_requires_admin_auth() {
+    let env = Env::default();
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
+    let client = ReviewContractClient::new(&env, &contract_id);
+
+    // No auth mocked for the admin
+    client.upgrade(&BytesN::from_array(&env, &[0u8; 32]));
+}
+
+#[test]
+fn test_migrate_legacy_storage() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
+    let client = ReviewContractClient::new(&env, &contract_id);
+
+    // Simulate a deployment from before schema versioning
+    env.as_contract(&contract_id, || {
+        env.storage().instance().remove(&StorageKey::Version);
+    });
+    assert_eq!(client.version(), 0);
+
+    assert_eq!(client.migrate(), SCHEMA_VERSION);
+    assert_eq!(client.version(), SCHEMA_VERSION);
+
+    // Migrating again is a no-op
+    assert_eq!(client.migrate(), SCHEMA_VERSION);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #7)")]
+fn test_migrate_newer_schema() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
+    let client = ReviewContractClient::new(&env, &contract_id);
+
+    env.as_contract(&contract_id, || {
+        env.storage()
+            .instance()
+            .set(&StorageKey::Version, &(SCHEMA_VERSION + 1));
+    });
+
+    client.migrate();
+}
+
+// =========================
+// Emergency Pause
+// =========================
+#[test]
+#[should_panic(expected = "Error(Contract, #8)")]
+fn test_pause_blocks_submit_review() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(ReviewContract, (&admin,));
+    let client = ReviewContractClient::new(&env, &contract_id);
+
+    client.pause(&admin);
+
+    let booking_id = Symbol::new(&env, "booking1");
+    let reviewer_did = Symbol::new(&env, "reviewer1");
+    let target_did = Symbol::new(&env, "target1");
+    let comment = String::from_str(&env, "Great experience!");
+    client.submit_review(&booking_id, &reviewer_did, &target_did, &5, &comment);
+}
+
+#[test]
+fn test_views_work_while_paused() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(ReviewContract, (&admin,));
+    let client = ReviewContractClient::new(&env, &contract_id);
+
+    let booking_id = Symbol::new(&env, "booking1");
+    let reviewer_did = Symbol::new(&env, "reviewer1");
+    let target_did = Symbol::new(&env, "target1");
+    let comment = String::from_str(&env, "Great experience!");
+    client.submit_review(&booking_id, &reviewer_did, &target_did, &5, &comment);
+
+    client.pause(&admin);
+    assert!(client.is_paused());
+
+    let reviews = client.get_reviews_for_user(&target_did);
+    assert_eq!(reviews.len(), 1);
+    assert_eq!(client.get_reputation(&target_did), 0);
+}
+
+#[test]
+fn test_guardian_pause_and_admin_unpause() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(ReviewContract, (&admin,));
+    let client = ReviewContractClient::new(&env, &contract_id);
+
+    let guardian = Address::generate(&env);
+    client.set_guardian(&guardian);
+
+    client.pause(&guardian);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("paused"),).into_val(&env),
+                guardian.into_val(&env)
+            )
+        ]
+    );
+    assert!(client.is_paused());
+
+    client.unpause();
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (symbol_short!("unpaused"),).into_val(&env),
+                admin.into_val(&env)
+            )
+        ]
+    );
+    assert!(!client.is_paused());
+
+    // Submissions work again
+    let booking_id = Symbol::new(&env, "booking1");
+    let reviewer_did = Symbol::new(&env, "reviewer1");
+    let target_did = Symbol::new(&env, "target1");
+    let comment = String::from_str(&env, "Great experience!");
+    client.submit_review(&booking_id, &reviewer_did, &target_did, &5, &comment);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #9)")]
+fn test_pause_unauthorized() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(ReviewContract, (Address::generate(&env),));
+    let client = ReviewContractClient::new(&env, &contract_id);
+
+    let stranger = Address::generate(&env);
+    client.pause(&stranger);
+}
@@ -11,7 +11,7 @@ source "$SCRIPT_DIR/utils/network-config.sh"
 source "$SCRIPT_DIR/utils/test-helpers.sh"
 
 # Configuration
-CONTRACTS=("booking" "property-listing" "review-contract")
+CONTRACTS=("booking" "property-listing" "review-contract" "featured-auction")
 BUILD_DIR="$CONTRACTS_DIR/target"
 WASM_DIR="$BUILD_DIR/wasm32-unknown-unknown/release"
 
@@ -12,7 +12,7 @@ source "$SCRIPT_DIR/utils/test-helpers.sh"
 source "$SCRIPT_DIR/utils/contract-addresses.sh"
 
 # Configuration
-CONTRACTS=("booking" "property-listing" "review-contract")
+CONTRACTS=("booking" "property-listing" "review-contract" "featured-auction")
 WASM_DIR="$CONTRACTS_DIR/target/wasm32-unknown-unknown/release"
 DEPLOYMENT_LOG="$TESTS_DIR/logs/deployment-$(date +%Y%m%d-%H%M%S).log"
 
@@ -66,10 +66,17 @@ for contract in "${CONTRACTS[@]}"; do
     wasm_file="$WASM_DIR/${wasm_name}.wasm"
     contract_key="$contract"
     
+    # Constructor arguments. The admin is set at deployment, so no one can
+    # claim the contract before it is set up.
+    CONSTRUCTOR_ARGS=(--admin "$ACCOUNT_ID")
+    if [ "$contract" = "featured-auction" ]; then
+        CONSTRUCTOR_ARGS+=(--listing_contract "$(get_contract_address "property-listing")" --treasury "${TREASURY_ADDRESS:-$ACCOUNT_ID}")
+    fi
+    
     # Deploy contract
-    log_info "Running: stellar contract deploy --source-account testnet-key --wasm $wasm_file --network testnet --rpc-url https://soroban-testnet.stellar.org:443 --network-passphrase \"Test SDF Network ; September 2015\""
+    log_info "Running: stellar contract deploy --source-account testnet-key --wasm $wasm_file --network testnet --rpc-url https://soroban-testnet.stellar.org:443 --network-passphrase \"Test SDF Network ; September 2015\" -- ${CONSTRUCTOR_ARGS[*]}"
     
-    if DEPLOY_OUTPUT=$(stellar contract deploy --source-account testnet-key --wasm "$wasm_file" --network testnet --rpc-url https://soroban-testnet.stellar.org:443 --network-passphrase "Test SDF Network ; September 2015" 2>&1); then
+    if DEPLOY_OUTPUT=$(stellar contract deploy --source-account testnet-key --wasm "$wasm_file" --network testnet --rpc-url https://soroban-testnet.stellar.org:443 --network-passphrase "Test SDF Network ; September 2015" -- "${CONSTRUCTOR_ARGS[@]}" 2>&1); then
         # Extract contract ID from output
         CONTRACT_ID=$(echo "$DEPLOY_OUTPUT" | grep -o 'Contract ID: [A-Z0-9]*' | awk '{print $3}')
         
@@ -121,23 +128,7 @@ for contract in "${CONTRACTS[@]}"; do
     fi
 done
 
-# Initialize contracts (if needed)
-log_info "Initializing contracts..."
-
-# Initialize booking contract
 BOOKING_CONTRACT_ID=$(get_contract_address "booking")
-if [ "$BOOKING_CONTRACT_ID" != "null" ]; then
-    log_info "Initializing booking contract..."
-    if stellar contract invoke \
-        --global testnet \
-        --contract-id "$BOOKING_CONTRACT_ID" \
-        --function initialize \
-        &> /dev/null; then
-        log_success "Booking contract initialized"
-    else
-        log_warning "Booking contract initialization failed (may already be initialized)"
-    fi
-fi
 
 # Display deployment summary
 log_info "Deployment Summary"
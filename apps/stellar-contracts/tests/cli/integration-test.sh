@@ -49,7 +49,7 @@ log_info "  Review: $REVIEW_CONTRACT_ID"
 
 # Generate test data for integration tests
 PROPERTY_ID="PROP_INTEGRATION_$(date +%s)"
-PROPERTY_DATA_HASH="integration_hash_$(date +%s)"
+PROPERTY_DATA_HASH="$(hex_hash "integration_hash_$(date +%s)")"
 PROPERTY_OWNER="GALAXY_INTEGRATION_OWNER_$(date +%s)"
 USER_ID="USER_INTEGRATION_$(date +%s)"
 BOOKING_ID=""
@@ -176,11 +176,11 @@ MULTI_USER2="USER_MULTI2_$(date +%s)"
 
 # Create two properties
 run_test "Multi-user - Create property 1" \
-    "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function create_listing -- '$MULTI_PROPERTY1' 'hash1_$(date +%s)' '$MULTI_OWNER1'" \
+    "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function create_listing -- '$MULTI_PROPERTY1' '$(hex_hash hash1_$(date +%s))' '$MULTI_OWNER1'" \
     "success"
 
 run_test "Multi-user - Create property 2" \
-    "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function create_listing -- '$MULTI_PROPERTY2' 'hash2_$(date +%s)' '$MULTI_OWNER2'" \
+    "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function create_listing -- '$MULTI_PROPERTY2' '$(hex_hash hash2_$(date +%s))' '$MULTI_OWNER2'" \
     "success"
 
 # Create bookings for both properties
@@ -213,7 +213,7 @@ CONFLICT_USER2="USER_CONFLICT2_$(date +%s)"
 
 # Create property
 run_test "Conflict test - Create property" \
-    "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function create_listing -- '$CONFLICT_PROPERTY' 'conflict_hash_$(date +%s)' '$CONFLICT_OWNER'" \
+    "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function create_listing -- '$CONFLICT_PROPERTY' '$(hex_hash conflict_hash_$(date +%s))' '$CONFLICT_OWNER'" \
     "success"
 
 # Create first booking
@@ -246,7 +246,7 @@ CONSISTENCY_USER="USER_CONSISTENCY_$(date +%s)"
 
 # Create property
 run_test "Consistency test - Create property" \
-    "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function create_listing -- '$CONSISTENCY_PROPERTY' 'consistency_hash_$(date +%s)' '$CONSISTENCY_OWNER'" \
+    "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function create_listing -- '$CONSISTENCY_PROPERTY' '$(hex_hash consistency_hash_$(date +%s))' '$CONSISTENCY_OWNER'" \
     "success"
 
 # Create booking
@@ -269,7 +269,7 @@ if [ -n "$CONSISTENCY_BOOKING_ID" ]; then
         "success"
     
     run_test "Consistency test - Verify property bookings" \
-        "stellar contract invoke --global testnet --contract-id $BOOKING_CONTRACT_ID --function get_property_bookings -- '$CONSISTENCY_PROPERTY'" \
+        "stellar contract invoke --global testnet --contract-id $BOOKING_CONTRACT_ID --function get_property_bookings -- '$CONSISTENCY_PROPERTY' 0 10" \
         "success"
 else
     log_error "Failed to create consistency booking"
@@ -304,7 +304,7 @@ for i in {1..10}; do
     
     # Create property
     run_test "Load test - Create property $i" \
-        "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function create_listing -- '$LOAD_PROPERTY' 'load_hash${i}_$(date +%s)' '$LOAD_OWNER'" \
+        "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function create_listing -- '$LOAD_PROPERTY' '$(hex_hash load_hash${i}_$(date +%s))' '$LOAD_OWNER'" \
         "success"
     
     # Create booking
@@ -322,7 +322,7 @@ SYNC_USER="USER_SYNC_$(date +%s)"
 
 # Create property
 run_test "Sync test - Create property" \
-    "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function create_listing -- '$SYNC_PROPERTY' 'sync_hash_$(date +%s)' '$SYNC_OWNER'" \
+    "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function create_listing -- '$SYNC_PROPERTY' '$(hex_hash sync_hash_$(date +%s))' '$SYNC_OWNER'" \
     "success"
 
 # Create booking
@@ -369,7 +369,7 @@ JOURNEY_USER="USER_JOURNEY_$(date +%s)"
 
 # Step 1: Host lists property
 run_test "Journey - Host lists property" \
-    "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function create_listing -- '$JOURNEY_PROPERTY' 'journey_hash_$(date +%s)' '$JOURNEY_OWNER'" \
+    "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function create_listing -- '$JOURNEY_PROPERTY' '$(hex_hash journey_hash_$(date +%s))' '$JOURNEY_OWNER'" \
     "success"
 
 # Step 2: Guest searches and finds property
@@ -422,7 +422,6 @@ log_info "Creating expected test results..."
 cat > "$FIXTURES_DIR/expected-results.json" << 'EOF'
 {
   "booking_contract": {
-    "initialize": "success",
     "create_booking": "success",
     "check_availability": "success",
     "cancel_booking": "success",
@@ -91,9 +91,13 @@ run_test "Update status to completed" \
 # Test 7: Get property bookings
 log_info "Test 7: Get property bookings"
 run_test "Get property bookings" \
-    "stellar contract invoke --global testnet --contract-id $BOOKING_CONTRACT_ID --function get_property_bookings -- '$PROPERTY_ID'" \
+    "stellar contract invoke --global testnet --contract-id $BOOKING_CONTRACT_ID --function get_property_bookings -- '$PROPERTY_ID' 0 10" \
     "success"
 
+run_test "Get property bookings with zero limit" \
+    "stellar contract invoke --global testnet --contract-id $BOOKING_CONTRACT_ID --function get_property_bookings -- '$PROPERTY_ID' 0 0" \
+    "failure"
+
 # Test 8: Set escrow ID
 log_info "Test 8: Set escrow ID"
 ESCROW_ID="ESCROW_$(date +%s)"
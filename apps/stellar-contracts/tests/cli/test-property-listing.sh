@@ -33,7 +33,7 @@ log_success "Property listing contract ID: $PROPERTY_CONTRACT_ID"
 
 # Generate test data
 PROPERTY_ID="PROP_LISTING_$(date +%s)"
-DATA_HASH="hash_$(date +%s)_$(shasum -a 256 <<< "test_property_data" | cut -d' ' -f1)"
+DATA_HASH="$(hex_hash "test_property_data_$(date +%s)")"
 OWNER_ADDRESS="GALAXY_TEST_OWNER_$(date +%s)"
 
 log_info "Test data:"
@@ -55,7 +55,7 @@ run_test "Get property listing" \
 
 # Test 3: Update property listing
 log_info "Test 3: Update property listing"
-NEW_DATA_HASH="updated_hash_$(date +%s)_$(shasum -a 256 <<< "updated_property_data" | cut -d' ' -f1)"
+NEW_DATA_HASH="$(hex_hash "updated_property_data_$(date +%s)")"
 run_test "Update property listing" \
     "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function update_listing -- '$PROPERTY_ID' '$NEW_DATA_HASH' '$OWNER_ADDRESS'" \
     "success"
@@ -114,8 +114,8 @@ run_test "Get non-existent listing" \
 log_info "Test 12: Multiple listings from same owner"
 MULTI_PROPERTY1="PROP_MULTI1_$(date +%s)"
 MULTI_PROPERTY2="PROP_MULTI2_$(date +%s)"
-MULTI_HASH1="multi_hash1_$(date +%s)"
-MULTI_HASH2="multi_hash2_$(date +%s)"
+MULTI_HASH1="$(hex_hash "multi_hash1_$(date +%s)")"
+MULTI_HASH2="$(hex_hash "multi_hash2_$(date +%s)")"
 
 run_test "Multiple listings - first" \
     "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function create_listing -- '$MULTI_PROPERTY1' '$MULTI_HASH1' '$OWNER_ADDRESS'" \
@@ -128,7 +128,7 @@ run_test "Multiple listings - second" \
 # Test 13: Multiple listings from different owners
 log_info "Test 13: Multiple listings from different owners"
 DIFF_OWNER_PROPERTY="PROP_DIFF_OWNER_$(date +%s)"
-DIFF_OWNER_HASH="diff_owner_hash_$(date +%s)"
+DIFF_OWNER_HASH="$(hex_hash "diff_owner_hash_$(date +%s)")"
 DIFF_OWNER_ADDRESS="GALAXY_DIFF_OWNER_$(date +%s)"
 
 run_test "Different owner listing" \
@@ -150,26 +150,26 @@ run_test "Empty data hash" \
 
 # Test 16: Edge case - maximum length property ID
 log_info "Test 16: Edge case - maximum length property ID"
-MAX_LENGTH_ID="PROP_MAX_LENGTH_$(date +%s)_$(printf 'A%.0s' {1..50})" # 50+ character ID
-MAX_LENGTH_HASH="max_length_hash_$(date +%s)"
+MAX_LENGTH_ID="PROP_MAX_LENGTH_$(date +%s)_$(printf 'A%.0s' {1..37})" # 64 character ID
+MAX_LENGTH_HASH="$(hex_hash "max_length_hash_$(date +%s)")"
 
 run_test "Maximum length property ID" \
     "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function create_listing -- '$MAX_LENGTH_ID' '$MAX_LENGTH_HASH' '$OWNER_ADDRESS'" \
     "success"
 
-# Test 17: Edge case - maximum length data hash
-log_info "Test 17: Edge case - maximum length data hash"
+# Test 17: Edge case - data hash longer than 32 bytes
+log_info "Test 17: Edge case - data hash longer than 32 bytes"
 MAX_HASH_PROPERTY_ID="PROP_MAX_HASH_$(date +%s)"
-MAX_LENGTH_DATA_HASH="$(printf 'H%.0s' {1..100})" # 100 character hash
+LONG_DATA_HASH="${DATA_HASH}${DATA_HASH}" # 64 bytes
 
-run_test "Maximum length data hash" \
-    "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function create_listing -- '$MAX_HASH_PROPERTY_ID' '$MAX_LENGTH_DATA_HASH' '$OWNER_ADDRESS'" \
-    "success"
+run_test "Data hash longer than 32 bytes" \
+    "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function create_listing -- '$MAX_HASH_PROPERTY_ID' '$LONG_DATA_HASH' '$OWNER_ADDRESS'" \
+    "failure"
 
 # Test 18: Status transition validation
 log_info "Test 18: Status transition validation"
 STATUS_PROPERTY_ID="PROP_STATUS_TEST_$(date +%s)"
-STATUS_HASH="status_hash_$(date +%s)"
+STATUS_HASH="$(hex_hash "status_hash_$(date +%s)")"
 
 # Create listing
 run_test "Status test - create listing" \
@@ -190,7 +190,7 @@ done
 # Test 19: Rapid status changes
 log_info "Test 19: Rapid status changes"
 RAPID_PROPERTY_ID="PROP_RAPID_$(date +%s)"
-RAPID_HASH="rapid_hash_$(date +%s)"
+RAPID_HASH="$(hex_hash "rapid_hash_$(date +%s)")"
 
 # Create listing
 run_test "Rapid changes - create listing" \
@@ -209,8 +209,8 @@ done
 log_info "Test 20: Concurrent listing creation simulation"
 CONCURRENT_PROPERTY1="PROP_CONCURRENT1_$(date +%s)"
 CONCURRENT_PROPERTY2="PROP_CONCURRENT2_$(date +%s)"
-CONCURRENT_HASH1="concurrent_hash1_$(date +%s)"
-CONCURRENT_HASH2="concurrent_hash2_$(date +%s)"
+CONCURRENT_HASH1="$(hex_hash "concurrent_hash1_$(date +%s)")"
+CONCURRENT_HASH2="$(hex_hash "concurrent_hash2_$(date +%s)")"
 
 # Create two listings simultaneously (simulated)
 run_test "Concurrent listing 1" \
@@ -224,7 +224,7 @@ run_test "Concurrent listing 2" \
 # Test 21: Data integrity verification
 log_info "Test 21: Data integrity verification"
 INTEGRITY_PROPERTY_ID="PROP_INTEGRITY_$(date +%s)"
-INTEGRITY_HASH="integrity_hash_$(date +%s)"
+INTEGRITY_HASH="$(hex_hash "integrity_hash_$(date +%s)")"
 
 # Create listing
 run_test "Integrity test - create listing" \
@@ -232,7 +232,7 @@ run_test "Integrity test - create listing" \
     "success"
 
 # Update with new hash
-NEW_INTEGRITY_HASH="new_integrity_hash_$(date +%s)"
+NEW_INTEGRITY_HASH="$(hex_hash "new_integrity_hash_$(date +%s)")"
 run_test "Integrity test - update hash" \
     "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function update_listing -- '$INTEGRITY_PROPERTY_ID' '$NEW_INTEGRITY_HASH' '$OWNER_ADDRESS'" \
     "success"
@@ -245,24 +245,32 @@ run_test "Integrity test - verify update" \
 # Test 22: Owner address validation
 log_info "Test 22: Owner address validation"
 INVALID_OWNER_PROPERTY_ID="PROP_INVALID_OWNER_$(date +%s)"
-INVALID_OWNER_HASH="invalid_owner_hash_$(date +%s)"
+INVALID_OWNER_HASH="$(hex_hash "invalid_owner_hash_$(date +%s)")"
 INVALID_OWNER_ADDRESS="INVALID_ADDRESS_FORMAT"
 
 run_test "Invalid owner address" \
     "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function create_listing -- '$INVALID_OWNER_PROPERTY_ID' '$INVALID_OWNER_HASH' '$INVALID_OWNER_ADDRESS'" \
     "failure"
 
-# Test 23: Get all listings (if function exists)
+# Test 23: Get all listings, one page at a time
 log_info "Test 23: Get all listings"
-run_test "Get all listings" \
-    "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function get_all_listings" \
+run_test "Get first page of listings" \
+    "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function get_all_listings -- 0 10" \
+    "success"
+
+run_test "Get next page of listings" \
+    "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function get_all_listings -- 10 10" \
     "success"
 
+run_test "Get listings with zero limit" \
+    "stellar contract invoke --global testnet --contract-id $PROPERTY_CONTRACT_ID --function get_all_listings -- 0 0" \
+    "failure"
+
 # Performance test - multiple listings
 log_info "Performance test: Multiple listings creation"
 for i in {1..10}; do
     PERF_PROPERTY_ID="PROP_PERF${i}_$(date +%s)"
-    PERF_HASH="perf_hash${i}_$(date +%s)"
+    PERF_HASH="$(hex_hash "perf_hash${i}_$(date +%s)")"
     PERF_OWNER="GALAXY_PERF_OWNER${i}_$(date +%s)"
     
     run_test "Performance test - listing $i" \
@@ -115,6 +115,12 @@ generate_test_data() {
     esac
 }
 
+# Hex encoded SHA-256 digest (32 bytes) of the given data, as passed for
+# listing data hashes
+hex_hash() {
+    shasum -a 256 <<< "$1" | cut -d' ' -f1
+}
+
 # Print test summary
 print_test_summary() {
     echo